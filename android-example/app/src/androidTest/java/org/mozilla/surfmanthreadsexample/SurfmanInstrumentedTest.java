@@ -16,14 +16,22 @@ import static org.junit.Assert.*;
  */
 @RunWith(AndroidJUnit4.class)
 public class SurfmanInstrumentedTest {
+    private static native void testConnectionLifetime();
     private static native void testContextCreation();
+    private static native void testContextDescriptorEnumeration();
+    private static native void testContextNegotiation();
+    private static native void testContextPriorityAndReleaseBehavior();
     private static native void testCrossDeviceSurfaceTextureBlitFramebuffer();
     private static native void testCrossThreadSurfaceTextureBlitFramebuffer();
     private static native void testDeviceAccessors();
     private static native void testDeviceCreation();
     private static native void testGenericSurfaceCreation();
     private static native void testGL();
+    private static native void testModernCompatibilityProfile();
+    private static native void testMultisampledSurface();
     private static native void testNewlyCreatedContextsAreNotCurrent();
+    private static native void testNoConfigContext();
+    private static native void testSurfaceFormats();
     private static native void testSurfaceTextureBlitFramebuffer();
     private static native void testSurfaceTextureRightSideUp();
 
@@ -39,11 +47,31 @@ public class SurfmanInstrumentedTest {
         assertEquals("org.mozilla.surfmanthreadsexample", appContext.getPackageName());
     }
 
+    @Test
+    public void connectionLifetime() {
+        testConnectionLifetime();
+    }
+
     @Test
     public void contextCreation() {
         testContextCreation();
     }
 
+    @Test
+    public void contextDescriptorEnumeration() {
+        testContextDescriptorEnumeration();
+    }
+
+    @Test
+    public void contextNegotiation() {
+        testContextNegotiation();
+    }
+
+    @Test
+    public void contextPriorityAndReleaseBehavior() {
+        testContextPriorityAndReleaseBehavior();
+    }
+
     @Test
     public void crossDeviceSurfaceTextureBlitFramebuffer() {
         testCrossDeviceSurfaceTextureBlitFramebuffer();
@@ -74,11 +102,31 @@ public class SurfmanInstrumentedTest {
         testGL();
     }
 
+    @Test
+    public void modernCompatibilityProfile() {
+        testModernCompatibilityProfile();
+    }
+
+    @Test
+    public void multisampledSurface() {
+        testMultisampledSurface();
+    }
+
     @Test
     public void newlyCreatedContextsAreNotCurrent() {
         testNewlyCreatedContextsAreNotCurrent();
     }
 
+    @Test
+    public void noConfigContext() {
+        testNoConfigContext();
+    }
+
+    @Test
+    public void surfaceFormats() {
+        testSurfaceFormats();
+    }
+
     @Test
     public void surfaceTextureBlitFramebuffer() {
         testSurfaceTextureBlitFramebuffer();
@@ -71,6 +71,14 @@ pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanThre
 
 // NB: New tests should be added here.
 
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testConnectionLifetime(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_connection_lifetime();
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testContextCreation(
     _env: JNIEnv,
@@ -79,6 +87,30 @@ pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInst
     tests::test_context_creation();
 }
 
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testContextDescriptorEnumeration(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_context_descriptor_enumeration();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testContextNegotiation(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_context_negotiation();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testContextPriorityAndReleaseBehavior(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_context_priority_and_release_behavior();
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testCrossDeviceSurfaceTextureBlitFramebuffer(
     _env: JNIEnv,
@@ -127,6 +159,22 @@ pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInst
     tests::test_gl();
 }
 
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testModernCompatibilityProfile(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_modern_compatibility_profile();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testMultisampledSurface(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_multisampled_surface();
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testNewlyCreatedContextsAreNotCurrent(
     _env: JNIEnv,
@@ -135,6 +183,22 @@ pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInst
     tests::test_newly_created_contexts_are_not_current();
 }
 
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testNoConfigContext(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_no_config_context();
+}
+
+#[no_mangle]
+pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testSurfaceFormats(
+    _env: JNIEnv,
+    _class: JClass,
+) {
+    tests::test_surface_formats();
+}
+
 #[no_mangle]
 pub unsafe extern "system" fn Java_org_mozilla_surfmanthreadsexample_SurfmanInstrumentedTest_testSurfaceTextureBlitFramebuffer(
     _env: JNIEnv,
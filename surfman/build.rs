@@ -24,7 +24,9 @@ fn main() {
         angle: { all(windows, feature = "sm-angle") },
         angle_builtin: { all(windows, feature = "sm-angle-builtin") },
         angle_default: { all(windows, feature = "sm-angle-default") },
+        gbm: { all(linux, feature = "sm-gbm") },
         no_wgl: { all(windows, feature = "sm-no-wgl") },
+        osmesa: { all(linux, feature = "sm-osmesa") },
         wayland_default: { all(linux, feature = "sm-wayland-default") },
         x11: { all(linux, feature = "sm-x11") },
     }
@@ -38,19 +40,29 @@ fn main() {
         || (target_os == "windows" && cfg!(feature = "sm-angle"))
         || target_family.as_ref().map_or(false, |f| f == "unix")
     {
-        let mut file = File::create(&dest.join("egl_bindings.rs")).unwrap();
+        let mut file = File::create(dest.join("egl_bindings.rs")).unwrap();
         let registry = Registry::new(Api::Egl, (1, 5), Profile::Core, Fallbacks::All, []);
         registry.write_bindings(StructGenerator, &mut file).unwrap();
     }
 
     // Generate GL bindings.
     if target_os == "android" {
-        let mut file = File::create(&dest.join("gl_bindings.rs")).unwrap();
+        let mut file = File::create(dest.join("gl_bindings.rs")).unwrap();
         let registry = Registry::new(Api::Gles2, (3, 0), Profile::Core, Fallbacks::All, []);
         registry.write_bindings(StructGenerator, &mut file).unwrap();
     } else {
-        let mut file = File::create(&dest.join("gl_bindings.rs")).unwrap();
+        let mut file = File::create(dest.join("gl_bindings.rs")).unwrap();
         let registry = Registry::new(Api::Gl, (3, 3), Profile::Core, Fallbacks::All, []);
         registry.write_bindings(StructGenerator, &mut file).unwrap();
     }
+
+    // Generate GLES bindings for the desktop Linux tests, which create both OpenGL and OpenGL ES
+    // contexts. Build scripts can't tell whether tests are being built, so this only matches the
+    // `linux` half of the `#[cfg(all(linux, test))]` that includes them. `cfg_aliases!` defines
+    // the matcher macro used here to evaluate aliases.
+    if __cfg_aliases_matcher__!(linux) {
+        let mut file = File::create(dest.join("gles_bindings.rs")).unwrap();
+        let registry = Registry::new(Api::Gles2, (3, 0), Profile::Core, Fallbacks::All, []);
+        registry.write_bindings(StructGenerator, &mut file).unwrap();
+    }
 }
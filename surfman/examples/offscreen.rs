@@ -15,7 +15,7 @@ use std::mem;
 use std::path::Path;
 use std::slice;
 use surfman::{Connection, ContextAttributeFlags, ContextAttributes, GLApi, GLVersion};
-use surfman::{SurfaceAccess, SurfaceType};
+use surfman::{SurfaceAccess, SurfaceFormat, SurfaceType};
 
 mod common;
 
@@ -103,6 +103,7 @@ fn main() {
     let context_attributes = ContextAttributes {
         version: GLVersion::new(3, 3),
         flags: ContextAttributeFlags::empty(),
+        ..ContextAttributes::default()
     };
     let context_descriptor = device
         .create_context_descriptor(&context_attributes)
@@ -114,6 +115,7 @@ fn main() {
             SurfaceAccess::GPUOnly,
             SurfaceType::Generic {
                 size: Size2D::new(FRAMEBUFFER_WIDTH, FRAMEBUFFER_HEIGHT),
+                format: SurfaceFormat::RGBA8,
             },
         )
         .unwrap();
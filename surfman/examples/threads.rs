@@ -15,7 +15,7 @@ use surfman::{Adapter, Connection, Context, ContextDescriptor, Device, GLApi, Su
 use self::common::FilesystemResourceLoader;
 
 #[cfg(not(target_os = "android"))]
-use surfman::{ContextAttributeFlags, ContextAttributes, GLVersion};
+use surfman::{ContextAttributeFlags, ContextAttributes, GLVersion, SurfaceFormat};
 #[cfg(not(target_os = "android"))]
 use winit::{
     dpi::PhysicalSize,
@@ -109,6 +109,7 @@ fn main() {
     let context_attributes = ContextAttributes {
         version: GLVersion::new(3, 0),
         flags: ContextAttributeFlags::ALPHA,
+        ..ContextAttributes::default()
     };
     let context_descriptor = device
         .create_context_descriptor(&context_attributes)
@@ -404,7 +405,10 @@ fn worker_thread(
 ) {
     // Open the device, create a context, and make it current.
     let size = Size2D::new(SUBSCREEN_WIDTH, SUBSCREEN_HEIGHT);
-    let surface_type = SurfaceType::Generic { size };
+    let surface_type = SurfaceType::Generic {
+        size,
+        format: SurfaceFormat::RGBA8,
+    };
     let mut device = connection.create_device(&adapter).unwrap();
     let mut context = device.create_context(&context_descriptor, None).unwrap();
     let surface = device
@@ -440,7 +444,10 @@ fn worker_thread(
     let mut theta_z = INITIAL_ROTATION_Z;
 
     // Send an initial surface back to the main thread.
-    let surface_type = SurfaceType::Generic { size };
+    let surface_type = SurfaceType::Generic {
+        size,
+        format: SurfaceFormat::RGBA8,
+    };
     let surface = Some(
         device
             .create_surface(&context, SurfaceAccess::GPUOnly, surface_type)
@@ -22,7 +22,7 @@
 
 #![allow(missing_docs)]
 
-use crate::{ContextID, Error, SurfaceAccess, SurfaceInfo, SurfaceType};
+use crate::{ContextID, Error, SurfaceAccess, SurfaceFormat, SurfaceInfo, SurfaceType};
 use crate::device::Device as DeviceAPI;
 use euclid::default::Size2D;
 use fnv::{FnvHashMap, FnvHashSet};
@@ -173,7 +173,10 @@ impl<Device: DeviceAPI> SwapChainData<Device> {
                     "Creating a new surface ({:?}) for context {:?}",
                     self.size, self.context_id
                 );
-                let surface_type = SurfaceType::Generic { size: self.size };
+                let surface_type = SurfaceType::Generic {
+                    size: self.size,
+                    format: SurfaceFormat::RGBA8,
+                };
                 device.create_surface(context, self.surface_access, surface_type)
             })?;
 
@@ -269,7 +272,10 @@ impl<Device: DeviceAPI> SwapChainData<Device> {
         if (size.width < 1) || (size.height < 1) {
             return Err(Error::Failed);
         }
-        let surface_type = SurfaceType::Generic { size };
+        let surface_type = SurfaceType::Generic {
+            size,
+            format: SurfaceFormat::RGBA8,
+        };
         let new_back_buffer = device.create_surface(context, self.surface_access, surface_type)?;
         let mut old_back_buffer = self.back_buffer.take_surface(device, context)?;
         self.back_buffer
@@ -606,7 +612,10 @@ impl<Device: DeviceAPI> SwapChain<Device> {
         surface_access: SurfaceAccess,
         size: Size2D<i32>,
     ) -> Result<SwapChain<Device>, Error> {
-        let surface_type = SurfaceType::Generic { size };
+        let surface_type = SurfaceType::Generic {
+            size,
+            format: SurfaceFormat::RGBA8,
+        };
         let surface = device.create_surface(context, surface_access, surface_type)?;
         Ok(SwapChain(Arc::new(Mutex::new(SwapChainData {
             size,
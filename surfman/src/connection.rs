@@ -3,7 +3,7 @@
 //! The abstract interface that all connections conform to.
 
 use crate::Error;
-use crate::GLApi;
+use crate::{AdapterInfo, GLApi};
 
 use euclid::default::Size2D;
 
@@ -48,6 +48,9 @@ pub trait Connection: Sized {
     /// Returns the "best" adapter on this system, preferring software adapters.
     fn create_software_adapter(&self) -> Result<Self::Adapter, Error>;
 
+    /// Returns information about the hardware that the given adapter renders with.
+    fn adapter_info(&self, adapter: &Self::Adapter) -> Result<AdapterInfo, Error>;
+
     /// Opens a device.
     fn create_device(&self, adapter: &Self::Adapter) -> Result<Self::Device, Error>;
 
@@ -4,13 +4,15 @@
 
 #![allow(unused_imports)]
 
+use crate::device::Device as DeviceInterface;
 use crate::gl;
-use crate::gl::types::GLuint;
-use crate::info::GLVersion;
-use crate::Gl;
+use crate::gl::types::{GLenum, GLint, GLuint};
+use crate::info::{GLApi, GLVersion};
+use crate::{Error, Gl};
 
 use std::ffi::CStr;
-use std::os::raw::c_char;
+use std::mem;
+use std::os::raw::{c_char, c_void};
 use std::sync::Mutex;
 
 /// A unique ID among all currently-allocated contexts.
@@ -32,7 +34,7 @@ bitflags! {
     /// https://www.khronos.org/registry/webgl/specs/latest/1.0/#WEBGLCONTEXTATTRIBUTES
     ///
     /// There are some extra `surfman`-specific flags as well.
-    pub struct ContextAttributeFlags: u8 {
+    pub struct ContextAttributeFlags: u16 {
         /// Surfaces created for this context will have an alpha channel (RGBA or BGRA; i.e. 4
         /// channels, 32 bits per pixel, 8 bits per channel). If this is not present, surfaces will
         /// be RGBX or BGRX (i.e. 3 channels, 32 bits per pixel, 8 bits per channel).
@@ -45,9 +47,93 @@ bitflags! {
         /// The OpenGL compatibility profile will be used. If this is not present, the core profile
         /// is used.
         const COMPATIBILITY_PROFILE = 0x08;
+        /// Out-of-bounds buffer and texture accesses will have well-defined results instead of
+        /// crashing or reading other data. This is typically needed to run untrusted content such
+        /// as WebGL.
+        const ROBUST_ACCESS         = 0x10;
+        /// The context will be lost when the GPU is reset, and the reset will be reported by
+        /// `Device::context_reset_status()`. If this is not present, no reset notification is
+        /// given, and the context's behavior after a reset is undefined.
+        const LOSE_CONTEXT_ON_RESET = 0x20;
+        /// A debug context will be created, and the messages it generates will be delivered to the
+        /// device's debug message callback, or logged via the `log` crate if there is none.
+        /// Debug contexts may be slower than ordinary ones.
+        const DEBUG                 = 0x40;
+        /// Deprecated OpenGL functionality will be removed from the context. This only applies to
+        /// OpenGL 3.0 and later; OpenGL ES has no deprecated functionality.
+        const FORWARD_COMPATIBLE    = 0x80;
+        /// The context will not report errors, and the behavior of erroneous commands will be
+        /// undefined. This can reduce driver overhead in well-tested code. It can't be combined
+        /// with `ROBUST_ACCESS`, `LOSE_CONTEXT_ON_RESET`, or `DEBUG`; requesting those together
+        /// fails with `Error::IncompatibleContextAttributeFlags`.
+        const NO_ERROR              = 0x100;
+        /// The context will be created without a pixel format, so it can be made current with
+        /// surfaces of any pixel format on the device. Surfaces that `surfman` creates for the
+        /// context still use the pixel format of its descriptor. This requires
+        /// `EGL_KHR_no_config_context`.
+        const NO_CONFIG             = 0x200;
     }
 }
 
+/// Whether a context has been lost because of a GPU reset, as returned by
+/// `Device::context_reset_status()`.
+///
+/// Once a reset has been reported, the context is unusable. It must be destroyed and created anew,
+/// along with all of its OpenGL objects.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ContextResetStatus {
+    /// The context has not been reset.
+    NoError,
+    /// The context was reset, and this context caused it.
+    GuiltyContextReset,
+    /// The context was reset, but something other than this context caused it.
+    InnocentContextReset,
+    /// The context was reset for an unknown reason.
+    UnknownContextReset,
+}
+
+// These aren't in the OpenGL 3.3 and OpenGL ES 3.0 headers that our bindings are generated from.
+#[allow(dead_code)]
+pub(crate) const GL_GUILTY_CONTEXT_RESET: GLenum = 0x8253;
+#[allow(dead_code)]
+pub(crate) const GL_INNOCENT_CONTEXT_RESET: GLenum = 0x8254;
+#[allow(dead_code)]
+pub(crate) const GL_UNKNOWN_CONTEXT_RESET: GLenum = 0x8255;
+const GL_CONTEXT_FLAGS: GLenum = 0x821e;
+const GL_CONTEXT_FLAG_FORWARD_COMPATIBLE_BIT: GLint = 0x1;
+const GL_CONTEXT_FLAG_DEBUG_BIT: GLint = 0x2;
+const GL_CONTEXT_FLAG_ROBUST_ACCESS_BIT: GLint = 0x4;
+const GL_CONTEXT_FLAG_NO_ERROR_BIT: GLint = 0x8;
+const GL_CONTEXT_RELEASE_BEHAVIOR: GLenum = 0x82fb;
+const GL_RESET_NOTIFICATION_STRATEGY: GLenum = 0x8256;
+const GL_LOSE_CONTEXT_ON_RESET: GLint = 0x8252;
+
+/// How the GPU should schedule the work of a context relative to that of other contexts.
+///
+/// Priority is a hint: if the driver doesn't support priorities, or the process lacks permission
+/// to use the requested priority, the context gets a different one. The priority that was actually
+/// assigned is reported by `Device::context_descriptor_attributes()` for the descriptor returned
+/// by `Device::context_descriptor()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ContextPriority {
+    /// Background work that should yield to other contexts.
+    Low,
+    /// The default priority.
+    Medium,
+    /// Latency-sensitive work, such as drawing user interfaces.
+    High,
+}
+
+/// What happens to the pending commands of a context when it stops being current.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ContextReleaseBehavior {
+    /// Pending commands are flushed. This is the default.
+    Flush,
+    /// Pending commands are left as they are. This makes switching between contexts cheaper, but
+    /// commands aren't guaranteed to complete until the context flushes them itself.
+    None,
+}
+
 /// Attributes that control aspects of a context and/or surfaces created from that context.
 ///
 /// Similar to: https://www.khronos.org/registry/webgl/specs/latest/1.0/#WEBGLCONTEXTATTRIBUTES
@@ -60,6 +146,138 @@ pub struct ContextAttributes {
     pub version: GLVersion,
     /// Various flags.
     pub flags: ContextAttributeFlags,
+    /// The scheduling priority of the context.
+    ///
+    /// This is negotiated against the display: if the priority can't be requested, the context
+    /// is created with the default priority instead of failing.
+    pub priority: ContextPriority,
+    /// What happens to pending commands when the context stops being current.
+    ///
+    /// Like `priority`, this is negotiated against the display, falling back to `Flush`.
+    pub release_behavior: ContextReleaseBehavior,
+    /// The number of samples per pixel of generic surfaces created for this context, or 0 for
+    /// single-sampled surfaces.
+    ///
+    /// Multisampled surfaces are rendered to through a multisampled framebuffer, which is resolved
+    /// into the surface when it is unbound from its context or wrapped in a surface texture. The
+    /// count is clamped to the maximum that the implementation supports. Backends that don't
+    /// support multisampled surfaces report 0.
+    pub samples: u8,
+}
+
+/// The properties of the pixel format that a context descriptor selects, as returned by
+/// `Device::context_descriptor_pixel_format()`.
+///
+/// The color, depth, and stencil sizes and the sample count describe the buffers of widget
+/// surfaces. Generic surfaces take their format from `SurfaceFormat` and their sample count from
+/// `ContextAttributes::samples` instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelFormatInfo {
+    /// The number of bits in the red channel.
+    pub red_size: u8,
+    /// The number of bits in the green channel.
+    pub green_size: u8,
+    /// The number of bits in the blue channel.
+    pub blue_size: u8,
+    /// The number of bits in the alpha channel, or 0 if there is none.
+    pub alpha_size: u8,
+    /// The number of bits in the depth buffer, or 0 if there is none.
+    pub depth_size: u8,
+    /// The number of bits in the stencil buffer, or 0 if there is none.
+    pub stencil_size: u8,
+    /// The number of samples per pixel, or 0 if the pixel format is single-sampled.
+    pub samples: u8,
+    /// Whether the driver warns against using this pixel format.
+    pub caveat: PixelFormatCaveat,
+    /// Whether native rendering APIs can draw to surfaces with this pixel format.
+    pub native_renderable: bool,
+}
+
+/// A warning that the driver attaches to a pixel format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixelFormatCaveat {
+    /// The pixel format has no caveats.
+    None,
+    /// Rendering with the pixel format may be slow, typically because it isn't hardware
+    /// accelerated.
+    Slow,
+    /// The pixel format doesn't pass the conformance tests of its API.
+    NonConformant,
+}
+
+/// Preferences used to rank the pixel formats that satisfy a set of context attributes, as
+/// passed to `Device::choose_context_descriptor()`.
+///
+/// Pixel formats are compared on each preference in the order of the fields below. Ties are broken
+/// by the order in which the driver reports them. The default preferences, which
+/// `Device::create_context_descriptor()` uses, avoid pixel formats with caveats and express no
+/// other preference.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PixelFormatPreferences {
+    /// Whether to rank pixel formats without caveats above those with them.
+    pub avoid_caveats: bool,
+    /// The preferred number of samples per pixel. Pixel formats closer to this count rank
+    /// higher.
+    pub samples: Option<u8>,
+    /// Whether native rendering APIs should be able to draw to surfaces with the pixel format.
+    pub native_renderable: Option<bool>,
+}
+
+/// An ordered list of acceptable context attributes, as passed to
+/// `Device::negotiate_context_descriptor()`.
+///
+/// Each candidate is tried in turn, first with all of its flags and then with the optional flags
+/// dropped one entry at a time. The first attempt that the device can satisfy wins.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ContextNegotiation {
+    /// The acceptable attributes, most preferred first. These typically differ in version and
+    /// profile, e.g. OpenGL 4.5 core, then OpenGL 3.3 core, then OpenGL 3.0 compatibility.
+    pub candidates: Vec<ContextAttributes>,
+    /// Flags that may be dropped from a candidate that can't be satisfied, in the order that they
+    /// should be given up. Each entry is dropped along with all of the entries before it.
+    pub optional_flags: Vec<ContextAttributeFlags>,
+}
+
+/// What had to be given up to satisfy a `ContextNegotiation`, as returned by
+/// `Device::negotiate_context_descriptor()`.
+#[derive(Debug)]
+pub struct ContextNegotiationReport {
+    /// The index of the candidate that was satisfied.
+    pub candidate_index: usize,
+    /// The attributes that the descriptor was created with: the candidate minus `dropped_flags`.
+    ///
+    /// The priority and release behavior may still have fallen back to their defaults, as
+    /// reported by `Device::context_descriptor_attributes()`.
+    pub attributes: ContextAttributes,
+    /// The optional flags that were dropped from the candidate.
+    pub dropped_flags: ContextAttributeFlags,
+    /// The attempts that failed before the one that succeeded, in order, with their errors.
+    pub failed_attempts: Vec<(ContextAttributes, Error)>,
+}
+
+impl Default for ContextPriority {
+    #[inline]
+    fn default() -> ContextPriority {
+        ContextPriority::Medium
+    }
+}
+
+impl Default for ContextReleaseBehavior {
+    #[inline]
+    fn default() -> ContextReleaseBehavior {
+        ContextReleaseBehavior::Flush
+    }
+}
+
+impl Default for ContextAttributes {
+    /// Returns attributes with version 0.0, no flags, no multisampling, and the default priority
+    /// and release behavior.
+    ///
+    /// The version must be filled in before these attributes are useful.
+    #[inline]
+    fn default() -> ContextAttributes {
+        ContextAttributes::zeroed()
+    }
 }
 
 impl ContextAttributes {
@@ -68,6 +286,312 @@ impl ContextAttributes {
         ContextAttributes {
             version: GLVersion::new(0, 0),
             flags: ContextAttributeFlags::empty(),
+            priority: ContextPriority::Medium,
+            release_behavior: ContextReleaseBehavior::Flush,
+            samples: 0,
+        }
+    }
+}
+
+impl PixelFormatInfo {
+    // Describes the pixel format of backends that choose a single pixel format for the given
+    // attributes and can't report its properties.
+    #[allow(dead_code)]
+    pub(crate) fn from_attributes(attributes: &ContextAttributes) -> PixelFormatInfo {
+        let flags = attributes.flags;
+        PixelFormatInfo {
+            red_size: 8,
+            green_size: 8,
+            blue_size: 8,
+            alpha_size: if flags.contains(ContextAttributeFlags::ALPHA) {
+                8
+            } else {
+                0
+            },
+            depth_size: if flags.contains(ContextAttributeFlags::DEPTH) {
+                24
+            } else {
+                0
+            },
+            stencil_size: if flags.contains(ContextAttributeFlags::STENCIL) {
+                8
+            } else {
+                0
+            },
+            samples: 0,
+            caveat: PixelFormatCaveat::None,
+            native_renderable: true,
+        }
+    }
+}
+
+impl Default for PixelFormatPreferences {
+    /// Returns preferences that avoid pixel formats with caveats.
+    #[inline]
+    fn default() -> PixelFormatPreferences {
+        PixelFormatPreferences {
+            avoid_caveats: true,
+            samples: None,
+            native_renderable: None,
+        }
+    }
+}
+
+impl PixelFormatPreferences {
+    // Returns a key that sorts more preferable pixel formats first.
+    #[allow(dead_code)]
+    pub(crate) fn rank(&self, pixel_format: &PixelFormatInfo) -> (bool, u8, bool) {
+        let has_caveat = self.avoid_caveats && pixel_format.caveat != PixelFormatCaveat::None;
+        let sample_distance = match self.samples {
+            Some(samples) => (pixel_format.samples as i16 - samples as i16).unsigned_abs() as u8,
+            None => 0,
+        };
+        let native_renderable_mismatch = match self.native_renderable {
+            Some(native_renderable) => pixel_format.native_renderable != native_renderable,
+            None => false,
+        };
+        (has_caveat, sample_distance, native_renderable_mismatch)
+    }
+}
+
+// Implements `Device::negotiate_context_descriptor()` on top of the other device methods.
+//
+// Descriptors don't check the OpenGL version on every backend, so each attempt is verified by
+// creating and destroying a context. If every attempt fails, the error of the first one is
+// returned, since it describes the most preferred candidate.
+#[allow(dead_code)]
+pub(crate) fn negotiate_context_descriptor<D>(
+    device: &mut D,
+    negotiation: &ContextNegotiation,
+) -> Result<(D::ContextDescriptor, ContextNegotiationReport), Error>
+where
+    D: DeviceInterface,
+{
+    let mut failed_attempts = vec![];
+    for (candidate_index, candidate) in negotiation.candidates.iter().enumerate() {
+        let mut attributes = *candidate;
+        let mut dropped_flags = ContextAttributeFlags::empty();
+        let mut optional_flags = negotiation.optional_flags.iter();
+        loop {
+            match try_context_attributes(device, &attributes) {
+                Ok(descriptor) => {
+                    let report = ContextNegotiationReport {
+                        candidate_index,
+                        attributes,
+                        dropped_flags,
+                        failed_attempts,
+                    };
+                    return Ok((descriptor, report));
+                }
+                Err(err) => failed_attempts.push((attributes, err)),
+            }
+
+            // Give up the next optional flags that this candidate still has, if any.
+            match optional_flags
+                .by_ref()
+                .map(|&flags| flags & attributes.flags)
+                .find(|flags| !flags.is_empty())
+            {
+                Some(flags) => {
+                    attributes.flags.remove(flags);
+                    dropped_flags.insert(flags);
+                }
+                None => break,
+            }
+        }
+    }
+
+    Err(failed_attempts
+        .into_iter()
+        .next()
+        .map(|(_, err)| err)
+        .unwrap_or(Error::NoPixelFormatFound))
+}
+
+fn try_context_attributes<D>(
+    device: &mut D,
+    attributes: &ContextAttributes,
+) -> Result<D::ContextDescriptor, Error>
+where
+    D: DeviceInterface,
+{
+    let descriptor = device.create_context_descriptor(attributes)?;
+    let mut context = device.create_context(&descriptor, None)?;
+    device.destroy_context(&mut context)?;
+    Ok(descriptor)
+}
+
+impl ContextResetStatus {
+    #[allow(dead_code)]
+    pub(crate) fn from_gl(status: GLenum) -> ContextResetStatus {
+        match status {
+            gl::NO_ERROR => ContextResetStatus::NoError,
+            GL_GUILTY_CONTEXT_RESET => ContextResetStatus::GuiltyContextReset,
+            GL_INNOCENT_CONTEXT_RESET => ContextResetStatus::InnocentContextReset,
+            _ => ContextResetStatus::UnknownContextReset,
+        }
+    }
+}
+
+// The names that `glGetGraphicsResetStatus()` goes by in OpenGL 4.5 and OpenGL ES 3.2,
+// `GL_KHR_robustness`, `GL_ARB_robustness`, and `GL_EXT_robustness` respectively.
+static GET_GRAPHICS_RESET_STATUS_NAMES: [&str; 4] = [
+    "glGetGraphicsResetStatus",
+    "glGetGraphicsResetStatusKHR",
+    "glGetGraphicsResetStatusARB",
+    "glGetGraphicsResetStatusEXT",
+];
+
+/// Returns the reset status of the current context, looking up `glGetGraphicsResetStatus()` with
+/// the given function.
+///
+/// Returns `RequiredExtensionUnavailable` if the driver provides no variant of that function.
+#[allow(dead_code)]
+pub(crate) unsafe fn current_context_reset_status<F>(
+    get_proc_address: F,
+) -> Result<ContextResetStatus, Error>
+where
+    F: Fn(&str) -> *const c_void,
+{
+    let get_graphics_reset_status = GET_GRAPHICS_RESET_STATUS_NAMES
+        .iter()
+        .map(|symbol_name| get_proc_address(symbol_name))
+        .find(|function| !function.is_null())
+        .ok_or(Error::RequiredExtensionUnavailable)?;
+    let get_graphics_reset_status =
+        mem::transmute::<*const c_void, extern "system" fn() -> GLenum>(get_graphics_reset_status);
+    Ok(ContextResetStatus::from_gl(get_graphics_reset_status()))
+}
+
+/// Returns the `ROBUST_ACCESS`, `LOSE_CONTEXT_ON_RESET`, `DEBUG`, `FORWARD_COMPATIBLE`, and
+/// `NO_ERROR` flags that apply to the current context, which has the given API and version.
+///
+/// Queries that the context doesn't support are skipped rather than issued, so that this doesn't
+/// have to call `glGetError()` and swallow errors that the application hasn't checked yet.
+#[allow(dead_code)]
+pub(crate) fn current_context_flags(
+    gl: &Gl,
+    gl_api: GLApi,
+    gl_version: GLVersion,
+) -> ContextAttributeFlags {
+    let mut flags = ContextAttributeFlags::empty();
+    unsafe {
+        // `GL_CONTEXT_FLAGS` is core in OpenGL 3.0 and OpenGL ES 3.2, and `GL_KHR_debug` adds it
+        // to older OpenGL ES versions. Bits for features the context doesn't support are clear.
+        let has_context_flags = match gl_api {
+            GLApi::GL => gl_version_at_least(gl_version, 3, 0),
+            GLApi::GLES => {
+                gl_version_at_least(gl_version, 3, 2)
+                    || current_context_has_any_extension(gl, gl_version, &["GL_KHR_debug"])
+            }
+        };
+        if has_context_flags {
+            let mut context_flags = 0;
+            gl.GetIntegerv(GL_CONTEXT_FLAGS, &mut context_flags);
+            flags.set(
+                ContextAttributeFlags::ROBUST_ACCESS,
+                (context_flags & GL_CONTEXT_FLAG_ROBUST_ACCESS_BIT) != 0,
+            );
+            flags.set(
+                ContextAttributeFlags::DEBUG,
+                (context_flags & GL_CONTEXT_FLAG_DEBUG_BIT) != 0,
+            );
+            flags.set(
+                ContextAttributeFlags::FORWARD_COMPATIBLE,
+                (context_flags & GL_CONTEXT_FLAG_FORWARD_COMPATIBLE_BIT) != 0,
+            );
+            flags.set(
+                ContextAttributeFlags::NO_ERROR,
+                (context_flags & GL_CONTEXT_FLAG_NO_ERROR_BIT) != 0,
+            );
+        }
+
+        // `GL_RESET_NOTIFICATION_STRATEGY` is core in OpenGL 4.5 and OpenGL ES 3.2, and the
+        // robustness extensions all use the same enum.
+        let has_reset_notification_strategy = match gl_api {
+            GLApi::GL => gl_version_at_least(gl_version, 4, 5),
+            GLApi::GLES => gl_version_at_least(gl_version, 3, 2),
+        } || current_context_has_any_extension(
+            gl,
+            gl_version,
+            &[
+                "GL_ARB_robustness",
+                "GL_KHR_robustness",
+                "GL_EXT_robustness",
+            ],
+        );
+        if has_reset_notification_strategy {
+            let mut reset_notification_strategy = 0;
+            gl.GetIntegerv(
+                GL_RESET_NOTIFICATION_STRATEGY,
+                &mut reset_notification_strategy,
+            );
+            flags.set(
+                ContextAttributeFlags::LOSE_CONTEXT_ON_RESET,
+                reset_notification_strategy == GL_LOSE_CONTEXT_ON_RESET,
+            );
+        }
+    }
+    flags
+}
+
+/// Returns the release behavior of the current context, which has the given API and version.
+///
+/// Contexts that can't report their release behavior are assumed to flush. Like
+/// `current_context_flags()`, this doesn't call `glGetError()`.
+#[allow(dead_code)]
+pub(crate) fn current_context_release_behavior(
+    gl: &Gl,
+    gl_api: GLApi,
+    gl_version: GLVersion,
+) -> ContextReleaseBehavior {
+    // `GL_CONTEXT_RELEASE_BEHAVIOR` is core in OpenGL 4.5, and otherwise needs
+    // `GL_KHR_context_flush_control`.
+    let supported = (gl_api == GLApi::GL && gl_version_at_least(gl_version, 4, 5))
+        || current_context_has_any_extension(gl, gl_version, &["GL_KHR_context_flush_control"]);
+    if !supported {
+        return ContextReleaseBehavior::Flush;
+    }
+
+    unsafe {
+        let mut release_behavior = 0;
+        gl.GetIntegerv(GL_CONTEXT_RELEASE_BEHAVIOR, &mut release_behavior);
+        if release_behavior == gl::NONE as GLint {
+            ContextReleaseBehavior::None
+        } else {
+            ContextReleaseBehavior::Flush
+        }
+    }
+}
+
+fn gl_version_at_least(gl_version: GLVersion, major: u8, minor: u8) -> bool {
+    (gl_version.major, gl_version.minor) >= (major, minor)
+}
+
+// Returns true if the current context, which has the given version, supports any of the given
+// extensions. OpenGL and OpenGL ES 3.0 and later list extensions one at a time, and the combined
+// extension string isn't available in core profiles.
+fn current_context_has_any_extension(gl: &Gl, gl_version: GLVersion, names: &[&str]) -> bool {
+    unsafe {
+        if gl_version.major >= 3 {
+            let mut num_extensions = 0;
+            gl.GetIntegerv(gl::NUM_EXTENSIONS, &mut num_extensions);
+            (0..num_extensions as GLuint).any(|extension_index| {
+                let extension = gl.GetStringi(gl::EXTENSIONS, extension_index) as *const c_char;
+                !extension.is_null()
+                    && names
+                        .iter()
+                        .any(|name| CStr::from_ptr(extension).to_bytes() == name.as_bytes())
+            })
+        } else {
+            let extensions = gl.GetString(gl::EXTENSIONS) as *const c_char;
+            if extensions.is_null() {
+                return false;
+            }
+            CStr::from_ptr(extensions)
+                .to_string_lossy()
+                .split_whitespace()
+                .any(|extension| names.contains(&extension))
         }
     }
 }
@@ -80,29 +604,18 @@ pub(crate) fn current_context_uses_compatibility_profile(_gl: &Gl) -> bool {
 #[cfg(not(target_os = "android"))]
 #[allow(dead_code)]
 pub(crate) fn current_context_uses_compatibility_profile(gl: &Gl) -> bool {
+    let gl_version = GLVersion::current(gl);
     unsafe {
-        // First, try `GL_CONTEXT_PROFILE_MASK`.
-        let mut context_profile_mask = 0;
-        gl.GetIntegerv(gl::CONTEXT_PROFILE_MASK, &mut context_profile_mask);
-        if gl.GetError() == gl::NO_ERROR
-            && (context_profile_mask & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT as i32) != 0
-        {
-            return true;
-        }
-
-        // Second, look for the `GL_ARB_compatibility` extension.
-        let mut num_extensions = 0;
-        gl.GetIntegerv(gl::NUM_EXTENSIONS, &mut num_extensions);
-        if gl.GetError() == gl::NO_ERROR {
-            for extension_index in 0..(num_extensions as GLuint) {
-                let extension = gl.GetStringi(gl::EXTENSIONS, extension_index) as *const c_char;
-                let extension = CStr::from_ptr(extension);
-                if extension.to_str() == Ok("GL_ARB_compatibility") {
-                    return true;
-                }
+        // First, try `GL_CONTEXT_PROFILE_MASK`, which exists from OpenGL 3.2 on.
+        if gl_version_at_least(gl_version, 3, 2) {
+            let mut context_profile_mask = 0;
+            gl.GetIntegerv(gl::CONTEXT_PROFILE_MASK, &mut context_profile_mask);
+            if (context_profile_mask & gl::CONTEXT_COMPATIBILITY_PROFILE_BIT as i32) != 0 {
+                return true;
             }
         }
-
-        false
     }
+
+    // Second, look for the `GL_ARB_compatibility` extension.
+    current_context_has_any_extension(gl, gl_version, &["GL_ARB_compatibility"])
 }
@@ -0,0 +1,119 @@
+// surfman/surfman/src/debug/mod.rs
+//
+//! Debug messages from debug contexts and the windowing system.
+
+use std::sync::Arc;
+
+// Only the EGL backends deliver debug messages.
+#[cfg(any(android, angle, linux))]
+mod sink;
+#[cfg(any(android, angle, linux))]
+pub(crate) use self::sink::{install_gl_debug_message_callback, log_message, DebugSink};
+
+/// The part of the system that generated a debug message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DebugMessageSource {
+    /// The OpenGL API.
+    Api,
+    /// The windowing system, such as EGL.
+    WindowSystem,
+    /// The shader compiler.
+    ShaderCompiler,
+    /// A tool or library associated with OpenGL.
+    ThirdParty,
+    /// The application itself, via `glDebugMessageInsert()`.
+    Application,
+    /// Some other part of the system.
+    Other,
+}
+
+/// The kind of event that a debug message reports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DebugMessageType {
+    /// An error, typically from the API.
+    Error,
+    /// Use of behavior that has been deprecated.
+    DeprecatedBehavior,
+    /// Use of behavior that is undefined.
+    UndefinedBehavior,
+    /// Use of functionality that isn't portable.
+    Portability,
+    /// Code that may perform poorly.
+    Performance,
+    /// An annotation in the command stream.
+    Marker,
+    /// The start of a debug group.
+    PushGroup,
+    /// The end of a debug group.
+    PopGroup,
+    /// Some other kind of event.
+    Other,
+}
+
+/// How important a debug message is.
+///
+/// Severities are ordered from least to most severe.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DebugMessageSeverity {
+    /// Anything that isn't an error or a performance issue.
+    Notification,
+    /// Redundant state changes, trivial undefined behavior, and the like.
+    Low,
+    /// Major performance warnings, shader compilation warnings, use of deprecated behavior, and
+    /// the like.
+    Medium,
+    /// Errors and undefined behavior.
+    High,
+}
+
+/// A message from a debug context or from the windowing system.
+#[derive(Clone, Debug)]
+pub struct DebugMessage {
+    /// The part of the system that generated this message.
+    pub source: DebugMessageSource,
+    /// The kind of event that this message reports.
+    pub message_type: DebugMessageType,
+    /// How important this message is.
+    pub severity: DebugMessageSeverity,
+    /// An implementation-defined ID. For windowing system errors, this is the error code.
+    pub id: u32,
+    /// The text of the message.
+    pub message: String,
+}
+
+/// Selects which debug messages are delivered.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugMessageFilter {
+    /// Messages less severe than this are dropped.
+    pub min_severity: DebugMessageSeverity,
+    /// If present, only messages from these sources are delivered.
+    pub sources: Option<Vec<DebugMessageSource>>,
+}
+
+/// A function that receives debug messages.
+///
+/// This may be called on any thread that uses a context of the device it was registered on.
+pub type DebugMessageCallback = Arc<dyn Fn(&DebugMessage) + Send + Sync>;
+
+impl Default for DebugMessageFilter {
+    #[inline]
+    fn default() -> DebugMessageFilter {
+        DebugMessageFilter {
+            min_severity: DebugMessageSeverity::Low,
+            sources: None,
+        }
+    }
+}
+
+impl DebugMessageFilter {
+    /// Returns true if the given message passes this filter.
+    pub fn accepts(&self, message: &DebugMessage) -> bool {
+        if message.severity < self.min_severity {
+            return false;
+        }
+        match self.sources {
+            Some(ref sources) => sources.contains(&message.source),
+            None => true,
+        }
+    }
+}
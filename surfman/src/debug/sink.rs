@@ -0,0 +1,219 @@
+// surfman/surfman/src/debug/sink.rs
+//
+//! Delivery of debug messages to the callback of a device or to the `log` crate.
+
+use super::{DebugMessage, DebugMessageCallback, DebugMessageFilter};
+use super::{DebugMessageSeverity, DebugMessageSource, DebugMessageType};
+use crate::gl::types::{GLchar, GLenum, GLsizei, GLuint};
+use crate::Gl;
+
+use log::Level;
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::c_void;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::sync::{Arc, Mutex};
+
+// These aren't in the OpenGL 3.3 and OpenGL ES 3.0 headers that our bindings are generated from.
+const GL_DEBUG_OUTPUT_SYNCHRONOUS: GLenum = 0x8242;
+const GL_DEBUG_SOURCE_API: GLenum = 0x8246;
+const GL_DEBUG_SOURCE_WINDOW_SYSTEM: GLenum = 0x8247;
+const GL_DEBUG_SOURCE_SHADER_COMPILER: GLenum = 0x8248;
+const GL_DEBUG_SOURCE_THIRD_PARTY: GLenum = 0x8249;
+const GL_DEBUG_SOURCE_APPLICATION: GLenum = 0x824a;
+const GL_DEBUG_TYPE_ERROR: GLenum = 0x824c;
+const GL_DEBUG_TYPE_DEPRECATED_BEHAVIOR: GLenum = 0x824d;
+const GL_DEBUG_TYPE_UNDEFINED_BEHAVIOR: GLenum = 0x824e;
+const GL_DEBUG_TYPE_PORTABILITY: GLenum = 0x824f;
+const GL_DEBUG_TYPE_PERFORMANCE: GLenum = 0x8250;
+const GL_DEBUG_TYPE_MARKER: GLenum = 0x8268;
+const GL_DEBUG_TYPE_PUSH_GROUP: GLenum = 0x8269;
+const GL_DEBUG_TYPE_POP_GROUP: GLenum = 0x826a;
+const GL_DEBUG_SEVERITY_HIGH: GLenum = 0x9146;
+const GL_DEBUG_SEVERITY_MEDIUM: GLenum = 0x9147;
+const GL_DEBUG_SEVERITY_LOW: GLenum = 0x9148;
+
+// The names that `glDebugMessageCallback()` goes by in OpenGL 4.3 and OpenGL ES 3.2,
+// `GL_KHR_debug` on OpenGL ES, and `GL_ARB_debug_output` respectively.
+static DEBUG_MESSAGE_CALLBACK_NAMES: [&str; 3] = [
+    "glDebugMessageCallback",
+    "glDebugMessageCallbackKHR",
+    "glDebugMessageCallbackARB",
+];
+
+type GLDebugProc = extern "system" fn(
+    source: GLenum,
+    message_type: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    user_param: *mut c_void,
+);
+
+// Where debug messages for a device go. Debug contexts hold a reference to their device's sink,
+// which the driver passes back to `gl_debug_message_callback()`.
+pub(crate) struct DebugSink {
+    state: Mutex<DebugSinkState>,
+}
+
+struct DebugSinkState {
+    callback: Option<DebugMessageCallback>,
+    filter: DebugMessageFilter,
+}
+
+impl DebugSink {
+    pub(crate) fn new() -> Arc<DebugSink> {
+        Arc::new(DebugSink {
+            state: Mutex::new(DebugSinkState {
+                callback: None,
+                filter: DebugMessageFilter::default(),
+            }),
+        })
+    }
+
+    pub(crate) fn set_callback(&self, callback: Option<DebugMessageCallback>) {
+        self.state.lock().unwrap().callback = callback;
+    }
+
+    pub(crate) fn set_filter(&self, filter: DebugMessageFilter) {
+        self.state.lock().unwrap().filter = filter;
+    }
+
+    pub(crate) fn deliver(&self, message: &DebugMessage) {
+        // Don't hold the lock while calling out, in case the callback replaces itself.
+        let callback = {
+            let state = self.state.lock().unwrap();
+            if !state.filter.accepts(message) {
+                return;
+            }
+            state.callback.clone()
+        };
+
+        match callback {
+            Some(callback) => callback(message),
+            None => log_message(message),
+        }
+    }
+}
+
+impl DebugMessageSource {
+    fn from_gl(source: GLenum) -> DebugMessageSource {
+        match source {
+            GL_DEBUG_SOURCE_API => DebugMessageSource::Api,
+            GL_DEBUG_SOURCE_WINDOW_SYSTEM => DebugMessageSource::WindowSystem,
+            GL_DEBUG_SOURCE_SHADER_COMPILER => DebugMessageSource::ShaderCompiler,
+            GL_DEBUG_SOURCE_THIRD_PARTY => DebugMessageSource::ThirdParty,
+            GL_DEBUG_SOURCE_APPLICATION => DebugMessageSource::Application,
+            _ => DebugMessageSource::Other,
+        }
+    }
+}
+
+impl DebugMessageType {
+    fn from_gl(message_type: GLenum) -> DebugMessageType {
+        match message_type {
+            GL_DEBUG_TYPE_ERROR => DebugMessageType::Error,
+            GL_DEBUG_TYPE_DEPRECATED_BEHAVIOR => DebugMessageType::DeprecatedBehavior,
+            GL_DEBUG_TYPE_UNDEFINED_BEHAVIOR => DebugMessageType::UndefinedBehavior,
+            GL_DEBUG_TYPE_PORTABILITY => DebugMessageType::Portability,
+            GL_DEBUG_TYPE_PERFORMANCE => DebugMessageType::Performance,
+            GL_DEBUG_TYPE_MARKER => DebugMessageType::Marker,
+            GL_DEBUG_TYPE_PUSH_GROUP => DebugMessageType::PushGroup,
+            GL_DEBUG_TYPE_POP_GROUP => DebugMessageType::PopGroup,
+            _ => DebugMessageType::Other,
+        }
+    }
+}
+
+impl DebugMessageSeverity {
+    fn from_gl(severity: GLenum) -> DebugMessageSeverity {
+        match severity {
+            GL_DEBUG_SEVERITY_HIGH => DebugMessageSeverity::High,
+            GL_DEBUG_SEVERITY_MEDIUM => DebugMessageSeverity::Medium,
+            GL_DEBUG_SEVERITY_LOW => DebugMessageSeverity::Low,
+            _ => DebugMessageSeverity::Notification,
+        }
+    }
+}
+
+/// Forwards a debug message to the `log` crate, with a level corresponding to its severity.
+pub(crate) fn log_message(message: &DebugMessage) {
+    let level = match message.severity {
+        DebugMessageSeverity::High => Level::Error,
+        DebugMessageSeverity::Medium => Level::Warn,
+        DebugMessageSeverity::Low => Level::Info,
+        DebugMessageSeverity::Notification => Level::Debug,
+    };
+    log!(
+        target: "surfman::debug",
+        level,
+        "{:?} {:?} {:#x}: {}",
+        message.source,
+        message.message_type,
+        message.id,
+        message.message
+    );
+}
+
+/// Delivers debug messages from the current context to the given sink.
+///
+/// The sink must outlive the context. Returns false if the driver doesn't support debug output.
+pub(crate) unsafe fn install_gl_debug_message_callback<F>(
+    gl: &Gl,
+    get_proc_address: F,
+    sink: &Arc<DebugSink>,
+) -> bool
+where
+    F: Fn(&str) -> *const c_void,
+{
+    let debug_message_callback = match DEBUG_MESSAGE_CALLBACK_NAMES
+        .iter()
+        .map(|symbol_name| get_proc_address(symbol_name))
+        .find(|function| !function.is_null())
+    {
+        None => return false,
+        Some(debug_message_callback) => debug_message_callback,
+    };
+    let debug_message_callback: extern "system" fn(GLDebugProc, *const c_void) =
+        mem::transmute(debug_message_callback);
+    debug_message_callback(
+        gl_debug_message_callback,
+        Arc::as_ptr(sink) as *const c_void,
+    );
+
+    // Deliver messages on the thread that caused them, so that they show up in backtraces.
+    gl.Enable(GL_DEBUG_OUTPUT_SYNCHRONOUS);
+    true
+}
+
+extern "system" fn gl_debug_message_callback(
+    source: GLenum,
+    message_type: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    user_param: *mut c_void,
+) {
+    // Never unwind into the driver.
+    drop(panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        let message = if message.is_null() {
+            String::new()
+        } else if length >= 0 {
+            let bytes = slice::from_raw_parts(message as *const u8, length as usize);
+            String::from_utf8_lossy(bytes).into_owned()
+        } else {
+            CStr::from_ptr(message).to_string_lossy().into_owned()
+        };
+        let sink = &*(user_param as *const DebugSink);
+        sink.deliver(&DebugMessage {
+            source: DebugMessageSource::from_gl(source),
+            message_type: DebugMessageType::from_gl(message_type),
+            severity: DebugMessageSeverity::from_gl(severity),
+            id,
+            message,
+        });
+    })));
+}
@@ -4,7 +4,13 @@
 
 use super::connection::Connection as ConnectionInterface;
 use crate::gl::types::{GLenum, GLuint};
-use crate::{ContextAttributes, ContextID, Error, GLApi, SurfaceAccess, SurfaceInfo, SurfaceType};
+use crate::SurfaceAccess;
+use crate::{AdapterInfo, ContextAttributes, ContextID, ContextResetStatus, Error, GLApi};
+use crate::{ContextNegotiation, ContextNegotiationReport, DebugMessage, DebugMessageFilter};
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::{DmaBufDescriptor, SurfaceHandle};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
+use crate::{SurfaceFormat, SurfaceInfo, SurfaceType};
 use euclid::default::Size2D;
 
 use std::os::raw::c_void;
@@ -43,6 +49,27 @@ where
     /// Returns the OpenGL API flavor that this device supports (OpenGL or OpenGL ES).
     fn gl_api(&self) -> GLApi;
 
+    /// Returns information about the hardware that this device renders with.
+    fn adapter_info(&self) -> Result<AdapterInfo, Error>;
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error>;
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// Messages come from contexts created with the `DEBUG` flag and, on EGL backends, from the
+    /// windowing system. The callback replaces any previous one. Backends without debug output
+    /// never call it.
+    fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static;
+
+    /// Removes the debug message callback, forwarding messages to the `log` crate again.
+    fn clear_debug_message_callback(&self);
+
+    /// Selects which debug messages of this device are delivered.
+    fn set_debug_message_filter(&self, filter: DebugMessageFilter);
+
     // context.rs
 
     /// Creates a context descriptor with the given attributes.
@@ -53,6 +80,33 @@ where
         attributes: &ContextAttributes,
     ) -> Result<Self::ContextDescriptor, Error>;
 
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// Backends that can't enumerate pixel formats return the single descriptor that
+    /// `create_context_descriptor()` would.
+    fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<Self::ContextDescriptor>, Error>;
+
+    /// Creates a context descriptor with the given attributes, choosing the pixel format that
+    /// ranks highest according to the given preferences.
+    fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<Self::ContextDescriptor, Error>;
+
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(Self::ContextDescriptor, ContextNegotiationReport), Error>;
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -88,12 +142,25 @@ where
     /// made current.
     fn make_no_context_current(&self) -> Result<(), Error>;
 
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated. If the driver can't
+    /// report resets, a `RequiredExtensionUnavailable` error is returned.
+    fn context_reset_status(&self, context: &Self::Context) -> Result<ContextResetStatus, Error>;
+
     /// Returns the attributes that the context descriptor was created with.
     fn context_descriptor_attributes(
         &self,
         context_descriptor: &Self::ContextDescriptor,
     ) -> ContextAttributes;
 
+    /// Returns the properties of the pixel format that the context descriptor selects.
+    fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &Self::ContextDescriptor,
+    ) -> PixelFormatInfo;
+
     /// Fetches the address of an OpenGL function associated with this context.
     ///
     /// OpenGL functions are local to a context. You should not use OpenGL functions on one context
@@ -171,6 +238,29 @@ where
         surface: Self::Surface,
     ) -> Result<Self::SurfaceTexture, (Error, Self::Surface)>;
 
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    ///
+    /// The file descriptors in the descriptor are closed once the buffer has been imported. If
+    /// the backend or driver can't import buffers, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Self::Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Self::Surface, Error>;
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    ///
+    /// No framebuffer is created, so the surface returned by `destroy_surface_texture()` can't be
+    /// bound to a context. The same requirements as `create_surface_from_dmabuf()` apply.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Self::Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Self::SurfaceTexture, Error>;
+
     /// Destroys a surface.
     ///
     /// The supplied context must be the context the surface is associated with, or this returns
@@ -197,6 +287,29 @@ where
         surface_texture: Self::SurfaceTexture,
     ) -> Result<Self::Surface, (Error, Self::SurfaceTexture)>;
 
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    ///
+    /// The file descriptors refer to the memory of the surface itself, so no copy is made. If the
+    /// backend or driver can't export surfaces, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn export_surface_dmabuf(&self, surface: &Self::Surface) -> Result<DmaBufDescriptor, Error>;
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    ///
+    /// Send the handle over a Unix domain socket with `SurfaceHandle::send()`, and open it in the
+    /// receiving process with `create_surface_texture_from_handle()`.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_handle(&self, surface: &Self::Surface) -> Result<SurfaceHandle, Error>;
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Self::Context,
+        handle: SurfaceHandle,
+    ) -> Result<Self::SurfaceTexture, Error>;
+
     /// Returns the OpenGL texture target needed to read from this surface texture.
     ///
     /// This will be `GL_TEXTURE_2D` or `GL_TEXTURE_RECTANGLE`, depending on platform.
@@ -223,6 +336,17 @@ where
         size: Size2D<i32>,
     ) -> Result<(), Error>;
 
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    ///
+    /// Contents are anchored at the bottom left corner. Backends that can't preserve the contents
+    /// of generic surfaces return an `Unimplemented` error.
+    fn resize_surface_preserving_contents(
+        &self,
+        context: &Self::Context,
+        surface: &mut Self::Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error>;
+
     /// Returns various information about the surface, including the framebuffer object needed to
     /// render to this surface.
     ///
@@ -49,6 +49,8 @@ pub enum Error {
     SurfaceCreationFailed(WindowingApiError),
     /// The system couldn't import a surface from another thread.
     SurfaceImportFailed(WindowingApiError),
+    /// The system couldn't export a surface for use by another API or process.
+    SurfaceExportFailed(WindowingApiError),
     /// The system couldn't create a surface texture from a surface.
     SurfaceTextureCreationFailed(WindowingApiError),
     /// The system couldn't present a widget surface.
@@ -96,6 +98,15 @@ pub enum Error {
     IncompatibleNativeContext,
     /// The native device does not match the supplied connection.
     IncompatibleNativeDevice,
+    /// The device can't render to surfaces of the requested format.
+    UnsupportedSurfaceFormat,
+    /// The surface has no framebuffer, so it can't be bound to a context. Surfaces returned by
+    /// destroying surface textures that were created directly from imported buffers are like
+    /// this.
+    SurfaceNotRenderable,
+    /// The requested context attribute flags can't be combined, such as `NO_ERROR` with
+    /// `DEBUG`.
+    IncompatibleContextAttributeFlags,
 }
 
 /// Abstraction of the errors that EGL, CGL, GLX, CGL, etc. return.
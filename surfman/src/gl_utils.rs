@@ -4,8 +4,11 @@
 
 use crate::gl;
 use crate::gl::types::{GLenum, GLint, GLuint};
+use crate::surface::SurfaceFormat;
 use crate::Gl;
 
+use std::ptr;
+
 #[allow(dead_code)]
 pub(crate) fn create_and_bind_framebuffer(
     gl: &Gl,
@@ -49,3 +52,50 @@ pub(crate) fn destroy_framebuffer(gl: &Gl, framebuffer_object: GLuint) {
         gl.DeleteFramebuffers(1, &framebuffer_object);
     }
 }
+
+// Returns true if a texture of the given surface format can be created and rendered to with the
+// current context.
+//
+// A scratch texture and framebuffer are created and destroyed. Bindings are restored, and the GL
+// error that an unsupported format raises is consumed.
+#[allow(dead_code)]
+pub(crate) fn surface_format_is_renderable(gl: &Gl, format: SurfaceFormat) -> bool {
+    unsafe {
+        let (mut old_texture_object, mut old_unpack_buffer) = (0, 0);
+        let (mut old_draw_framebuffer, mut old_read_framebuffer) = (0, 0);
+        gl.GetIntegerv(gl::TEXTURE_BINDING_2D, &mut old_texture_object);
+        gl.GetIntegerv(gl::PIXEL_UNPACK_BUFFER_BINDING, &mut old_unpack_buffer);
+        gl.GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut old_draw_framebuffer);
+        gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut old_read_framebuffer);
+
+        let mut texture_object = 0;
+        gl.GenTextures(1, &mut texture_object);
+        gl.BindTexture(gl::TEXTURE_2D, texture_object);
+        gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+        let (internal_format, pixel_format, pixel_type) = format.gl_texture_formats();
+        gl.TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            internal_format as GLint,
+            1,
+            1,
+            0,
+            pixel_format,
+            pixel_type,
+            ptr::null(),
+        );
+        let allocated = gl.GetError() == gl::NO_ERROR;
+
+        let framebuffer_object = create_and_bind_framebuffer(gl, gl::TEXTURE_2D, texture_object);
+        let complete = gl.CheckFramebufferStatus(gl::FRAMEBUFFER) == gl::FRAMEBUFFER_COMPLETE;
+
+        gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, old_draw_framebuffer as GLuint);
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, old_read_framebuffer as GLuint);
+        gl.DeleteFramebuffers(1, &framebuffer_object);
+        gl.BindTexture(gl::TEXTURE_2D, old_texture_object as GLuint);
+        gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, old_unpack_buffer as GLuint);
+        gl.DeleteTextures(1, &texture_object);
+
+        allocated && complete
+    }
+}
@@ -7,7 +7,7 @@ use super::super::connection::{Connection, NativeConnection};
 use super::super::device::{Adapter, Device, NativeDevice};
 use super::super::surface::NativeWidget;
 use crate::connection::Connection as ConnectionInterface;
-use crate::info::GLApi;
+use crate::info::{AdapterInfo, GLApi};
 use crate::Error;
 
 use euclid::default::Size2D;
@@ -60,6 +60,11 @@ impl ConnectionInterface for Connection {
         Connection::create_software_adapter(self)
     }
 
+    #[inline]
+    fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        Connection::adapter_info(self, adapter)
+    }
+
     #[inline]
     fn create_device(&self, adapter: &Adapter) -> Result<Device, Error> {
         Connection::create_device(self, adapter)
@@ -10,7 +10,12 @@ use super::super::surface::{NativeWidget, Surface, SurfaceTexture};
 use crate::connection::Connection as ConnectionInterface;
 use crate::device::Device as DeviceInterface;
 use crate::gl::types::{GLenum, GLuint};
-use crate::{ContextAttributes, ContextID, Error, GLApi, SurfaceAccess, SurfaceInfo, SurfaceType};
+use crate::SurfaceAccess;
+use crate::{AdapterInfo, ContextAttributes, ContextID, ContextResetStatus, Error, GLApi};
+use crate::{ContextNegotiation, ContextNegotiationReport, DebugMessage, DebugMessageFilter};
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::{DmaBufDescriptor, SurfaceHandle};
+use crate::{PixelFormatInfo, PixelFormatPreferences, SurfaceFormat, SurfaceInfo, SurfaceType};
 use euclid::default::Size2D;
 
 use std::os::raw::c_void;
@@ -47,6 +52,34 @@ impl DeviceInterface for Device {
         Device::gl_api(self)
     }
 
+    #[inline]
+    fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        Device::adapter_info(self)
+    }
+
+    #[inline]
+    fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Device::supported_surface_formats(self)
+    }
+
+    #[inline]
+    fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        Device::set_debug_message_callback(self, callback)
+    }
+
+    #[inline]
+    fn clear_debug_message_callback(&self) {
+        Device::clear_debug_message_callback(self)
+    }
+
+    #[inline]
+    fn set_debug_message_filter(&self, filter: DebugMessageFilter) {
+        Device::set_debug_message_filter(self, filter)
+    }
+
     // context.rs
 
     #[inline]
@@ -57,6 +90,31 @@ impl DeviceInterface for Device {
         Device::create_context_descriptor(self, attributes)
     }
 
+    #[inline]
+    fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<Self::ContextDescriptor>, Error> {
+        Device::enumerate_context_descriptors(self, attributes)
+    }
+
+    #[inline]
+    fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<Self::ContextDescriptor, Error> {
+        Device::choose_context_descriptor(self, attributes, preferences)
+    }
+
+    #[inline]
+    fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(Self::ContextDescriptor, ContextNegotiationReport), Error> {
+        Device::negotiate_context_descriptor(self, negotiation)
+    }
+
     #[inline]
     fn create_context(
         &mut self,
@@ -94,6 +152,11 @@ impl DeviceInterface for Device {
         Device::make_no_context_current(self)
     }
 
+    #[inline]
+    fn context_reset_status(&self, context: &Context) -> Result<ContextResetStatus, Error> {
+        Device::context_reset_status(self, context)
+    }
+
     #[inline]
     fn context_descriptor_attributes(
         &self,
@@ -102,6 +165,14 @@ impl DeviceInterface for Device {
         Device::context_descriptor_attributes(self, context_descriptor)
     }
 
+    #[inline]
+    fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &Self::ContextDescriptor,
+    ) -> PixelFormatInfo {
+        Device::context_descriptor_pixel_format(self, context_descriptor)
+    }
+
     #[inline]
     fn get_proc_address(&self, context: &Self::Context, symbol_name: &str) -> *const c_void {
         Device::get_proc_address(self, context, symbol_name)
@@ -160,6 +231,26 @@ impl DeviceInterface for Device {
         Device::create_surface_texture(self, context, surface)
     }
 
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Surface, Error> {
+        Device::create_surface_from_dmabuf(self, context, descriptor)
+    }
+
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture, Error> {
+        Device::create_surface_texture_from_dmabuf(self, context, descriptor)
+    }
+
     #[inline]
     fn destroy_surface(
         &self,
@@ -178,6 +269,28 @@ impl DeviceInterface for Device {
         Device::destroy_surface_texture(self, context, surface_texture)
     }
 
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn export_surface_dmabuf(&self, surface: &Surface) -> Result<DmaBufDescriptor, Error> {
+        Device::export_surface_dmabuf(self, surface)
+    }
+
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_handle(&self, surface: &Surface) -> Result<SurfaceHandle, Error> {
+        Device::create_surface_handle(self, surface)
+    }
+
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture, Error> {
+        Device::create_surface_texture_from_handle(self, context, handle)
+    }
+
     #[inline]
     fn surface_gl_texture_target(&self) -> GLenum {
         Device::surface_gl_texture_target(self)
@@ -202,6 +315,16 @@ impl DeviceInterface for Device {
         Device::resize_surface(self, context, surface, size)
     }
 
+    #[inline]
+    fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        Device::resize_surface_preserving_contents(self, context, surface, size)
+    }
+
     #[inline]
     fn surface_info(&self, surface: &Self::Surface) -> SurfaceInfo {
         Device::surface_info(self, surface)
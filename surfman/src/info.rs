@@ -7,6 +7,7 @@ use crate::Gl;
 
 use std::ffi::CStr;
 use std::os::raw::c_char;
+use std::path::PathBuf;
 
 /// The API (OpenGL or OpenGL ES).
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -63,3 +64,90 @@ impl GLVersion {
         }
     }
 }
+
+/// Describes the hardware (or software rasterizer) that an adapter renders with.
+///
+/// This is returned by `Connection::adapter_info()` and `Device::adapter_info()`. Fields that the
+/// platform has no way of reporting are `None`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AdapterInfo {
+    /// The vendor of the OpenGL implementation (`GL_VENDOR`).
+    pub vendor: String,
+    /// The name of the renderer (`GL_RENDERER`).
+    pub renderer: String,
+    /// The name of the driver, if known (for example, `iris` or `llvmpipe` on Mesa).
+    pub driver: Option<String>,
+    /// The path to the DRM render node that this adapter renders with, if any.
+    pub drm_render_node: Option<PathBuf>,
+    /// True if this adapter renders on the CPU.
+    pub software: bool,
+    /// The OpenGL or OpenGL ES version of a context created with default attributes.
+    pub gl_version: GLVersion,
+    /// The EGL version of the display, as a `(major, minor)` pair, if the adapter is driven via
+    /// EGL.
+    pub egl_version: Option<(u8, u8)>,
+    /// The entry points that displays and window surfaces are created with, if the adapter is
+    /// driven via EGL.
+    pub egl_platform_path: Option<EGLPlatformPath>,
+}
+
+impl AdapterInfo {
+    // Describes the adapter of the current context from its OpenGL strings, on platforms that
+    // have no other way to identify it.
+    #[allow(dead_code)]
+    pub(crate) fn from_current_context(gl: &Gl) -> AdapterInfo {
+        let get_string = |name| unsafe {
+            let string = gl.GetString(name) as *const c_char;
+            if string.is_null() {
+                String::new()
+            } else {
+                CStr::from_ptr(string).to_string_lossy().into_owned()
+            }
+        };
+
+        let renderer = get_string(gl::RENDERER);
+        AdapterInfo {
+            vendor: get_string(gl::VENDOR),
+            software: renderer_is_software(&renderer),
+            renderer,
+            driver: None,
+            drm_render_node: None,
+            gl_version: GLVersion::current(gl),
+            egl_version: None,
+            egl_platform_path: None,
+        }
+    }
+}
+
+// Recognizes software rasterizers by their `GL_RENDERER` strings.
+#[allow(dead_code)]
+pub(crate) fn renderer_is_software(renderer: &str) -> bool {
+    [
+        "llvmpipe",
+        "softpipe",
+        "SwiftShader",
+        "Software Rasterizer",
+        "Apple Software Renderer",
+        "GDI Generic",
+    ]
+    .iter()
+    .any(|name| renderer.contains(name))
+}
+
+/// The entry points that an EGL display and its window surfaces are created with.
+///
+/// Drivers that stop at EGL 1.4 lack `eglGetPlatformDisplay()` and
+/// `eglCreatePlatformWindowSurface()`, so older equivalents are used instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EGLPlatformPath {
+    /// The EGL 1.5 `eglGetPlatformDisplay()` and `eglCreatePlatformWindowSurface()` functions.
+    Core,
+    /// The `eglGetPlatformDisplayEXT()` and `eglCreatePlatformWindowSurfaceEXT()` functions from
+    /// `EGL_EXT_platform_base`.
+    Extension,
+    /// The EGL 1.0 `eglGetDisplay()` and `eglCreateWindowSurface()` functions.
+    ///
+    /// These can only create displays for the native platform that the EGL library guesses from
+    /// the native display, and can't pass display attributes, so not every adapter is available.
+    Legacy,
+}
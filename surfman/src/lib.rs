@@ -36,6 +36,13 @@ pub use platform::system::device::{Adapter as SystemAdapter, Device as SystemDev
 #[cfg(target_os = "macos")]
 pub use platform::system::surface::Surface as SystemSurface;
 
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use platform::generic::egl::handle::SurfaceHandle;
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+pub use platform::generic::egl::loader::set_egl_library_path;
+#[cfg(all(unix, not(target_os = "macos")))]
+pub use platform::generic::egl::surface::{DmaBufDescriptor, DmaBufPlane};
+
 #[cfg(feature = "chains")]
 pub mod chains;
 pub mod connection;
@@ -45,13 +52,21 @@ pub mod error;
 pub use crate::error::{Error, WindowingApiError};
 
 mod context;
-pub use crate::context::{ContextAttributeFlags, ContextAttributes, ContextID};
+pub use crate::context::{ContextAttributeFlags, ContextAttributes, ContextID, ContextPriority};
+pub use crate::context::{ContextNegotiation, ContextNegotiationReport};
+pub use crate::context::{ContextReleaseBehavior, ContextResetStatus};
+pub use crate::context::{PixelFormatCaveat, PixelFormatInfo, PixelFormatPreferences};
+
+mod debug;
+pub use crate::debug::{DebugMessage, DebugMessageCallback, DebugMessageFilter};
+pub use crate::debug::{DebugMessageSeverity, DebugMessageSource, DebugMessageType};
 
 mod info;
-pub use crate::info::{GLApi, GLVersion};
+pub use crate::info::{AdapterInfo, EGLPlatformPath, GLApi, GLVersion};
 
 mod surface;
-pub use crate::surface::{SurfaceAccess, SurfaceID, SurfaceInfo, SurfaceType, SystemSurfaceInfo};
+pub use crate::surface::{SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceInfo};
+pub use crate::surface::{SurfaceType, SystemSurfaceInfo};
 
 pub mod macros;
 
@@ -67,6 +82,11 @@ mod gl {
     include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
 }
 
+#[cfg(all(linux, test))]
+mod gles {
+    include!(concat!(env!("OUT_DIR"), "/gles_bindings.rs"));
+}
+
 #[cfg(any(
     target_os = "android",
     all(target_os = "windows", feature = "sm-angle"),
@@ -8,7 +8,7 @@ use super::device::{Adapter, Device, NativeDevice};
 use super::ffi::ANativeWindow;
 use super::surface::NativeWidget;
 use crate::Error;
-use crate::GLApi;
+use crate::{AdapterInfo, GLApi};
 
 use euclid::default::Size2D;
 
@@ -76,9 +76,19 @@ impl Connection {
         Ok(Adapter)
     }
 
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
+    }
+
     /// Opens the hardware device corresponding to the given adapter.
     ///
     /// Device handles are local to a single thread.
+    ///
+    /// Surfaces on this backend are hardware buffers wrapped in EGL images, so this returns a
+    /// `RequiredExtensionUnavailable` error if the driver doesn't support
+    /// `EGL_ANDROID_image_native_buffer`.
     #[inline]
     pub fn create_device(&self, _: &Adapter) -> Result<Device, Error> {
         Device::new()
@@ -94,10 +104,12 @@ impl Connection {
         &self,
         native_device: NativeDevice,
     ) -> Result<Device, Error> {
-        Ok(Device {
+        let device = Device {
             egl_display: native_device.0,
             display_is_owned: false,
-        })
+        };
+        device.check_extensions()?;
+        Ok(device)
     }
 
     /// Opens the display connection corresponding to the given `winit` window.
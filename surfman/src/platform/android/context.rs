@@ -4,7 +4,7 @@
 
 use super::device::Device;
 use super::surface::{Surface, SurfaceObjects};
-use crate::context::{ContextID, CREATE_CONTEXT_MUTEX};
+use crate::context::{current_context_reset_status, ContextID, CREATE_CONTEXT_MUTEX};
 use crate::egl;
 use crate::egl::types::{EGLConfig, EGLContext, EGLSurface, EGLint};
 use crate::platform::generic::egl::context::{self, CurrentContextGuard};
@@ -12,7 +12,9 @@ use crate::platform::generic::egl::device::EGL_FUNCTIONS;
 use crate::platform::generic::egl::error::ToWindowingApiError;
 use crate::platform::generic::egl::surface::ExternalEGLSurfaces;
 use crate::surface::Framebuffer;
-use crate::{ContextAttributes, Error, Gl, SurfaceInfo};
+use crate::{ContextAttributeFlags, ContextAttributes, ContextResetStatus, Error, Gl, SurfaceInfo};
+use crate::{ContextNegotiation, ContextNegotiationReport};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
 
 use std::mem;
 use std::os::raw::c_void;
@@ -20,6 +22,14 @@ use std::thread;
 
 pub use crate::platform::generic::egl::context::{ContextDescriptor, NativeContext};
 
+// Contexts render to pbuffers, which need an RGB color buffer.
+const CONFIG_ATTRIBUTES: [EGLint; 4] = [
+    egl::COLOR_BUFFER_TYPE as EGLint,
+    egl::RGB_BUFFER as EGLint,
+    egl::SURFACE_TYPE as EGLint,
+    egl::PBUFFER_BIT as EGLint,
+];
+
 thread_local! {
     #[doc(hidden)]
     pub static GL_FUNCTIONS: Gl = Gl::load_with(context::get_proc_address);
@@ -67,22 +77,61 @@ impl Device {
         &self,
         attributes: &ContextAttributes,
     ) -> Result<ContextDescriptor, Error> {
+        self.choose_context_descriptor(attributes, &PixelFormatPreferences::default())
+    }
+
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// The descriptors are in the order that EGL sorts their configs in, which isn't necessarily
+    /// the order that `create_context_descriptor()` prefers. Context descriptors are local to this
+    /// device.
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor>, Error> {
         unsafe {
-            ContextDescriptor::new(
+            ContextDescriptor::enumerate(
                 self.egl_display,
-                attributes,
-                &[
-                    egl::COLOR_BUFFER_TYPE as EGLint,
-                    egl::RGB_BUFFER as EGLint,
-                    egl::SURFACE_TYPE as EGLint,
-                    egl::PBUFFER_BIT as EGLint,
-                    egl::RENDERABLE_TYPE as EGLint,
-                    egl::OPENGL_ES2_BIT as EGLint,
-                ],
+                &supported_attributes(attributes)?,
+                self.gl_api(),
+                &CONFIG_ATTRIBUTES,
             )
         }
     }
 
+    /// Creates a context descriptor with the given attributes, choosing the pixel format that
+    /// ranks highest according to the given preferences.
+    ///
+    /// Context descriptors are local to this device.
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor, Error> {
+        unsafe {
+            ContextDescriptor::choose(
+                self.egl_display,
+                &supported_attributes(attributes)?,
+                self.gl_api(),
+                &CONFIG_ATTRIBUTES,
+                preferences,
+            )
+        }
+    }
+
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    #[inline]
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor, ContextNegotiationReport), Error> {
+        crate::context::negotiate_context_descriptor(self, negotiation)
+    }
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -98,12 +147,10 @@ impl Device {
 
         unsafe {
             // Create the EGL context.
-            let gl_api = self.gl_api();
             let egl_context = context::create_context(
                 egl_display,
                 descriptor,
                 share_with.map_or(egl::NO_CONTEXT, |ctx| ctx.egl_context),
-                gl_api,
             )?;
 
             // Create a dummy pbuffer.
@@ -304,6 +351,15 @@ impl Device {
         unsafe { context_descriptor.attributes(self.egl_display) }
     }
 
+    /// Returns the properties of the EGL config that the context descriptor selects.
+    #[inline]
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> PixelFormatInfo {
+        unsafe { context_descriptor.pixel_format(self.egl_display) }
+    }
+
     /// Fetches the address of an OpenGL function associated with this context.
     ///
     /// OpenGL functions are local to a context. You should not use OpenGL functions on one context
@@ -316,11 +372,30 @@ impl Device {
         context::get_proc_address(symbol_name)
     }
 
-    pub(crate) fn context_to_egl_config(
-        &self,
-        context: &Context,
-    ) -> EGLConfig {
-        unsafe { context::egl_config_from_id(self.egl_display, context::get_context_attr(self.egl_display, context.egl_context, egl::CONFIG_ID as EGLint)) }
+    pub(crate) fn context_to_egl_config(&self, context: &Context) -> EGLConfig {
+        unsafe {
+            context::egl_config_from_id(
+                self.egl_display,
+                context::get_context_attr(
+                    self.egl_display,
+                    context.egl_context,
+                    egl::CONFIG_ID as EGLint,
+                ),
+            )
+        }
+    }
+
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated. If the driver lacks
+    /// the `glGetGraphicsResetStatus()` function, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    ///
+    /// The context is briefly made current in order to query its status.
+    pub fn context_reset_status(&self, context: &Context) -> Result<ContextResetStatus, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        unsafe { current_context_reset_status(context::get_proc_address) }
     }
 
     pub(crate) fn temporarily_make_context_current(
@@ -374,3 +449,16 @@ impl Device {
         }
     }
 }
+
+// Multisampled surfaces aren't supported on this backend, so they fall back to single-sampled
+// ones. Configless contexts aren't supported either, and the dummy pbuffers that contexts render
+// to when no surface is bound need a config.
+fn supported_attributes(attributes: &ContextAttributes) -> Result<ContextAttributes, Error> {
+    if attributes.flags.contains(ContextAttributeFlags::NO_CONFIG) {
+        return Err(Error::RequiredExtensionUnavailable);
+    }
+    Ok(ContextAttributes {
+        samples: 0,
+        ..*attributes
+    })
+}
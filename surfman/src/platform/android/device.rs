@@ -5,8 +5,9 @@
 use super::connection::Connection;
 use crate::egl;
 use crate::egl::types::EGLDisplay;
-use crate::platform::generic::egl::device::EGL_FUNCTIONS;
-use crate::{Error, GLApi};
+use crate::platform::generic::egl::device::{self, EGL_FUNCTIONS};
+use crate::platform::generic::egl::ffi::EGL_EXTENSION_FUNCTIONS;
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
 
 /// Represents a hardware display adapter that can be used for rendering (including the CPU).
 ///
@@ -61,14 +62,34 @@ impl Device {
                 let result = egl.Initialize(egl_display, &mut major_version, &mut minor_version);
                 assert_ne!(result, egl::FALSE);
 
-                Ok(Device {
+                // Dropping the device terminates the display if this fails.
+                let device = Device {
                     egl_display,
                     display_is_owned: true,
-                })
+                };
+                device.check_extensions()?;
+                Ok(device)
             }
         })
     }
 
+    // Surfaces are hardware buffers wrapped in EGL images, so there's no way to work without
+    // them.
+    pub(crate) fn check_extensions(&self) -> Result<(), Error> {
+        let supported = EGL_EXTENSION_FUNCTIONS.CreateImageKHR.is_some()
+            && EGL_EXTENSION_FUNCTIONS.DestroyImageKHR.is_some()
+            && EGL_EXTENSION_FUNCTIONS.ImageTargetTexture2DOES.is_some()
+            && EGL_EXTENSION_FUNCTIONS
+                .GetNativeClientBufferANDROID
+                .is_some()
+            && device::has_display_extension(self.egl_display, "EGL_ANDROID_image_native_buffer");
+        if supported {
+            Ok(())
+        } else {
+            Err(Error::RequiredExtensionUnavailable)
+        }
+    }
+
     /// Returns the EGL display corresponding to this device.
     #[inline]
     pub fn native_device(&self) -> NativeDevice {
@@ -92,4 +113,41 @@ impl Device {
     pub fn gl_api(&self) -> GLApi {
         GLApi::GLES
     }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The GL strings are read from a temporary context.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        unsafe { device::query_adapter_info(self.egl_display, self.gl_api()) }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Only `RGBA8` is supported on this backend.
+    #[inline]
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Ok(vec![SurfaceFormat::RGBA8])
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// This backend doesn't route debug messages yet, so the callback is never called.
+    #[inline]
+    pub fn set_debug_message_callback<F>(&self, _: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+    }
+
+    /// Removes the debug message callback.
+    ///
+    /// This backend doesn't route debug messages yet, so this does nothing.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {}
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// This backend doesn't route debug messages yet, so this does nothing.
+    #[inline]
+    pub fn set_debug_message_filter(&self, _: DebugMessageFilter) {}
 }
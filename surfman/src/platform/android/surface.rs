@@ -23,7 +23,8 @@ use crate::platform::generic::egl::ffi::EGL_IMAGE_PRESERVED_KHR;
 use crate::platform::generic::egl::ffi::EGL_NATIVE_BUFFER_ANDROID;
 use crate::platform::generic::egl::ffi::EGL_NO_IMAGE_KHR;
 use crate::renderbuffers::Renderbuffers;
-use crate::{Error, SurfaceAccess, SurfaceID, SurfaceInfo, SurfaceType, WindowingApiError};
+use crate::WindowingApiError;
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceInfo, SurfaceType};
 
 use euclid::default::Size2D;
 use std::fmt::{self, Debug, Formatter};
@@ -33,6 +34,8 @@ use std::ptr;
 use std::thread;
 
 pub use crate::platform::generic::egl::context::ContextDescriptor;
+pub use crate::platform::generic::egl::handle::SurfaceHandle;
+pub use crate::platform::generic::egl::surface::{DmaBufDescriptor, DmaBufPlane};
 
 const SURFACE_GL_TEXTURE_TARGET: GLenum = gl::TEXTURE_2D;
 
@@ -127,7 +130,11 @@ impl Device {
         surface_type: SurfaceType<NativeWidget>,
     ) -> Result<Surface, Error> {
         match surface_type {
-            SurfaceType::Generic { size } => self.create_generic_surface(context, &size),
+            SurfaceType::Generic {
+                size,
+                format: SurfaceFormat::RGBA8,
+            } => self.create_generic_surface(context, &size),
+            SurfaceType::Generic { .. } => Err(Error::UnsupportedSurfaceFormat),
             SurfaceType::Widget { native_widget } => unsafe {
                 self.create_window_surface(context, native_widget.native_window)
             },
@@ -164,11 +171,18 @@ impl Device {
                 }
 
                 // Create an EGL image, and bind it to a texture.
-                let egl_image = self.create_egl_image(context, hardware_buffer);
+                let egl_image = match self.create_egl_image(context, hardware_buffer) {
+                    Ok(egl_image) => egl_image,
+                    Err(err) => {
+                        AHardwareBuffer_release(hardware_buffer);
+                        return Err(err);
+                    }
+                };
 
-                // Initialize and bind the image to the texture.
+                // Initialize and bind the image to the texture. `create_egl_image()` checked for
+                // `GL_OES_EGL_image`, so this can't fail.
                 let texture_object =
-                    generic::egl::surface::bind_egl_image_to_gl_texture(gl, egl_image);
+                    generic::egl::surface::bind_egl_image_to_gl_texture(gl, egl_image)?;
 
                 // Create the framebuffer, and bind the texture to it.
                 let framebuffer_object = gl_utils::create_and_bind_framebuffer(
@@ -256,9 +270,17 @@ impl Device {
                         Err(err) => return Err((err, surface)),
                     };
 
-                    let local_egl_image = self.create_egl_image(context, hardware_buffer);
-                    let texture_object =
-                        generic::egl::surface::bind_egl_image_to_gl_texture(gl, local_egl_image);
+                    let local_egl_image = match self.create_egl_image(context, hardware_buffer) {
+                        Ok(local_egl_image) => local_egl_image,
+                        Err(err) => return Err((err, surface)),
+                    };
+                    let texture_object = match generic::egl::surface::bind_egl_image_to_gl_texture(
+                        gl,
+                        local_egl_image,
+                    ) {
+                        Ok(texture_object) => texture_object,
+                        Err(err) => return Err((err, surface)),
+                    };
                     Ok(SurfaceTexture {
                         surface,
                         local_egl_image,
@@ -304,18 +326,40 @@ impl Device {
         Ok(())
     }
 
+    /// Resizes a surface, keeping its contents.
+    ///
+    /// `resize_surface()` never reallocates surfaces on this backend, so this is equivalent to
+    /// it.
+    #[inline]
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.resize_surface(context, surface, size)
+    }
+
     #[allow(non_snake_case)]
     unsafe fn create_egl_image(
         &self,
         _: &Context,
         hardware_buffer: *mut AHardwareBuffer,
-    ) -> EGLImageKHR {
+    ) -> Result<EGLImageKHR, Error> {
+        // The image is bound to a texture afterward, so the `GL_OES_EGL_image` entry point is
+        // needed too.
+        let (eglGetNativeClientBufferANDROID, eglCreateImageKHR) = match (
+            EGL_EXTENSION_FUNCTIONS.GetNativeClientBufferANDROID,
+            EGL_EXTENSION_FUNCTIONS.CreateImageKHR,
+            EGL_EXTENSION_FUNCTIONS.ImageTargetTexture2DOES,
+        ) {
+            (Some(eglGetNativeClientBufferANDROID), Some(eglCreateImageKHR), Some(_)) => {
+                (eglGetNativeClientBufferANDROID, eglCreateImageKHR)
+            }
+            _ => return Err(Error::RequiredExtensionUnavailable),
+        };
+
         // Get the native client buffer.
-        let eglGetNativeClientBufferANDROID =
-            EGL_EXTENSION_FUNCTIONS.GetNativeClientBufferANDROID.expect(
-                "Where's the `EGL_ANDROID_get_native_client_buffer` \
-                                            extension?",
-            );
         let client_buffer =
             eglGetNativeClientBufferANDROID(hardware_buffer as *const AHardwareBuffer as *const _);
         assert!(!client_buffer.is_null());
@@ -327,7 +371,7 @@ impl Device {
             egl::NONE as EGLint,
             0,
         ];
-        let egl_image = (EGL_EXTENSION_FUNCTIONS.CreateImageKHR)(
+        let egl_image = eglCreateImageKHR(
             self.egl_display,
             egl::NO_CONTEXT,
             EGL_NATIVE_BUFFER_ANDROID,
@@ -335,7 +379,7 @@ impl Device {
             egl_image_attributes.as_ptr(),
         );
         assert_ne!(egl_image, EGL_NO_IMAGE_KHR);
-        egl_image
+        Ok(egl_image)
     }
 
     /// Destroys a surface.
@@ -345,6 +389,7 @@ impl Device {
     ///
     /// You must explicitly call this method to dispose of a surface. Otherwise, a panic occurs in
     /// the `drop` method.
+    #[allow(non_snake_case)]
     pub fn destroy_surface(
         &self,
         context: &mut Context,
@@ -363,6 +408,11 @@ impl Device {
                     ref mut texture_object,
                     ref mut renderbuffers,
                 } => {
+                    // Check this first, so that nothing is destroyed if it's missing.
+                    let eglDestroyImageKHR = EGL_EXTENSION_FUNCTIONS
+                        .DestroyImageKHR
+                        .ok_or(Error::RequiredExtensionUnavailable)?;
+
                     GL_FUNCTIONS.with(|gl| {
                         gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
                         gl.DeleteFramebuffers(1, framebuffer_object);
@@ -374,8 +424,7 @@ impl Device {
                         *texture_object = 0;
 
                         let egl_display = self.egl_display;
-                        let result =
-                            (EGL_EXTENSION_FUNCTIONS.DestroyImageKHR)(egl_display, *egl_image);
+                        let result = eglDestroyImageKHR(egl_display, *egl_image);
                         assert_ne!(result, egl::FALSE);
                         *egl_image = EGL_NO_IMAGE_KHR;
 
@@ -403,11 +452,17 @@ impl Device {
     ///
     /// All surface textures must be explicitly destroyed with this function, or a panic will
     /// occur.
+    #[allow(non_snake_case)]
     pub fn destroy_surface_texture(
         &self,
         context: &mut Context,
         mut surface_texture: SurfaceTexture,
     ) -> Result<Surface, (Error, SurfaceTexture)> {
+        let eglDestroyImageKHR = match EGL_EXTENSION_FUNCTIONS.DestroyImageKHR {
+            Some(eglDestroyImageKHR) => eglDestroyImageKHR,
+            None => return Err((Error::RequiredExtensionUnavailable, surface_texture)),
+        };
+
         let _guard = self.temporarily_make_context_current(context);
         GL_FUNCTIONS.with(|gl| {
             unsafe {
@@ -415,10 +470,7 @@ impl Device {
                 surface_texture.texture_object = 0;
 
                 let egl_display = self.egl_display;
-                let result = (EGL_EXTENSION_FUNCTIONS.DestroyImageKHR)(
-                    egl_display,
-                    surface_texture.local_egl_image,
-                );
+                let result = eglDestroyImageKHR(egl_display, surface_texture.local_egl_image);
                 assert_ne!(result, egl::FALSE);
                 surface_texture.local_egl_image = EGL_NO_IMAGE_KHR;
             }
@@ -434,6 +486,60 @@ impl Device {
         Err(Error::Unimplemented)
     }
 
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    ///
+    /// This isn't supported on Android, so it returns a `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_from_dmabuf(
+        &mut self,
+        _: &Context,
+        _: DmaBufDescriptor,
+    ) -> Result<Surface, Error> {
+        Err(Error::RequiredExtensionUnavailable)
+    }
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    ///
+    /// This isn't supported on Android, so it returns a `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_texture_from_dmabuf(
+        &self,
+        _: &mut Context,
+        _: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture, Error> {
+        Err(Error::RequiredExtensionUnavailable)
+    }
+
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    ///
+    /// This isn't supported on Android, so it returns a `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn export_surface_dmabuf(&self, _: &Surface) -> Result<DmaBufDescriptor, Error> {
+        Err(Error::RequiredExtensionUnavailable)
+    }
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    ///
+    /// Like `export_surface_dmabuf()`, this always returns a `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_handle(&self, surface: &Surface) -> Result<SurfaceHandle, Error> {
+        self.export_surface_dmabuf(surface)
+            .map(SurfaceHandle::from_dmabuf)
+    }
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    ///
+    /// Like `create_surface_texture_from_dmabuf()`, this always returns a
+    /// `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture, Error> {
+        self.create_surface_texture_from_dmabuf(context, handle.into_dmabuf())
+    }
+
     /// Returns the OpenGL texture target needed to read from this surface texture.
     ///
     /// This will be `GL_TEXTURE_2D` or `GL_TEXTURE_RECTANGLE`, depending on platform.
@@ -459,6 +565,7 @@ impl Device {
                 } => framebuffer_object,
                 SurfaceObjects::Window { .. } => 0,
             },
+            format: SurfaceFormat::RGBA8,
         }
     }
 
@@ -2,26 +2,44 @@
 //
 //! Functionality common to backends using EGL contexts.
 
-use super::device::EGL_FUNCTIONS;
+use super::debug as egl_debug;
+use super::device::{self, EGL_FUNCTIONS};
 use super::error::ToWindowingApiError;
-use super::ffi::EGL_CONTEXT_OPENGL_PROFILE_MASK;
+use super::ffi::EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_KHR;
+use super::ffi::EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR;
+use super::ffi::EGL_CONTEXT_PRIORITY_LOW_IMG;
+use super::ffi::EGL_NO_CONFIG_KHR;
+use super::ffi::{EGL_CONTEXT_FLAGS_KHR, EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR};
 use super::ffi::{EGL_CONTEXT_MINOR_VERSION_KHR, EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT};
+use super::ffi::{EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE_BIT_KHR, EGL_CONTEXT_OPENGL_NO_ERROR_KHR};
+use super::ffi::{EGL_CONTEXT_OPENGL_PROFILE_MASK, EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT};
+use super::ffi::{
+    EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT, EGL_LOSE_CONTEXT_ON_RESET_KHR,
+};
+use super::ffi::{EGL_CONTEXT_PRIORITY_HIGH_IMG, EGL_CONTEXT_PRIORITY_LEVEL_IMG};
+use super::ffi::{EGL_CONTEXT_RELEASE_BEHAVIOR_KHR, EGL_CONTEXT_RELEASE_BEHAVIOR_NONE_KHR};
 use super::surface::{EGLBackedSurface, ExternalEGLSurfaces};
 use crate::context::{self, CREATE_CONTEXT_MUTEX};
+use crate::debug::{self, DebugSink};
 use crate::egl;
-use crate::egl::types::{EGLConfig, EGLContext, EGLDisplay, EGLSurface, EGLint};
+use crate::egl::types::{EGLConfig, EGLContext, EGLDisplay, EGLSurface, EGLenum, EGLint};
 use crate::surface::Framebuffer;
-use crate::{ContextAttributeFlags, ContextAttributes, ContextID, Error, GLApi, GLVersion};
-use crate::{Gl, SurfaceInfo};
+use crate::WindowingApiError;
+use crate::{ContextAttributeFlags, ContextAttributes, ContextID, ContextResetStatus, Error};
+use crate::{ContextPriority, ContextReleaseBehavior, GLApi, GLVersion, Gl, SurfaceInfo};
+use crate::{PixelFormatCaveat, PixelFormatInfo, PixelFormatPreferences};
 
 use std::ffi::CString;
 use std::mem;
 use std::os::raw::{c_char, c_void};
 use std::ptr;
+use std::sync::Arc;
 use std::thread;
 
 #[allow(dead_code)]
 const DUMMY_PBUFFER_SIZE: EGLint = 16;
+// Only widget surfaces and pbuffers use the color buffer of the config. Generic surfaces are
+// textures, whose format is chosen when they're created.
 const RGB_CHANNEL_BIT_DEPTH: EGLint = 8;
 
 pub(crate) struct EGLBackedContext {
@@ -29,6 +47,14 @@ pub(crate) struct EGLBackedContext {
     pub(crate) id: ContextID,
     framebuffer: Framebuffer<EGLBackedSurface, ExternalEGLSurfaces>,
     context_is_owned: bool,
+    // The sink that the driver delivers debug messages to, if this is a debug context. This must
+    // outlive the EGL context.
+    debug_sink: Option<Arc<DebugSink>>,
+    // The number of samples per pixel of generic surfaces created for this context.
+    pub(crate) samples: u8,
+    // The config of the surfaces that are created for this context. Unlike the `EGL_CONFIG_ID`
+    // of the EGL context, this is known even if the context was created without a config.
+    pub(crate) egl_config_id: EGLint,
 }
 
 /// Wrapper for a native `EGLContext`.
@@ -48,8 +74,18 @@ pub struct NativeContext {
 #[derive(Clone)]
 pub struct ContextDescriptor {
     pub(crate) egl_config_id: EGLint,
+    pub(crate) gl_api: GLApi,
     pub(crate) gl_version: GLVersion,
     pub(crate) compatibility_profile: bool,
+    pub(crate) robust_access: bool,
+    pub(crate) lose_context_on_reset: bool,
+    pub(crate) debug: bool,
+    pub(crate) forward_compatible: bool,
+    pub(crate) no_error: bool,
+    pub(crate) priority: ContextPriority,
+    pub(crate) release_behavior: ContextReleaseBehavior,
+    pub(crate) samples: u8,
+    pub(crate) no_config: bool,
 }
 
 #[must_use]
@@ -86,10 +122,11 @@ impl Drop for CurrentContextGuard {
 
 impl EGLBackedContext {
     pub(crate) unsafe fn new(
+        gl: &Gl,
         egl_display: EGLDisplay,
         descriptor: &ContextDescriptor,
         share_with: Option<&EGLBackedContext>,
-        gl_api: GLApi,
+        debug_sink: &Arc<DebugSink>,
     ) -> Result<EGLBackedContext, Error> {
         let mut next_context_id = CREATE_CONTEXT_MUTEX.lock().unwrap();
 
@@ -98,21 +135,41 @@ impl EGLBackedContext {
             egl_display,
             descriptor,
             share_with.map_or(egl::NO_CONTEXT, |ctx| ctx.egl_context),
-            gl_api,
         )?;
 
+        // Route the debug messages of debug contexts to the device's sink.
+        let debug_sink = if descriptor.debug {
+            egl_debug::route_egl_debug_messages_to(debug_sink);
+            EGL_FUNCTIONS.with(|egl| {
+                let _guard = CurrentContextGuard::new();
+                egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
+                if !debug::install_gl_debug_message_callback(gl, get_proc_address, debug_sink) {
+                    warn!("Debug context was created, but the driver lacks debug output");
+                }
+            });
+            Some(debug_sink.clone())
+        } else {
+            None
+        };
+
         // Wrap and return it.
         let context = EGLBackedContext {
             egl_context,
             id: *next_context_id,
             framebuffer: Framebuffer::None,
             context_is_owned: true,
+            debug_sink,
+            samples: descriptor.samples,
+            egl_config_id: descriptor.egl_config_id,
         };
         next_context_id.0 += 1;
         Ok(context)
     }
 
-    pub(crate) unsafe fn from_native_context(native_context: NativeContext) -> EGLBackedContext {
+    pub(crate) unsafe fn from_native_context(
+        egl_display: EGLDisplay,
+        native_context: NativeContext,
+    ) -> EGLBackedContext {
         let mut next_context_id = CREATE_CONTEXT_MUTEX.lock().unwrap();
         let context = EGLBackedContext {
             egl_context: native_context.egl_context,
@@ -122,11 +179,48 @@ impl EGLBackedContext {
                 read: native_context.egl_read_surface,
             }),
             context_is_owned: false,
+            debug_sink: None,
+            samples: 0,
+            egl_config_id: get_context_attr(
+                egl_display,
+                native_context.egl_context,
+                egl::CONFIG_ID as EGLint,
+            ),
         };
         next_context_id.0 += 1;
         context
     }
 
+    // Like `from_native_context()`, but surfaces for the context are created with the given
+    // config. This is needed for contexts created without a config (`EGL_KHR_no_config_context`),
+    // which report a config ID of 0. If the native context has no surfaces attached, surfman
+    // surfaces can be bound to it.
+    #[allow(dead_code)]
+    pub(crate) unsafe fn from_native_context_and_config(
+        egl_display: EGLDisplay,
+        native_context: NativeContext,
+        egl_config: EGLConfig,
+    ) -> Result<EGLBackedContext, Error> {
+        let egl_config_id = get_config_attr(egl_display, egl_config, egl::CONFIG_ID as EGLint);
+        let context_config_id = get_context_attr(
+            egl_display,
+            native_context.egl_context,
+            egl::CONFIG_ID as EGLint,
+        );
+        if context_config_id != 0 && context_config_id != egl_config_id {
+            return Err(Error::IncompatibleNativeContext);
+        }
+
+        let mut context = EGLBackedContext::from_native_context(egl_display, native_context);
+        context.egl_config_id = egl_config_id;
+        if native_context.egl_draw_surface == egl::NO_SURFACE
+            && native_context.egl_read_surface == egl::NO_SURFACE
+        {
+            context.framebuffer = Framebuffer::None;
+        }
+        Ok(context)
+    }
+
     pub(crate) unsafe fn destroy(&mut self, egl_display: EGLDisplay) {
         EGL_FUNCTIONS.with(|egl| {
             egl.MakeCurrent(
@@ -142,6 +236,7 @@ impl EGLBackedContext {
             }
 
             self.egl_context = egl::NO_CONTEXT;
+            self.debug_sink = None;
         });
     }
 
@@ -194,6 +289,9 @@ impl EGLBackedContext {
         if self.id != surface.context_id {
             return Err((Error::IncompatibleSurface, surface));
         }
+        if !surface.is_renderable() {
+            return Err((Error::SurfaceNotRenderable, surface));
+        }
 
         match self.framebuffer {
             Framebuffer::None => self.framebuffer = Framebuffer::Surface(surface),
@@ -220,17 +318,35 @@ impl EGLBackedContext {
             Framebuffer::External(_) => return Err(Error::ExternalRenderTarget),
         }
 
-        let surface = match mem::replace(&mut self.framebuffer, Framebuffer::None) {
+        let mut surface = match mem::replace(&mut self.framebuffer, Framebuffer::None) {
             Framebuffer::Surface(surface) => surface,
             Framebuffer::None | Framebuffer::External(_) => unreachable!(),
         };
 
+        // Resolve multisampled rendering, so that the surface is ready to be read from, whether
+        // directly, through a surface texture, or as the front buffer of a swap chain. Surfaces
+        // that other contexts can only read by copying are copied now, while this context can
+        // still be made current.
+        surface.resolve(gl, egl_display, self.egl_context);
+        surface.capture_contents(gl, egl_display);
+
         // If we're current, we stay current, but with no surface attached.
         surface.unbind(gl, egl_display, self.egl_context);
 
         Ok(Some(surface))
     }
 
+    // Returns the descriptor of this context, including the attributes that the EGL context
+    // itself doesn't record.
+    pub(crate) unsafe fn descriptor(&self, gl: &Gl, egl_display: EGLDisplay) -> ContextDescriptor {
+        let mut descriptor = ContextDescriptor::from_egl_context(gl, egl_display, self.egl_context);
+        descriptor.samples = self.samples;
+        if descriptor.no_config {
+            descriptor.egl_config_id = self.egl_config_id;
+        }
+        descriptor
+    }
+
     pub(crate) fn surface_info(&self) -> Result<Option<SurfaceInfo>, Error> {
         match self.framebuffer {
             Framebuffer::None => Ok(None),
@@ -238,6 +354,23 @@ impl EGLBackedContext {
             Framebuffer::Surface(ref surface) => Ok(Some(surface.info())),
         }
     }
+
+    pub(crate) unsafe fn reset_status(
+        &self,
+        egl_display: EGLDisplay,
+    ) -> Result<ContextResetStatus, Error> {
+        // Some drivers refuse to make a lost context current, which tells us all we need to know.
+        let _guard = CurrentContextGuard::new();
+        match self.make_current(egl_display) {
+            Ok(()) => {}
+            Err(Error::MakeCurrentFailed(WindowingApiError::ContextLost)) => {
+                return Ok(ContextResetStatus::UnknownContextReset)
+            }
+            Err(err) => return Err(err),
+        }
+
+        context::current_context_reset_status(get_proc_address)
+    }
 }
 
 impl NativeContext {
@@ -264,8 +397,45 @@ impl ContextDescriptor {
     pub(crate) unsafe fn new(
         egl_display: EGLDisplay,
         attributes: &ContextAttributes,
+        gl_api: GLApi,
+        extra_config_attributes: &[EGLint],
+    ) -> Result<ContextDescriptor, Error> {
+        ContextDescriptor::choose(
+            egl_display,
+            attributes,
+            gl_api,
+            extra_config_attributes,
+            &PixelFormatPreferences::default(),
+        )
+    }
+
+    pub(crate) unsafe fn choose(
+        egl_display: EGLDisplay,
+        attributes: &ContextAttributes,
+        gl_api: GLApi,
         extra_config_attributes: &[EGLint],
+        preferences: &PixelFormatPreferences,
     ) -> Result<ContextDescriptor, Error> {
+        let mut descriptors =
+            ContextDescriptor::enumerate(egl_display, attributes, gl_api, extra_config_attributes)?;
+
+        // The sort is stable, so configs that rank equally stay in the order that
+        // `eglChooseConfig` returned them in.
+        descriptors.sort_by_cached_key(|descriptor| {
+            preferences.rank(&descriptor.pixel_format(egl_display))
+        });
+        descriptors
+            .into_iter()
+            .next()
+            .ok_or(Error::NoPixelFormatFound)
+    }
+
+    pub(crate) unsafe fn enumerate(
+        egl_display: EGLDisplay,
+        attributes: &ContextAttributes,
+        gl_api: GLApi,
+        extra_config_attributes: &[EGLint],
+    ) -> Result<Vec<ContextDescriptor>, Error> {
         let flags = attributes.flags;
 
         let alpha_size = if flags.contains(ContextAttributeFlags::ALPHA) {
@@ -285,16 +455,77 @@ impl ContextDescriptor {
         };
 
         let compatibility_profile = flags.contains(ContextAttributeFlags::COMPATIBILITY_PROFILE);
+        let robust_access = flags.contains(ContextAttributeFlags::ROBUST_ACCESS);
+        let lose_context_on_reset = flags.contains(ContextAttributeFlags::LOSE_CONTEXT_ON_RESET);
+        let debug = flags.contains(ContextAttributeFlags::DEBUG);
+        let forward_compatible = flags.contains(ContextAttributeFlags::FORWARD_COMPATIBLE);
+        let no_error = flags.contains(ContextAttributeFlags::NO_ERROR);
+        let no_config = flags.contains(ContextAttributeFlags::NO_CONFIG);
+
+        // OpenGL ES has no profiles.
+        if compatibility_profile && gl_api == GLApi::GLES {
+            return Err(Error::UnsupportedGLProfile);
+        }
+
+        // A context that reports no errors can't be robust or report resets or debug messages.
+        if no_error && (robust_access || lose_context_on_reset || debug) {
+            return Err(Error::IncompatibleContextAttributeFlags);
+        }
 
-        // Mesa doesn't support the OpenGL compatibility profile post version 3.0. Take that into
-        // account.
-        if compatibility_profile
-            && (attributes.version.major > 3
-                || attributes.version.major == 3 && attributes.version.minor > 0)
+        // Robustness is part of `EGL_KHR_create_context` for OpenGL, but OpenGL ES needs
+        // `EGL_EXT_create_context_robustness`.
+        let robustness_extension = match gl_api {
+            GLApi::GL => "EGL_KHR_create_context",
+            GLApi::GLES => "EGL_EXT_create_context_robustness",
+        };
+        if (robust_access || lose_context_on_reset)
+            && !device::has_display_extension(egl_display, robustness_extension)
         {
-            return Err(Error::UnsupportedGLProfile);
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+        if debug && !device::has_display_extension(egl_display, "EGL_KHR_create_context") {
+            return Err(Error::RequiredExtensionUnavailable);
         }
 
+        // Forward compatibility only exists in OpenGL, and it contradicts the compatibility
+        // profile.
+        if forward_compatible {
+            if gl_api == GLApi::GLES || compatibility_profile {
+                return Err(Error::UnsupportedGLProfile);
+            }
+            if !device::has_display_extension(egl_display, "EGL_KHR_create_context") {
+                return Err(Error::RequiredExtensionUnavailable);
+            }
+        }
+        if no_error
+            && !device::has_display_extension(egl_display, "EGL_KHR_create_context_no_error")
+        {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+        if no_config && !device::has_display_extension(egl_display, "EGL_KHR_no_config_context") {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+
+        // Priority and release behavior are hints, so fall back to the defaults if they can't be
+        // requested.
+        let mut priority = attributes.priority;
+        if priority != ContextPriority::Medium
+            && !device::has_display_extension(egl_display, "EGL_IMG_context_priority")
+        {
+            priority = ContextPriority::Medium;
+        }
+        let mut release_behavior = attributes.release_behavior;
+        if release_behavior != ContextReleaseBehavior::Flush
+            && !device::has_display_extension(egl_display, "EGL_KHR_context_flush_control")
+        {
+            release_behavior = ContextReleaseBehavior::Flush;
+        }
+
+        let renderable_type = match gl_api {
+            GLApi::GL => egl::OPENGL_BIT,
+            GLApi::GLES => egl::OPENGL_ES2_BIT,
+        };
+
         // Create required config attributes.
         //
         // We check these separately because `eglChooseConfig` on its own might give us 32-bit
@@ -317,11 +548,13 @@ impl ContextDescriptor {
             depth_size,
             egl::STENCIL_SIZE as EGLint,
             stencil_size,
+            egl::RENDERABLE_TYPE as EGLint,
+            renderable_type as EGLint,
         ]);
         requested_config_attributes.extend_from_slice(extra_config_attributes);
         requested_config_attributes.extend_from_slice(&[egl::NONE as EGLint, 0, 0, 0]);
 
-        EGL_FUNCTIONS.with(|egl| {
+        let descriptors = EGL_FUNCTIONS.with(|egl| {
             // See how many applicable configs there are.
             let mut config_count = 0;
             let result = egl.ChooseConfig(
@@ -336,7 +569,7 @@ impl ContextDescriptor {
                 return Err(Error::PixelFormatSelectionFailed(err));
             }
             if config_count == 0 {
-                return Err(Error::NoPixelFormatFound);
+                return Ok(vec![]);
             }
 
             // Enumerate all those configs.
@@ -354,29 +587,84 @@ impl ContextDescriptor {
                 return Err(Error::PixelFormatSelectionFailed(err));
             }
 
-            // Sanitize configs.
-            let egl_config = configs
+            configs.truncate(real_config_count as usize);
+
+            // Sanitize configs, and create a descriptor for each one that remains.
+            Ok(configs
                 .into_iter()
                 .filter(|&egl_config| {
                     required_config_attributes
                         .chunks(2)
                         .all(|pair| get_config_attr(egl_display, egl_config, pair[0]) == pair[1])
                 })
-                .next();
-            let egl_config = match egl_config {
-                None => return Err(Error::NoPixelFormatFound),
-                Some(egl_config) => egl_config,
-            };
+                .map(|egl_config| ContextDescriptor {
+                    egl_config_id: get_config_attr(
+                        egl_display,
+                        egl_config,
+                        egl::CONFIG_ID as EGLint,
+                    ),
+                    gl_api,
+                    gl_version: attributes.version,
+                    compatibility_profile,
+                    robust_access,
+                    lose_context_on_reset,
+                    debug,
+                    forward_compatible,
+                    no_error,
+                    priority,
+                    release_behavior,
+                    samples: attributes.samples,
+                    no_config,
+                })
+                .collect::<Vec<_>>())
+        })?;
+
+        // The compatibility profile is optional after OpenGL 3.0, and drivers differ in the
+        // versions that they provide it for: older Mesa stops at 3.0, for instance, while current
+        // Mesa goes up to 4.x. The only way to find out is to try.
+        let version = attributes.version;
+        if compatibility_profile && (version.major > 3 || version.major == 3 && version.minor > 0) {
+            if let Some(descriptor) = descriptors.first() {
+                if !descriptor.supports_compatibility_profile(egl_display) {
+                    return Err(Error::UnsupportedGLProfile);
+                }
+            }
+        }
 
-            // Get the config ID and version.
-            let egl_config_id = get_config_attr(egl_display, egl_config, egl::CONFIG_ID as EGLint);
-            let gl_version = attributes.version;
+        Ok(descriptors)
+    }
 
-            Ok(ContextDescriptor {
-                egl_config_id,
-                gl_version,
-                compatibility_profile,
-            })
+    // Probes for the version and profile of this descriptor by creating a context. Drivers may
+    // accept the request and quietly create a context without the compatibility profile, so the
+    // context that results is checked as well.
+    unsafe fn supports_compatibility_profile(&self, egl_display: EGLDisplay) -> bool {
+        // Leave out the other attributes, which may fail for unrelated reasons.
+        let probe_descriptor = ContextDescriptor {
+            robust_access: false,
+            lose_context_on_reset: false,
+            debug: false,
+            forward_compatible: false,
+            no_error: false,
+            priority: ContextPriority::Medium,
+            release_behavior: ContextReleaseBehavior::Flush,
+            ..self.clone()
+        };
+        let egl_context = match create_context(egl_display, &probe_descriptor, egl::NO_CONTEXT) {
+            Ok(egl_context) => egl_context,
+            Err(_) => return false,
+        };
+
+        EGL_FUNCTIONS.with(|egl| {
+            let supported = {
+                let _guard = CurrentContextGuard::new();
+                egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context)
+                    != egl::FALSE
+                    && context::current_context_uses_compatibility_profile(&Gl::load_with(
+                        get_proc_address,
+                    ))
+            };
+            egl.DestroyContext(egl_display, egl_context);
+            supported
         })
     }
 
@@ -386,17 +674,55 @@ impl ContextDescriptor {
         egl_context: EGLContext,
     ) -> ContextDescriptor {
         let egl_config_id = get_context_attr(egl_display, egl_context, egl::CONFIG_ID as EGLint);
+        let egl_client_type =
+            get_context_attr(egl_display, egl_context, egl::CONTEXT_CLIENT_TYPE as EGLint);
+        let gl_api = if egl_client_type as EGLenum == egl::OPENGL_ES_API {
+            GLApi::GLES
+        } else {
+            GLApi::GL
+        };
+
+        // Drivers may assign a different priority than the one that was requested.
+        let priority = if device::has_display_extension(egl_display, "EGL_IMG_context_priority") {
+            let egl_priority = get_context_attr(
+                egl_display,
+                egl_context,
+                EGL_CONTEXT_PRIORITY_LEVEL_IMG as EGLint,
+            );
+            match egl_priority as EGLenum {
+                EGL_CONTEXT_PRIORITY_LOW_IMG => ContextPriority::Low,
+                EGL_CONTEXT_PRIORITY_HIGH_IMG => ContextPriority::High,
+                _ => ContextPriority::Medium,
+            }
+        } else {
+            ContextPriority::Medium
+        };
 
         EGL_FUNCTIONS.with(|egl| {
             let _guard = CurrentContextGuard::new();
             egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
             let gl_version = GLVersion::current(gl);
-            let compatibility_profile = context::current_context_uses_compatibility_profile(gl);
+            let compatibility_profile =
+                gl_api == GLApi::GL && context::current_context_uses_compatibility_profile(gl);
+            let flags = context::current_context_flags(gl, gl_api, gl_version);
 
             ContextDescriptor {
                 egl_config_id,
+                gl_api,
                 gl_version,
                 compatibility_profile,
+                robust_access: flags.contains(ContextAttributeFlags::ROBUST_ACCESS),
+                lose_context_on_reset: flags.contains(ContextAttributeFlags::LOSE_CONTEXT_ON_RESET),
+                debug: flags.contains(ContextAttributeFlags::DEBUG),
+                forward_compatible: flags.contains(ContextAttributeFlags::FORWARD_COMPATIBLE),
+                no_error: flags.contains(ContextAttributeFlags::NO_ERROR),
+                priority,
+                release_behavior: context::current_context_release_behavior(gl, gl_api, gl_version),
+                // Multisampling is done by surfaces, so the EGL context doesn't know about it.
+                // `EGLBackedContext::descriptor()` fills this in.
+                samples: 0,
+                // Contexts created without a config report a config ID of zero.
+                no_config: egl_config_id == 0,
             }
         })
     }
@@ -444,11 +770,49 @@ impl ContextDescriptor {
             ContextAttributeFlags::COMPATIBILITY_PROFILE,
             self.compatibility_profile,
         );
+        attribute_flags.set(ContextAttributeFlags::ROBUST_ACCESS, self.robust_access);
+        attribute_flags.set(ContextAttributeFlags::DEBUG, self.debug);
+        attribute_flags.set(
+            ContextAttributeFlags::FORWARD_COMPATIBLE,
+            self.forward_compatible,
+        );
+        attribute_flags.set(ContextAttributeFlags::NO_ERROR, self.no_error);
+        attribute_flags.set(ContextAttributeFlags::NO_CONFIG, self.no_config);
+        attribute_flags.set(
+            ContextAttributeFlags::LOSE_CONTEXT_ON_RESET,
+            self.lose_context_on_reset,
+        );
 
         // Create appropriate context attributes.
         ContextAttributes {
             flags: attribute_flags,
             version: self.gl_version,
+            priority: self.priority,
+            release_behavior: self.release_behavior,
+            samples: self.samples,
+        }
+    }
+
+    pub(crate) unsafe fn pixel_format(&self, egl_display: EGLDisplay) -> PixelFormatInfo {
+        let egl_config = egl_config_from_id(egl_display, self.egl_config_id);
+        let get = |attr: EGLenum| get_config_attr(egl_display, egl_config, attr as EGLint);
+
+        let caveat = match get(egl::CONFIG_CAVEAT) as EGLenum {
+            egl::SLOW_CONFIG => PixelFormatCaveat::Slow,
+            egl::NON_CONFORMANT_CONFIG => PixelFormatCaveat::NonConformant,
+            _ => PixelFormatCaveat::None,
+        };
+
+        PixelFormatInfo {
+            red_size: get(egl::RED_SIZE) as u8,
+            green_size: get(egl::GREEN_SIZE) as u8,
+            blue_size: get(egl::BLUE_SIZE) as u8,
+            alpha_size: get(egl::ALPHA_SIZE) as u8,
+            depth_size: get(egl::DEPTH_SIZE) as u8,
+            stencil_size: get(egl::STENCIL_SIZE) as u8,
+            samples: get(egl::SAMPLES) as u8,
+            caveat,
+            native_renderable: get(egl::NATIVE_RENDERABLE) != egl::FALSE as EGLint,
         }
     }
 }
@@ -470,17 +834,20 @@ pub(crate) unsafe fn create_context(
     egl_display: EGLDisplay,
     descriptor: &ContextDescriptor,
     share_with: EGLContext,
-    gl_api: GLApi,
 ) -> Result<EGLContext, Error> {
     EGL_FUNCTIONS.with(|egl| {
-        let ok = egl.BindAPI(match gl_api {
+        let ok = egl.BindAPI(match descriptor.gl_api {
             GLApi::GL => egl::OPENGL_API,
             GLApi::GLES => egl::OPENGL_ES_API,
         });
         assert_ne!(ok, egl::FALSE);
     });
 
-    let egl_config = egl_config_from_id(egl_display, descriptor.egl_config_id);
+    let egl_config = if descriptor.no_config {
+        EGL_NO_CONFIG_KHR
+    } else {
+        egl_config_from_id(egl_display, descriptor.egl_config_id)
+    };
 
     let mut egl_context_attributes = vec![
         egl::CONTEXT_CLIENT_VERSION as EGLint,
@@ -498,6 +865,73 @@ pub(crate) unsafe fn create_context(
         ]);
     }
 
+    // `ContextDescriptor::new()` checked for the extension that defines these. Mesa only accepts
+    // the `EGL_EXT_create_context_robustness` attributes for OpenGL ES, so OpenGL uses the
+    // `EGL_KHR_create_context` equivalents.
+    let mut egl_context_flags = 0;
+    match descriptor.gl_api {
+        GLApi::GL => {
+            if descriptor.robust_access {
+                egl_context_flags |= EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR;
+            }
+            if descriptor.lose_context_on_reset {
+                egl_context_attributes.extend(&[
+                    EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_KHR as EGLint,
+                    EGL_LOSE_CONTEXT_ON_RESET_KHR as EGLint,
+                ]);
+            }
+        }
+        GLApi::GLES => {
+            if descriptor.robust_access {
+                egl_context_attributes.extend(&[
+                    EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT as EGLint,
+                    egl::TRUE as EGLint,
+                ]);
+            }
+            if descriptor.lose_context_on_reset {
+                egl_context_attributes.extend(&[
+                    EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT as EGLint,
+                    EGL_LOSE_CONTEXT_ON_RESET_KHR as EGLint,
+                ]);
+            }
+        }
+    }
+    if descriptor.debug {
+        egl_context_flags |= EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR;
+    }
+    if descriptor.forward_compatible {
+        egl_context_flags |= EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE_BIT_KHR;
+    }
+    if egl_context_flags != 0 {
+        egl_context_attributes.extend(&[EGL_CONTEXT_FLAGS_KHR as EGLint, egl_context_flags]);
+    }
+    if descriptor.no_error {
+        egl_context_attributes.extend(&[
+            EGL_CONTEXT_OPENGL_NO_ERROR_KHR as EGLint,
+            egl::TRUE as EGLint,
+        ]);
+    }
+
+    // `ContextDescriptor::new()` dropped these if the display doesn't support them, so only the
+    // non-default values need to be passed.
+    let egl_priority = match descriptor.priority {
+        ContextPriority::Low => Some(EGL_CONTEXT_PRIORITY_LOW_IMG),
+        ContextPriority::Medium => None,
+        ContextPriority::High => Some(EGL_CONTEXT_PRIORITY_HIGH_IMG),
+    };
+    if let Some(egl_priority) = egl_priority {
+        egl_context_attributes.extend(&[
+            EGL_CONTEXT_PRIORITY_LEVEL_IMG as EGLint,
+            egl_priority as EGLint,
+        ]);
+    }
+    if descriptor.release_behavior == ContextReleaseBehavior::None {
+        egl_context_attributes.extend(&[
+            EGL_CONTEXT_RELEASE_BEHAVIOR_KHR as EGLint,
+            EGL_CONTEXT_RELEASE_BEHAVIOR_NONE_KHR as EGLint,
+        ]);
+    }
+
     // Include some extra zeroes to work around broken implementations.
     //
     // FIXME(pcwalton): Which implementations are those? (This is copied from Gecko.)
@@ -0,0 +1,108 @@
+// surfman/surfman/src/platform/generic/egl/debug.rs
+//
+//! Routes `EGL_KHR_debug` messages to the debug message sink of a device.
+
+use super::device;
+use super::ffi::{EGLLabelKHR, EGL_EXTENSION_FUNCTIONS};
+use super::ffi::{EGL_DEBUG_MSG_CRITICAL_KHR, EGL_DEBUG_MSG_ERROR_KHR};
+use super::ffi::{EGL_DEBUG_MSG_INFO_KHR, EGL_DEBUG_MSG_WARN_KHR};
+use crate::debug::{self, DebugMessage, DebugMessageSeverity, DebugMessageSource};
+use crate::debug::{DebugMessageType, DebugSink};
+use crate::egl;
+use crate::egl::types::{EGLAttrib, EGLenum, EGLint};
+
+use std::cell::RefCell;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Once};
+
+thread_local! {
+    // EGL calls the debug callback on the thread that made the failing call, and the callback has
+    // no user data, so the sink is tracked per thread.
+    static EGL_DEBUG_SINK: RefCell<Option<Arc<DebugSink>>> = const { RefCell::new(None) };
+}
+
+// `eglDebugMessageControlKHR()` sets a single callback for the whole process, so it's installed
+// once and never removed. Messages on threads without a sink go to the `log` crate.
+static INSTALL_EGL_DEBUG_CALLBACK: Once = Once::new();
+
+/// Delivers EGL debug messages on this thread to the given sink.
+///
+/// The callback is installed on first use if `EGL_KHR_debug` is available. Otherwise, this is a
+/// no-op. Once installed, the callback stays in place for the life of the process, even after
+/// every device has been dropped.
+#[allow(non_snake_case)]
+pub(crate) fn route_egl_debug_messages_to(sink: &Arc<DebugSink>) {
+    INSTALL_EGL_DEBUG_CALLBACK.call_once(|| {
+        let eglDebugMessageControlKHR = match EGL_EXTENSION_FUNCTIONS.DebugMessageControlKHR {
+            Some(eglDebugMessageControlKHR) if device::has_client_extension("EGL_KHR_debug") => {
+                eglDebugMessageControlKHR
+            }
+            _ => return,
+        };
+
+        // Only critical and error messages are enabled. Warnings and info messages are too noisy
+        // to be worth the cost of a callback on every EGL call.
+        let attributes = [
+            EGL_DEBUG_MSG_CRITICAL_KHR as EGLAttrib,
+            egl::TRUE as EGLAttrib,
+            EGL_DEBUG_MSG_ERROR_KHR as EGLAttrib,
+            egl::TRUE as EGLAttrib,
+            EGL_DEBUG_MSG_WARN_KHR as EGLAttrib,
+            egl::FALSE as EGLAttrib,
+            EGL_DEBUG_MSG_INFO_KHR as EGLAttrib,
+            egl::FALSE as EGLAttrib,
+            egl::NONE as EGLAttrib,
+        ];
+        let result = eglDebugMessageControlKHR(egl_debug_message_callback, attributes.as_ptr());
+        if result != egl::SUCCESS as EGLint {
+            warn!("Failed to install the EGL debug callback: {:#x}", result);
+        }
+    });
+
+    EGL_DEBUG_SINK.with(|egl_debug_sink| *egl_debug_sink.borrow_mut() = Some(sink.clone()));
+}
+
+extern "C" fn egl_debug_message_callback(
+    error: EGLenum,
+    command: *const c_char,
+    message_type: EGLint,
+    _: EGLLabelKHR,
+    _: EGLLabelKHR,
+    message: *const c_char,
+) {
+    // Never unwind into the driver.
+    drop(panic::catch_unwind(AssertUnwindSafe(|| unsafe {
+        let (message_type, severity) = match message_type as EGLenum {
+            EGL_DEBUG_MSG_CRITICAL_KHR | EGL_DEBUG_MSG_ERROR_KHR => {
+                (DebugMessageType::Error, DebugMessageSeverity::High)
+            }
+            EGL_DEBUG_MSG_WARN_KHR => (DebugMessageType::Other, DebugMessageSeverity::Medium),
+            _ => (DebugMessageType::Other, DebugMessageSeverity::Notification),
+        };
+        let command = if command.is_null() {
+            "EGL".into()
+        } else {
+            CStr::from_ptr(command).to_string_lossy()
+        };
+        let message = if message.is_null() {
+            "".into()
+        } else {
+            CStr::from_ptr(message).to_string_lossy()
+        };
+        let message = DebugMessage {
+            source: DebugMessageSource::WindowSystem,
+            message_type,
+            severity,
+            id: error,
+            message: format!("{}: {}", command, message),
+        };
+
+        // Don't hold the borrow while calling out, in case the callback uses EGL.
+        match EGL_DEBUG_SINK.with(|egl_debug_sink| egl_debug_sink.borrow().clone()) {
+            Some(sink) => sink.deliver(&message),
+            None => debug::log_message(&message),
+        }
+    })));
+}
@@ -2,14 +2,34 @@
 //
 //! Functionality common to backends using EGL displays.
 
+use super::context::{self, CurrentContextGuard};
+use super::error::ToWindowingApiError;
+use super::ffi::EGL_EXTENSION_FUNCTIONS;
+use super::ffi::EGL_TRACK_REFERENCES_KHR;
+use super::ffi::{EGL_DEVICE_EXT, EGL_DRIVER_NAME_EXT, EGL_DRM_RENDER_NODE_FILE_EXT};
+use super::ffi::{EGL_PLATFORM_GBM_KHR, EGL_PLATFORM_WAYLAND_KHR, EGL_PLATFORM_X11_KHR};
+use crate::egl;
+use crate::egl::types::{EGLAttrib, EGLDeviceEXT, EGLDisplay, EGLNativeDisplayType, EGLenum};
+use crate::egl::types::{EGLBoolean, EGLint};
 use crate::egl::Egl;
+use crate::gl;
+use crate::gl_utils;
+use crate::info;
+use crate::{AdapterInfo, EGLPlatformPath, Error, GLApi, GLVersion, Gl, SurfaceFormat};
 
-use std::ffi::CString;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ffi::CStr;
 use std::mem;
 use std::os::raw::{c_char, c_void};
+use std::path::PathBuf;
+use std::ptr;
+use std::sync::Mutex;
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(target_os = "android")]
 use libc::{dlopen, dlsym, RTLD_LAZY};
+#[cfg(any(target_os = "android", target_os = "windows"))]
+use std::ffi::CString;
 #[cfg(target_os = "windows")]
 use winapi::shared::minwindef::HMODULE;
 #[cfg(target_os = "windows")]
@@ -18,7 +38,48 @@ use winapi::shared::ntdef::LPCSTR;
 use winapi::um::libloaderapi;
 
 thread_local! {
-    pub static EGL_FUNCTIONS: Egl = Egl::load_with(get_proc_address);
+    pub static EGL_FUNCTIONS: Egl = {
+        EGL_THREAD_RELEASER.with(|_| {});
+        Egl::load_with(get_proc_address)
+    };
+    static EGL_THREAD_RELEASER: EGLThreadReleaser = EGLThreadReleaser::new();
+}
+
+lazy_static! {
+    // The references to each EGL display that surfman initialized itself or was given ownership
+    // of. Other displays don't appear here and are never terminated.
+    static ref EGL_DISPLAY_REFERENCES: Mutex<HashMap<usize, DisplayReferences>> =
+        Mutex::new(HashMap::new());
+}
+
+struct DisplayReferences {
+    count: usize,
+    // Whether to call `eglTerminate()` once the last reference is released.
+    terminate: bool,
+}
+
+// Calls `eglReleaseThread()` when a thread that has used EGL exits, so that the driver can free
+// the state it keeps for that thread, including any context still current on it.
+struct EGLThreadReleaser {
+    release_thread: Option<extern "system" fn() -> EGLBoolean>,
+}
+
+impl EGLThreadReleaser {
+    fn new() -> EGLThreadReleaser {
+        unsafe {
+            let release_thread: Option<extern "system" fn() -> EGLBoolean> =
+                mem::transmute(get_proc_address("eglReleaseThread"));
+            EGLThreadReleaser { release_thread }
+        }
+    }
+}
+
+impl Drop for EGLThreadReleaser {
+    fn drop(&mut self) {
+        if let Some(release_thread) = self.release_thread {
+            release_thread();
+        }
+    }
 }
 
 #[cfg(target_os = "windows")]
@@ -31,7 +92,7 @@ lazy_static! {
     };
 }
 
-#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+#[cfg(target_os = "android")]
 lazy_static! {
     static ref EGL_LIBRARY: EGLLibraryWrapper = {
         unsafe {
@@ -45,10 +106,12 @@ lazy_static! {
 
 #[cfg(target_os = "windows")]
 struct EGLLibraryWrapper(HMODULE);
-#[cfg(not(target_os = "windows"))]
+#[cfg(target_os = "android")]
 struct EGLLibraryWrapper(*mut c_void);
 
+#[cfg(any(target_os = "android", target_os = "windows"))]
 unsafe impl Send for EGLLibraryWrapper {}
+#[cfg(any(target_os = "android", target_os = "windows"))]
 unsafe impl Sync for EGLLibraryWrapper {}
 
 #[cfg(target_os = "windows")]
@@ -60,7 +123,7 @@ fn get_proc_address(symbol_name: &str) -> *const c_void {
     }
 }
 
-#[cfg(not(target_os = "windows"))]
+#[cfg(target_os = "android")]
 fn get_proc_address(symbol_name: &str) -> *const c_void {
     unsafe {
         let symbol_name: CString = CString::new(symbol_name).unwrap();
@@ -69,7 +132,498 @@ fn get_proc_address(symbol_name: &str) -> *const c_void {
     }
 }
 
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+use super::loader::get_proc_address;
+
 pub(crate) unsafe fn lookup_egl_extension(name: &'static [u8]) -> *mut c_void {
     EGL_FUNCTIONS
         .with(|egl| mem::transmute(egl.GetProcAddress(&name[0] as *const u8 as *const c_char)))
 }
+
+/// Initializes the given display and takes a reference to it, to be given up with
+/// `release_display()`.
+///
+/// `eglGetPlatformDisplay()` returns the same handle every time it's called with the same
+/// arguments, and `eglTerminate()` affects every user of that handle, including other libraries.
+/// So surfman only terminates a display once the last reference is released if the display was
+/// opened with `EGL_TRACK_REFERENCES_KHR`, in which case the EGL library counts
+/// `eglInitialize()` and `eglTerminate()` calls itself. Other displays stay initialized.
+pub(crate) unsafe fn initialize_display(egl_display: EGLDisplay) -> Result<(), Error> {
+    let mut display_references = EGL_DISPLAY_REFERENCES.lock().unwrap();
+    if let Some(references) = display_references.get_mut(&(egl_display as usize)) {
+        references.count += 1;
+        return Ok(());
+    }
+
+    EGL_FUNCTIONS.with(|egl| {
+        // Initializing an already-initialized display has no effect, unless it tracks references.
+        let (mut egl_major_version, mut egl_minor_version) = (0, 0);
+        let ok = egl.Initialize(egl_display, &mut egl_major_version, &mut egl_minor_version);
+        if ok == egl::FALSE {
+            return Err(Error::ConnectionFailed);
+        }
+
+        display_references.insert(
+            egl_display as usize,
+            DisplayReferences {
+                count: 1,
+                terminate: tracks_references(egl_display),
+            },
+        );
+        Ok(())
+    })
+}
+
+/// Takes ownership of a display that was opened outside surfman, initializing it if necessary.
+/// This returns a reference to it that must be given up with `release_display()`, like
+/// `initialize_display()`, but the display is terminated with the last reference even if it was
+/// already initialized.
+pub(crate) unsafe fn adopt_display(egl_display: EGLDisplay) -> Result<(), Error> {
+    let mut display_references = EGL_DISPLAY_REFERENCES.lock().unwrap();
+    if let Some(references) = display_references.get_mut(&(egl_display as usize)) {
+        references.count += 1;
+        references.terminate = true;
+        return Ok(());
+    }
+
+    EGL_FUNCTIONS.with(|egl| {
+        // Initializing an already-initialized display has no effect.
+        let (mut egl_major_version, mut egl_minor_version) = (0, 0);
+        let ok = egl.Initialize(egl_display, &mut egl_major_version, &mut egl_minor_version);
+        if ok == egl::FALSE {
+            return Err(Error::ConnectionFailed);
+        }
+
+        display_references.insert(
+            egl_display as usize,
+            DisplayReferences {
+                count: 1,
+                terminate: true,
+            },
+        );
+        Ok(())
+    })
+}
+
+/// Takes another reference to the given display if surfman initialized it, to be given up with
+/// `release_display()`.
+pub(crate) fn retain_display(egl_display: EGLDisplay) {
+    let mut display_references = EGL_DISPLAY_REFERENCES.lock().unwrap();
+    if let Some(references) = display_references.get_mut(&(egl_display as usize)) {
+        references.count += 1;
+    }
+}
+
+/// Gives up a reference to the given display. If this was the last reference, the display is
+/// terminated if it was adopted or tracks references.
+pub(crate) unsafe fn release_display(egl_display: EGLDisplay) {
+    let mut display_references = EGL_DISPLAY_REFERENCES.lock().unwrap();
+    let references = match display_references.get_mut(&(egl_display as usize)) {
+        Some(references) => references,
+        None => return,
+    };
+    references.count -= 1;
+    if references.count == 0 {
+        let terminate = references.terminate;
+        display_references.remove(&(egl_display as usize));
+        if terminate {
+            EGL_FUNCTIONS.with(|egl| egl.Terminate(egl_display));
+        }
+    }
+}
+
+// Returns true if the given initialized display was opened with `EGL_TRACK_REFERENCES_KHR`.
+#[allow(non_snake_case)]
+unsafe fn tracks_references(egl_display: EGLDisplay) -> bool {
+    let eglQueryDisplayAttribKHR = match EGL_EXTENSION_FUNCTIONS.QueryDisplayAttribKHR {
+        Some(eglQueryDisplayAttribKHR) => eglQueryDisplayAttribKHR,
+        None => return false,
+    };
+    if !has_client_extension("EGL_KHR_display_reference") {
+        return false;
+    }
+    let mut value: EGLAttrib = 0;
+    let ok = eglQueryDisplayAttribKHR(egl_display, EGL_TRACK_REFERENCES_KHR as EGLint, &mut value);
+    ok != egl::FALSE && value == egl::TRUE as EGLAttrib
+}
+
+/// Returns true if the EGL client extension string (that is, the extension string of
+/// `EGL_NO_DISPLAY`) contains the given extension.
+pub(crate) fn has_client_extension(extension_name: &str) -> bool {
+    // The client extension string is null if `EGL_EXT_client_extensions` is unsupported.
+    has_display_extension(egl::NO_DISPLAY, extension_name)
+}
+
+/// Returns true if the extension string of the given initialized display contains the given
+/// extension.
+pub(crate) fn has_display_extension(egl_display: EGLDisplay, extension_name: &str) -> bool {
+    EGL_FUNCTIONS.with(|egl| unsafe {
+        let extensions = egl.QueryString(egl_display, egl::EXTENSIONS as EGLint);
+        if extensions.is_null() {
+            return false;
+        }
+        CStr::from_ptr(extensions)
+            .to_string_lossy()
+            .split_whitespace()
+            .any(|extension| extension == extension_name)
+    })
+}
+
+/// Returns true if textures can be shared between contexts on the given initialized display by
+/// wrapping them in EGL images, via `EGL_KHR_gl_texture_2D_image` and `GL_OES_EGL_image`.
+///
+/// `eglGetProcAddress()` returns null for entry points it doesn't know about, but may return
+/// entry points for extensions that the display doesn't support, so both are checked.
+pub(crate) fn supports_texture_images(egl_display: EGLDisplay) -> bool {
+    EGL_EXTENSION_FUNCTIONS.CreateImageKHR.is_some()
+        && EGL_EXTENSION_FUNCTIONS.DestroyImageKHR.is_some()
+        && EGL_EXTENSION_FUNCTIONS.ImageTargetTexture2DOES.is_some()
+        && has_display_extension(egl_display, "EGL_KHR_gl_texture_2D_image")
+}
+
+/// Returns true if the EGL library itself, as opposed to any particular display, implements EGL
+/// 1.5.
+///
+/// EGL 1.5 libraries report their version for `EGL_NO_DISPLAY`, but older ones raise an
+/// `EGL_BAD_DISPLAY` error, so this looks at the client extensions instead. The
+/// `EGL_KHR_platform_*` extensions all require EGL 1.5.
+fn client_supports_egl_1_5() -> bool {
+    EGL_FUNCTIONS.with(|egl| unsafe {
+        let extensions = egl.QueryString(egl::NO_DISPLAY, egl::EXTENSIONS as EGLint);
+        if extensions.is_null() {
+            return false;
+        }
+        CStr::from_ptr(extensions)
+            .to_string_lossy()
+            .split_whitespace()
+            .any(|extension| extension.starts_with("EGL_KHR_platform_"))
+    })
+}
+
+/// Returns the EGL version of the given initialized display.
+pub(crate) fn egl_version(egl_display: EGLDisplay) -> Option<(u8, u8)> {
+    EGL_FUNCTIONS.with(|egl| unsafe {
+        // The version string looks like `1.5 Mesa 23.0.4` or `1.4 (ANGLE 2.1)`.
+        let version = egl.QueryString(egl_display, egl::VERSION as EGLint);
+        if version.is_null() {
+            return None;
+        }
+        let version = CStr::from_ptr(version).to_string_lossy();
+        let mut version_iter = version.split(['.', ' ']);
+        match (version_iter.next(), version_iter.next()) {
+            (Some(major), Some(minor)) => Some((major.parse().ok()?, minor.parse().ok()?)),
+            _ => None,
+        }
+    })
+}
+
+/// Opens an EGL display for the given platform, using the newest entry point that the EGL library
+/// supports. Returns `EGL_NO_DISPLAY` on failure.
+///
+/// If `EGL_KHR_display_reference` is supported, the display is opened with
+/// `EGL_TRACK_REFERENCES_KHR`, so that it can be terminated without affecting other libraries.
+///
+/// The legacy `eglGetDisplay()` fallback can't pass attributes and leaves the choice of platform to
+/// the EGL library, so it's only used for window system platforms, and only when no attributes
+/// were requested.
+#[allow(non_snake_case)]
+pub(crate) unsafe fn get_platform_display(
+    platform: EGLenum,
+    native_display: *mut c_void,
+    egl_display_attributes: &[EGLAttrib],
+) -> EGLDisplay {
+    let legacy_allowed = egl_display_attributes.is_empty();
+    let mut egl_display_attributes = egl_display_attributes.to_vec();
+    if has_client_extension("EGL_KHR_display_reference") {
+        egl_display_attributes.push(EGL_TRACK_REFERENCES_KHR as EGLAttrib);
+        egl_display_attributes.push(egl::TRUE as EGLAttrib);
+    }
+
+    EGL_FUNCTIONS.with(|egl| {
+        if egl.GetPlatformDisplay.is_loaded() && client_supports_egl_1_5() {
+            egl_display_attributes.push(egl::NONE as EGLAttrib);
+            return egl.GetPlatformDisplay(
+                platform,
+                native_display,
+                egl_display_attributes.as_ptr(),
+            );
+        }
+
+        if let Some(eglGetPlatformDisplayEXT) = EGL_EXTENSION_FUNCTIONS.GetPlatformDisplayEXT {
+            if has_client_extension("EGL_EXT_platform_base") {
+                // The extension predates `EGLAttrib`, so attributes are passed as `EGLint`s. If
+                // one doesn't fit, such as a pointer, the display can't be opened this way.
+                let egl_display_attributes: Result<Vec<EGLint>, _> = egl_display_attributes
+                    .iter()
+                    .map(|&attribute| EGLint::try_from(attribute))
+                    .collect();
+                let mut egl_display_attributes = match egl_display_attributes {
+                    Ok(egl_display_attributes) => egl_display_attributes,
+                    Err(_) => return egl::NO_DISPLAY,
+                };
+                egl_display_attributes.push(egl::NONE as EGLint);
+                return eglGetPlatformDisplayEXT(
+                    platform,
+                    native_display,
+                    egl_display_attributes.as_ptr(),
+                );
+            }
+        }
+
+        match platform {
+            EGL_PLATFORM_X11_KHR | EGL_PLATFORM_WAYLAND_KHR | EGL_PLATFORM_GBM_KHR
+                if legacy_allowed =>
+            {
+                egl.GetDisplay(native_display as EGLNativeDisplayType)
+            }
+            _ => egl::NO_DISPLAY,
+        }
+    })
+}
+
+/// Returns the entry points that window surfaces are created with on the given initialized
+/// display.
+pub(crate) fn platform_path(egl_display: EGLDisplay) -> EGLPlatformPath {
+    let core = EGL_FUNCTIONS.with(|egl| egl.CreatePlatformWindowSurface.is_loaded());
+    if core && egl_version(egl_display) >= Some((1, 5)) {
+        EGLPlatformPath::Core
+    } else if EGL_EXTENSION_FUNCTIONS
+        .CreatePlatformWindowSurfaceEXT
+        .is_some()
+        && has_client_extension("EGL_EXT_platform_base")
+    {
+        EGLPlatformPath::Extension
+    } else {
+        EGLPlatformPath::Legacy
+    }
+}
+
+/// Gathers information about the adapter that the given display renders with.
+///
+/// The EGL device, if any, is queried via `EGL_EXT_device_query`. The GL strings are read from a
+/// temporary context, which is destroyed before returning.
+pub(crate) unsafe fn query_adapter_info(
+    egl_display: EGLDisplay,
+    gl_api: GLApi,
+) -> Result<AdapterInfo, Error> {
+    let device_info = query_egl_device_info(egl_display);
+    let (vendor, renderer, gl_version) = query_gl_strings(egl_display, gl_api)?;
+
+    // Without `EGL_MESA_device_software` to go on, fall back to recognizing the renderer string.
+    let software = device_info
+        .software
+        .unwrap_or_else(|| info::renderer_is_software(&renderer));
+
+    Ok(AdapterInfo {
+        vendor,
+        renderer,
+        driver: device_info.driver,
+        drm_render_node: device_info.drm_render_node,
+        software,
+        gl_version,
+        egl_version: egl_version(egl_display),
+        egl_platform_path: Some(platform_path(egl_display)),
+    })
+}
+
+#[derive(Default)]
+struct EGLDeviceInfo {
+    driver: Option<String>,
+    drm_render_node: Option<PathBuf>,
+    software: Option<bool>,
+}
+
+#[allow(non_snake_case)]
+unsafe fn query_egl_device_info(egl_display: EGLDisplay) -> EGLDeviceInfo {
+    let mut device_info = EGLDeviceInfo::default();
+
+    // `EGL_MESA_query_driver` reports the driver name even when there's no EGL device.
+    if let Some(eglGetDisplayDriverName) = EGL_EXTENSION_FUNCTIONS.GetDisplayDriverName {
+        device_info.driver = string_from_ptr(eglGetDisplayDriverName(egl_display));
+    }
+
+    let (eglQueryDisplayAttribEXT, eglQueryDeviceStringEXT) = match (
+        EGL_EXTENSION_FUNCTIONS.QueryDisplayAttribEXT,
+        EGL_EXTENSION_FUNCTIONS.QueryDeviceStringEXT,
+    ) {
+        (Some(query_display_attrib), Some(query_device_string)) => {
+            (query_display_attrib, query_device_string)
+        }
+        _ => return device_info,
+    };
+
+    let mut egl_device: EGLAttrib = 0;
+    let ok = eglQueryDisplayAttribEXT(egl_display, EGL_DEVICE_EXT as EGLint, &mut egl_device);
+    if ok == egl::FALSE {
+        return device_info;
+    }
+    let egl_device = egl_device as EGLDeviceEXT;
+
+    let extensions = match string_from_ptr(eglQueryDeviceStringEXT(
+        egl_device,
+        egl::EXTENSIONS as EGLint,
+    )) {
+        Some(extensions) => extensions,
+        None => return device_info,
+    };
+    let has_extension = |name| {
+        extensions
+            .split_whitespace()
+            .any(|extension| extension == name)
+    };
+
+    device_info.software = Some(has_extension("EGL_MESA_device_software"));
+    if has_extension("EGL_EXT_device_drm_render_node") {
+        device_info.drm_render_node = string_from_ptr(eglQueryDeviceStringEXT(
+            egl_device,
+            EGL_DRM_RENDER_NODE_FILE_EXT as EGLint,
+        ))
+        .map(PathBuf::from);
+    }
+    if device_info.driver.is_none() && has_extension("EGL_EXT_device_persistent_id") {
+        device_info.driver = string_from_ptr(eglQueryDeviceStringEXT(
+            egl_device,
+            EGL_DRIVER_NAME_EXT as EGLint,
+        ));
+    }
+
+    device_info
+}
+
+/// Returns the surface formats that generic surfaces on the given display can have.
+///
+/// Each format is tried out in a temporary context, which is destroyed before returning.
+pub(crate) unsafe fn query_supported_surface_formats(
+    egl_display: EGLDisplay,
+    gl_api: GLApi,
+) -> Result<Vec<SurfaceFormat>, Error> {
+    with_temporary_context(egl_display, gl_api, |gl| {
+        SurfaceFormat::ALL
+            .iter()
+            .cloned()
+            .filter(|&format| {
+                format == SurfaceFormat::RGBA8 || gl_utils::surface_format_is_renderable(gl, format)
+            })
+            .collect()
+    })
+}
+
+unsafe fn query_gl_strings(
+    egl_display: EGLDisplay,
+    gl_api: GLApi,
+) -> Result<(String, String, GLVersion), Error> {
+    with_temporary_context(egl_display, gl_api, |gl| {
+        (
+            string_from_ptr(gl.GetString(gl::VENDOR) as *const c_char).unwrap_or_default(),
+            string_from_ptr(gl.GetString(gl::RENDERER) as *const c_char).unwrap_or_default(),
+            GLVersion::current(gl),
+        )
+    })
+}
+
+// Runs the given function with a temporary context of the given API current, restoring the
+// previously-current context afterward.
+unsafe fn with_temporary_context<F, T>(
+    egl_display: EGLDisplay,
+    gl_api: GLApi,
+    callback: F,
+) -> Result<T, Error>
+where
+    F: FnOnce(&Gl) -> T,
+{
+    EGL_FUNCTIONS.with(|egl| {
+        let (egl_api, renderable_type) = match gl_api {
+            GLApi::GL => (egl::OPENGL_API, egl::OPENGL_BIT),
+            GLApi::GLES => (egl::OPENGL_ES_API, egl::OPENGL_ES2_BIT),
+        };
+        // Displays without pbuffer support (e.g. GBM) can make the context current without a
+        // surface instead.
+        let surfaceless = has_display_extension(egl_display, "EGL_KHR_surfaceless_context");
+        let surface_type = if surfaceless { 0 } else { egl::PBUFFER_BIT };
+        let config_attributes = [
+            egl::RENDERABLE_TYPE as EGLint,
+            renderable_type as EGLint,
+            egl::SURFACE_TYPE as EGLint,
+            surface_type as EGLint,
+            egl::NONE as EGLint,
+            0,
+            0,
+            0,
+        ];
+        let (mut egl_config, mut config_count) = (ptr::null(), 0);
+        let ok = egl.ChooseConfig(
+            egl_display,
+            config_attributes.as_ptr(),
+            &mut egl_config,
+            1,
+            &mut config_count,
+        );
+        if ok == egl::FALSE || config_count == 0 {
+            return Err(Error::NoPixelFormatFound);
+        }
+
+        let ok = egl.BindAPI(egl_api);
+        assert_ne!(ok, egl::FALSE);
+
+        let context_attributes = match gl_api {
+            GLApi::GL => [egl::NONE as EGLint, 0, 0, 0],
+            GLApi::GLES => [
+                egl::CONTEXT_CLIENT_VERSION as EGLint,
+                2,
+                egl::NONE as EGLint,
+                0,
+            ],
+        };
+        let egl_context = egl.CreateContext(
+            egl_display,
+            egl_config,
+            egl::NO_CONTEXT,
+            context_attributes.as_ptr(),
+        );
+        if egl_context == egl::NO_CONTEXT {
+            let err = egl.GetError().to_windowing_api_error();
+            return Err(Error::ContextCreationFailed(err));
+        }
+        let egl_surface = if surfaceless {
+            egl::NO_SURFACE
+        } else {
+            context::create_dummy_pbuffer(egl_display, egl_context)
+        };
+
+        let result = {
+            let _guard = CurrentContextGuard::new();
+            let ok = egl.MakeCurrent(egl_display, egl_surface, egl_surface, egl_context);
+            if ok == egl::FALSE {
+                let err = egl.GetError().to_windowing_api_error();
+                Err(Error::MakeCurrentFailed(err))
+            } else {
+                let gl = Gl::load_with(context::get_proc_address);
+                let result = callback(&gl);
+
+                // Release the temporary context before the guard restores the old one, in case
+                // there was no old context to restore.
+                egl.MakeCurrent(
+                    egl_display,
+                    egl::NO_SURFACE,
+                    egl::NO_SURFACE,
+                    egl::NO_CONTEXT,
+                );
+                Ok(result)
+            }
+        };
+
+        if egl_surface != egl::NO_SURFACE {
+            egl.DestroySurface(egl_display, egl_surface);
+        }
+        egl.DestroyContext(egl_display, egl_context);
+        result
+    })
+}
+
+unsafe fn string_from_ptr(string: *const c_char) -> Option<String> {
+    if string.is_null() {
+        None
+    } else {
+        Some(CStr::from_ptr(string).to_string_lossy().into_owned())
+    }
+}
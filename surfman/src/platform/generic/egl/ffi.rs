@@ -4,10 +4,10 @@
 
 #![allow(dead_code)]
 
-use crate::egl::types::{EGLAttrib, EGLBoolean, EGLContext, EGLDeviceEXT, EGLDisplay, EGLSurface};
-use crate::egl::types::{EGLenum, EGLint};
+use crate::egl::types::{EGLAttrib, EGLBoolean, EGLConfig, EGLContext, EGLDeviceEXT, EGLDisplay};
+use crate::egl::types::{EGLSurface, EGLenum, EGLint, EGLuint64KHR};
 
-use std::os::raw::c_void;
+use std::os::raw::{c_char, c_int, c_void};
 
 pub enum EGLClientBufferOpaque {}
 pub type EGLClientBuffer = *mut EGLClientBufferOpaque;
@@ -15,42 +15,112 @@ pub type EGLClientBuffer = *mut EGLClientBufferOpaque;
 pub enum EGLImageKHROpaque {}
 pub type EGLImageKHR = *mut EGLImageKHROpaque;
 
+pub type EGLLabelKHR = *mut c_void;
+pub type EGLDebugProcKHR = extern "C" fn(
+    error: EGLenum,
+    command: *const c_char,
+    message_type: EGLint,
+    thread_label: EGLLabelKHR,
+    object_label: EGLLabelKHR,
+    message: *const c_char,
+);
+
+pub const EGL_NO_CONFIG_KHR: EGLConfig = 0 as EGLConfig;
+
+pub const EGL_NATIVE_PIXMAP_KHR: EGLenum = 0x30b0;
 pub const EGL_GL_TEXTURE_2D_KHR: EGLenum = 0x30b1;
 pub const EGL_IMAGE_PRESERVED_KHR: EGLenum = 0x30d2;
+pub const EGL_CONTEXT_RELEASE_BEHAVIOR_NONE_KHR: EGLenum = 0;
+pub const EGL_CONTEXT_RELEASE_BEHAVIOR_KHR: EGLenum = 0x2097;
+pub const EGL_CONTEXT_RELEASE_BEHAVIOR_FLUSH_KHR: EGLenum = 0x2098;
+pub const EGL_CONTEXT_OPENGL_ROBUST_ACCESS_EXT: EGLenum = 0x30bf;
 pub const EGL_CONTEXT_MINOR_VERSION_KHR: EGLenum = 0x30fb;
+pub const EGL_CONTEXT_FLAGS_KHR: EGLenum = 0x30fc;
 pub const EGL_CONTEXT_OPENGL_PROFILE_MASK: EGLenum = 0x30fd;
+pub const EGL_CONTEXT_PRIORITY_LEVEL_IMG: EGLenum = 0x3100;
+pub const EGL_CONTEXT_PRIORITY_HIGH_IMG: EGLenum = 0x3101;
+pub const EGL_CONTEXT_PRIORITY_MEDIUM_IMG: EGLenum = 0x3102;
+pub const EGL_CONTEXT_PRIORITY_LOW_IMG: EGLenum = 0x3103;
+pub const EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_EXT: EGLenum = 0x3138;
 pub const EGL_PLATFORM_DEVICE_EXT: EGLenum = 0x313f;
+pub const EGL_CONTEXT_OPENGL_NO_ERROR_KHR: EGLenum = 0x31b3;
+pub const EGL_CONTEXT_OPENGL_RESET_NOTIFICATION_STRATEGY_KHR: EGLenum = 0x31bd;
+pub const EGL_LOSE_CONTEXT_ON_RESET_KHR: EGLenum = 0x31bf;
 pub const EGL_NATIVE_BUFFER_ANDROID: EGLenum = 0x3140;
 pub const EGL_PLATFORM_X11_KHR: EGLenum = 0x31d5;
+pub const EGL_PLATFORM_GBM_KHR: EGLenum = 0x31d7;
 pub const EGL_PLATFORM_WAYLAND_KHR: EGLenum = 0x31d8;
 pub const EGL_PLATFORM_SURFACELESS_MESA: EGLenum = 0x31dd;
 pub const EGL_D3D_TEXTURE_2D_SHARE_HANDLE_ANGLE: EGLenum = 0x3200;
 pub const EGL_BAD_DEVICE_EXT: EGLenum = 0x322b;
 pub const EGL_DEVICE_EXT: EGLenum = 0x322c;
+pub const EGL_LINUX_DMA_BUF_EXT: EGLenum = 0x3270;
+pub const EGL_LINUX_DRM_FOURCC_EXT: EGLenum = 0x3271;
+pub const EGL_DMA_BUF_PLANE0_FD_EXT: EGLenum = 0x3272;
+pub const EGL_DMA_BUF_PLANE0_OFFSET_EXT: EGLenum = 0x3273;
+pub const EGL_DMA_BUF_PLANE0_PITCH_EXT: EGLenum = 0x3274;
+pub const EGL_DMA_BUF_PLANE1_FD_EXT: EGLenum = 0x3275;
+pub const EGL_DMA_BUF_PLANE1_OFFSET_EXT: EGLenum = 0x3276;
+pub const EGL_DMA_BUF_PLANE1_PITCH_EXT: EGLenum = 0x3277;
+pub const EGL_DMA_BUF_PLANE2_FD_EXT: EGLenum = 0x3278;
+pub const EGL_DMA_BUF_PLANE2_OFFSET_EXT: EGLenum = 0x3279;
+pub const EGL_DMA_BUF_PLANE2_PITCH_EXT: EGLenum = 0x327a;
+pub const EGL_TRACK_REFERENCES_KHR: EGLenum = 0x3352;
+pub const EGL_DRIVER_NAME_EXT: EGLenum = 0x335e;
+pub const EGL_DRM_RENDER_NODE_FILE_EXT: EGLenum = 0x3377;
+pub const EGL_DEBUG_MSG_CRITICAL_KHR: EGLenum = 0x33b9;
+pub const EGL_DEBUG_MSG_ERROR_KHR: EGLenum = 0x33ba;
+pub const EGL_DEBUG_MSG_WARN_KHR: EGLenum = 0x33bb;
+pub const EGL_DEBUG_MSG_INFO_KHR: EGLenum = 0x33bc;
 pub const EGL_D3D11_DEVICE_ANGLE: EGLenum = 0x33a1;
 pub const EGL_DXGI_KEYED_MUTEX_ANGLE: EGLenum = 0x33a2;
 pub const EGL_D3D_TEXTURE_ANGLE: EGLenum = 0x33a3;
+pub const EGL_DMA_BUF_PLANE3_FD_EXT: EGLenum = 0x3440;
+pub const EGL_DMA_BUF_PLANE3_OFFSET_EXT: EGLenum = 0x3441;
+pub const EGL_DMA_BUF_PLANE3_PITCH_EXT: EGLenum = 0x3442;
+pub const EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT: EGLenum = 0x3443;
+pub const EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT: EGLenum = 0x3444;
+pub const EGL_DMA_BUF_PLANE1_MODIFIER_LO_EXT: EGLenum = 0x3445;
+pub const EGL_DMA_BUF_PLANE1_MODIFIER_HI_EXT: EGLenum = 0x3446;
+pub const EGL_DMA_BUF_PLANE2_MODIFIER_LO_EXT: EGLenum = 0x3447;
+pub const EGL_DMA_BUF_PLANE2_MODIFIER_HI_EXT: EGLenum = 0x3448;
+pub const EGL_DMA_BUF_PLANE3_MODIFIER_LO_EXT: EGLenum = 0x3449;
+pub const EGL_DMA_BUF_PLANE3_MODIFIER_HI_EXT: EGLenum = 0x344a;
 
 pub const EGL_NO_DEVICE_EXT: EGLDeviceEXT = 0 as EGLDeviceEXT;
 pub const EGL_NO_IMAGE_KHR: EGLImageKHR = 0 as EGLImageKHR;
 
+// The modifier of buffers whose layout is determined implicitly by the driver.
+pub const DRM_FORMAT_MOD_INVALID: u64 = 0x00ff_ffff_ffff_ffff;
+// The modifier of buffers stored row by row, without tiling or compression.
+pub const DRM_FORMAT_MOD_LINEAR: u64 = 0;
+
+// Little-endian fourcc code for 32-bit pixels stored as R, G, B, A bytes in memory.
+pub const DRM_FORMAT_ABGR8888: u32 = 0x34324241;
+
+// Little-endian fourcc codes for the other formats that generic surfaces can have.
+pub const DRM_FORMAT_ABGR2101010: u32 = 0x30334241;
+pub const DRM_FORMAT_XBGR2101010: u32 = 0x30334258;
+pub const DRM_FORMAT_ABGR16161616F: u32 = 0x48344241;
+pub const DRM_FORMAT_XBGR16161616F: u32 = 0x48344258;
+pub const DRM_FORMAT_R8: u32 = 0x20203852;
+pub const DRM_FORMAT_GR88: u32 = 0x38385247;
+
+// From `linux/dma-buf.h`.
+pub const DMA_BUF_IOCTL_SYNC: u64 = 0x40086200;
+pub const DMA_BUF_SYNC_READ: u64 = 1 << 0;
+pub const DMA_BUF_SYNC_WRITE: u64 = 1 << 1;
+pub const DMA_BUF_SYNC_START: u64 = 0 << 2;
+pub const DMA_BUF_SYNC_END: u64 = 1 << 2;
+
 pub const EGL_CONTEXT_OPENGL_CORE_PROFILE_BIT: EGLint = 1;
 pub const EGL_CONTEXT_OPENGL_COMPATIBILITY_PROFILE_BIT: EGLint = 2;
+pub const EGL_CONTEXT_OPENGL_DEBUG_BIT_KHR: EGLint = 1;
+pub const EGL_CONTEXT_OPENGL_FORWARD_COMPATIBLE_BIT_KHR: EGLint = 2;
+pub const EGL_CONTEXT_OPENGL_ROBUST_ACCESS_BIT_KHR: EGLint = 4;
 
 #[allow(non_snake_case)]
 pub(crate) struct EGLExtensionFunctions {
-    // Ubiquitous extensions assumed to be present
-    pub(crate) CreateImageKHR: extern "C" fn(
-        dpy: EGLDisplay,
-        ctx: EGLContext,
-        target: EGLenum,
-        buffer: EGLClientBuffer,
-        attrib_list: *const EGLint,
-    ) -> EGLImageKHR,
-    pub(crate) DestroyImageKHR: extern "C" fn(dpy: EGLDisplay, image: EGLImageKHR) -> EGLBoolean,
-    pub(crate) ImageTargetTexture2DOES: extern "C" fn(target: EGLenum, image: EGLImageKHR),
-
-    // Optional extensions
     pub(crate) CreateDeviceANGLE: Option<
         extern "C" fn(
             device_type: EGLint,
@@ -58,14 +128,73 @@ pub(crate) struct EGLExtensionFunctions {
             attrib_list: *const EGLAttrib,
         ) -> EGLDeviceEXT,
     >,
+    pub(crate) CreateImageKHR: Option<
+        extern "C" fn(
+            dpy: EGLDisplay,
+            ctx: EGLContext,
+            target: EGLenum,
+            buffer: EGLClientBuffer,
+            attrib_list: *const EGLint,
+        ) -> EGLImageKHR,
+    >,
+    pub(crate) CreatePlatformWindowSurfaceEXT: Option<
+        extern "C" fn(
+            dpy: EGLDisplay,
+            config: EGLConfig,
+            native_window: *mut c_void,
+            attrib_list: *const EGLint,
+        ) -> EGLSurface,
+    >,
+    pub(crate) DebugMessageControlKHR:
+        Option<extern "C" fn(callback: EGLDebugProcKHR, attrib_list: *const EGLAttrib) -> EGLint>,
+    pub(crate) DestroyImageKHR:
+        Option<extern "C" fn(dpy: EGLDisplay, image: EGLImageKHR) -> EGLBoolean>,
+    pub(crate) ExportDMABUFImageMESA: Option<
+        extern "C" fn(
+            dpy: EGLDisplay,
+            image: EGLImageKHR,
+            fds: *mut c_int,
+            strides: *mut EGLint,
+            offsets: *mut EGLint,
+        ) -> EGLBoolean,
+    >,
+    pub(crate) ExportDMABUFImageQueryMESA: Option<
+        extern "C" fn(
+            dpy: EGLDisplay,
+            image: EGLImageKHR,
+            fourcc: *mut c_int,
+            num_planes: *mut c_int,
+            modifiers: *mut EGLuint64KHR,
+        ) -> EGLBoolean,
+    >,
+    pub(crate) GetDisplayDriverName: Option<extern "C" fn(dpy: EGLDisplay) -> *const c_char>,
     pub(crate) GetNativeClientBufferANDROID:
         Option<extern "C" fn(buffer: *const c_void) -> EGLClientBuffer>,
+    pub(crate) GetPlatformDisplayEXT: Option<
+        extern "C" fn(
+            platform: EGLenum,
+            native_display: *mut c_void,
+            attrib_list: *const EGLint,
+        ) -> EGLDisplay,
+    >,
+    pub(crate) ImageTargetTexture2DOES: Option<extern "C" fn(target: EGLenum, image: EGLImageKHR)>,
     pub(crate) QueryDeviceAttribEXT: Option<
         extern "C" fn(device: EGLDeviceEXT, attribute: EGLint, value: *mut EGLAttrib) -> EGLBoolean,
     >,
+    pub(crate) QueryDeviceStringEXT:
+        Option<extern "C" fn(device: EGLDeviceEXT, name: EGLint) -> *const c_char>,
+    pub(crate) QueryDevicesEXT: Option<
+        extern "C" fn(
+            max_devices: EGLint,
+            devices: *mut EGLDeviceEXT,
+            num_devices: *mut EGLint,
+        ) -> EGLBoolean,
+    >,
     pub(crate) QueryDisplayAttribEXT: Option<
         extern "C" fn(dpy: EGLDisplay, attribute: EGLint, value: *mut EGLAttrib) -> EGLBoolean,
     >,
+    pub(crate) QueryDisplayAttribKHR:
+        Option<extern "C" fn(dpy: EGLDisplay, name: EGLint, value: *mut EGLAttrib) -> EGLBoolean>,
     pub(crate) QuerySurfacePointerANGLE: Option<
         extern "C" fn(
             dpy: EGLDisplay,
@@ -82,14 +211,22 @@ lazy_static! {
         use std::mem::transmute as cast;
         unsafe {
             EGLExtensionFunctions {
+                CreateDeviceANGLE: cast(get(b"eglCreateDeviceANGLE\0")),
                 CreateImageKHR: cast(get(b"eglCreateImageKHR\0")),
+                CreatePlatformWindowSurfaceEXT: cast(get(b"eglCreatePlatformWindowSurfaceEXT\0")),
+                DebugMessageControlKHR: cast(get(b"eglDebugMessageControlKHR\0")),
                 DestroyImageKHR: cast(get(b"eglDestroyImageKHR\0")),
-                ImageTargetTexture2DOES: cast(get(b"glEGLImageTargetTexture2DOES\0")),
-
-                CreateDeviceANGLE: cast(get(b"eglCreateDeviceANGLE\0")),
+                ExportDMABUFImageMESA: cast(get(b"eglExportDMABUFImageMESA\0")),
+                ExportDMABUFImageQueryMESA: cast(get(b"eglExportDMABUFImageQueryMESA\0")),
+                GetDisplayDriverName: cast(get(b"eglGetDisplayDriverName\0")),
                 GetNativeClientBufferANDROID: cast(get(b"eglGetNativeClientBufferANDROID\0")),
+                GetPlatformDisplayEXT: cast(get(b"eglGetPlatformDisplayEXT\0")),
+                ImageTargetTexture2DOES: cast(get(b"glEGLImageTargetTexture2DOES\0")),
                 QueryDeviceAttribEXT: cast(get(b"eglQueryDeviceAttribEXT\0")),
+                QueryDeviceStringEXT: cast(get(b"eglQueryDeviceStringEXT\0")),
+                QueryDevicesEXT: cast(get(b"eglQueryDevicesEXT\0")),
                 QueryDisplayAttribEXT: cast(get(b"eglQueryDisplayAttribEXT\0")),
+                QueryDisplayAttribKHR: cast(get(b"eglQueryDisplayAttribKHR\0")),
                 QuerySurfacePointerANGLE: cast(get(b"eglQuerySurfacePointerANGLE\0")),
             }
         }
@@ -0,0 +1,408 @@
+// surfman/surfman/src/platform/generic/egl/handle.rs
+//
+//! Handles that allow surfaces to be shared with other processes.
+
+use super::surface::{DmaBufDescriptor, DmaBufPlane};
+
+use euclid::default::Size2D;
+use std::io::{self, Error as IOError, ErrorKind};
+use std::mem;
+use std::os::raw::c_void;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::ptr;
+
+// DMA-BUF import accepts at most four planes.
+const MAX_PLANES: usize = 4;
+
+// Width, height, fourcc, modifier and plane count, followed by the offset and stride of each
+// plane.
+const MESSAGE_SIZE: usize = 4 + 4 + 4 + 8 + 4 + MAX_PLANES * (4 + 4);
+
+/// A handle to the contents of a surface that can be sent to another process.
+///
+/// A handle consists of DMA-BUF file descriptors plus the metadata needed to interpret them. Use
+/// `send()` and `recv()` to transfer it over a Unix domain socket; the file descriptors travel as
+/// `SCM_RIGHTS` ancillary data. The receiving process opens the handle as a surface texture with
+/// `Device::create_surface_texture_from_handle()`.
+///
+/// Ownership rules:
+///
+/// * The handle owns its file descriptors and closes them when dropped. Sending a handle
+///   duplicates the descriptors into the receiving process, so the sender should drop its copy
+///   once `send()` returns.
+///
+/// * The buffer shares the surface's memory; nothing is copied. It stays alive as long as any
+///   process holds a file descriptor to it or a surface texture made from it, so the sending
+///   process may destroy its surface without invalidating textures in other processes.
+///
+/// * Because the memory is shared, the sending process must finish rendering before sending the
+///   handle (with `glFlush()` on drivers with implicit synchronization, `glFinish()` otherwise)
+///   and must not render to the surface again until the receiving process is done sampling from
+///   it. Passing surfaces back and forth in a swap chain satisfies this.
+#[derive(Debug)]
+pub struct SurfaceHandle {
+    pub(crate) descriptor: DmaBufDescriptor,
+}
+
+impl SurfaceHandle {
+    /// Wraps a DMA-BUF descriptor in a surface handle.
+    #[inline]
+    pub fn from_dmabuf(descriptor: DmaBufDescriptor) -> SurfaceHandle {
+        SurfaceHandle { descriptor }
+    }
+
+    /// Returns the DMA-BUF descriptor that this handle wraps.
+    #[inline]
+    pub fn into_dmabuf(self) -> DmaBufDescriptor {
+        self.descriptor
+    }
+
+    /// Returns the size of the surface that this handle refers to.
+    #[inline]
+    pub fn size(&self) -> Size2D<i32> {
+        self.descriptor.size
+    }
+
+    /// Sends this handle over the given Unix domain socket.
+    ///
+    /// Stream, datagram and sequenced-packet sockets are all supported. On stream sockets, the
+    /// message is written in full even if the kernel accepts only part of it at a time.
+    ///
+    /// The handle is not consumed. The receiving process gets duplicates of the file descriptors,
+    /// which remain valid even if this handle is dropped afterward.
+    pub fn send<S>(&self, socket: &S) -> io::Result<()>
+    where
+        S: AsRawFd,
+    {
+        let planes = &self.descriptor.planes;
+        if planes.is_empty() || planes.len() > MAX_PLANES {
+            return Err(IOError::new(
+                ErrorKind::InvalidInput,
+                "surface handles must have between one and four planes",
+            ));
+        }
+
+        let mut message = Vec::with_capacity(MESSAGE_SIZE);
+        message.extend_from_slice(&self.descriptor.size.width.to_ne_bytes());
+        message.extend_from_slice(&self.descriptor.size.height.to_ne_bytes());
+        message.extend_from_slice(&self.descriptor.fourcc.to_ne_bytes());
+        message.extend_from_slice(&self.descriptor.modifier.to_ne_bytes());
+        message.extend_from_slice(&(planes.len() as u32).to_ne_bytes());
+        for plane in planes {
+            message.extend_from_slice(&plane.offset.to_ne_bytes());
+            message.extend_from_slice(&plane.stride.to_ne_bytes());
+        }
+        message.resize(MESSAGE_SIZE, 0);
+
+        let fds: Vec<RawFd> = planes.iter().map(|plane| plane.fd.as_raw_fd()).collect();
+
+        // The file descriptors travel with the first chunk of the message. Only stream sockets
+        // can accept less than the whole message, in which case we send the rest separately.
+        let mut sent = 0;
+        while sent < MESSAGE_SIZE {
+            let fds: &[RawFd] = if sent == 0 { &fds } else { &[] };
+            match unsafe { send_with_fds(socket.as_raw_fd(), &message[sent..], fds) } {
+                Ok(0) => {
+                    return Err(IOError::new(
+                        ErrorKind::WriteZero,
+                        "surface handle was only partially sent",
+                    ))
+                }
+                Ok(count) => sent += count,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Receives a handle sent with `send()` from the given Unix domain socket.
+    ///
+    /// On stream sockets, this keeps reading until the whole message has arrived. Any file
+    /// descriptors received are closed if the message turns out to be incomplete or malformed.
+    ///
+    /// This blocks if the socket is blocking and no handle is available yet.
+    pub fn recv<S>(socket: &S) -> io::Result<SurfaceHandle>
+    where
+        S: AsRawFd,
+    {
+        let socket = socket.as_raw_fd();
+        let mut message = [0u8; MESSAGE_SIZE];
+
+        // Take ownership of every file descriptor we are given right away, so that they're closed
+        // if anything goes wrong.
+        let mut fds = vec![];
+        let mut received = 0;
+        while received < MESSAGE_SIZE {
+            match unsafe { recv_with_fds(socket, &mut message[received..], &mut fds) } {
+                Ok(0) if received == 0 => {
+                    return Err(IOError::new(
+                        ErrorKind::UnexpectedEof,
+                        "socket closed before a surface handle was received",
+                    ))
+                }
+                Ok(0) => {
+                    return Err(IOError::new(
+                        ErrorKind::UnexpectedEof,
+                        "socket closed in the middle of a surface handle",
+                    ))
+                }
+                Ok(count) => received += count,
+                Err(ref err) if err.kind() == ErrorKind::Interrupted => continue,
+                Err(err) => return Err(err),
+            }
+
+            // Datagrams arrive whole, so a short one is malformed rather than incomplete.
+            if received < MESSAGE_SIZE && socket_type(socket)? != libc::SOCK_STREAM {
+                return Err(invalid_message());
+            }
+        }
+
+        let read_u32 = |offset: usize| {
+            let mut bytes = [0; 4];
+            bytes.copy_from_slice(&message[offset..(offset + 4)]);
+            u32::from_ne_bytes(bytes)
+        };
+        let mut modifier = [0; 8];
+        modifier.copy_from_slice(&message[12..20]);
+
+        let plane_count = read_u32(20) as usize;
+        if plane_count == 0 || plane_count > MAX_PLANES || plane_count != fds.len() {
+            return Err(invalid_message());
+        }
+
+        let planes = fds
+            .into_iter()
+            .enumerate()
+            .map(|(index, fd)| DmaBufPlane {
+                fd,
+                offset: read_u32(24 + index * 8),
+                stride: read_u32(28 + index * 8),
+            })
+            .collect();
+
+        Ok(SurfaceHandle {
+            descriptor: DmaBufDescriptor {
+                size: Size2D::new(read_u32(0) as i32, read_u32(4) as i32),
+                fourcc: read_u32(8),
+                modifier: u64::from_ne_bytes(modifier),
+                planes,
+            },
+        })
+    }
+}
+
+// Sends as much of `data` as the socket accepts, with `fds` attached as `SCM_RIGHTS` ancillary
+// data if there are any. Returns the number of bytes sent.
+unsafe fn send_with_fds(socket: RawFd, data: &[u8], fds: &[RawFd]) -> io::Result<usize> {
+    let fds_size = mem::size_of_val(fds);
+    let mut control = vec![0u8; libc::CMSG_SPACE(fds_size as u32) as usize];
+    let mut iov = libc::iovec {
+        iov_base: data.as_ptr() as *mut c_void,
+        iov_len: data.len(),
+    };
+    let mut header: libc::msghdr = mem::zeroed();
+    header.msg_iov = &mut iov;
+    header.msg_iovlen = 1;
+    if !fds.is_empty() {
+        header.msg_control = control.as_mut_ptr() as *mut c_void;
+        header.msg_controllen = control.len() as _;
+
+        let cmsg = libc::CMSG_FIRSTHDR(&header);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(fds_size as u32) as _;
+        ptr::copy_nonoverlapping(fds.as_ptr(), libc::CMSG_DATA(cmsg) as *mut RawFd, fds.len());
+    }
+
+    let sent = libc::sendmsg(socket, &header, libc::MSG_NOSIGNAL);
+    if sent < 0 {
+        return Err(IOError::last_os_error());
+    }
+    Ok(sent as usize)
+}
+
+// Receives up to `data.len()` bytes, appending any file descriptors that came with them to `fds`.
+// Returns the number of bytes received.
+unsafe fn recv_with_fds(
+    socket: RawFd,
+    data: &mut [u8],
+    fds: &mut Vec<OwnedFd>,
+) -> io::Result<usize> {
+    let fds_size = mem::size_of::<RawFd>() * MAX_PLANES;
+    let mut control = vec![0u8; libc::CMSG_SPACE(fds_size as u32) as usize];
+    let mut iov = libc::iovec {
+        iov_base: data.as_mut_ptr() as *mut c_void,
+        iov_len: data.len(),
+    };
+    let mut header: libc::msghdr = mem::zeroed();
+    header.msg_iov = &mut iov;
+    header.msg_iovlen = 1;
+    header.msg_control = control.as_mut_ptr() as *mut c_void;
+    header.msg_controllen = control.len() as _;
+
+    let received = libc::recvmsg(socket, &mut header, libc::MSG_CMSG_CLOEXEC);
+    if received < 0 {
+        return Err(IOError::last_os_error());
+    }
+
+    let mut cmsg = libc::CMSG_FIRSTHDR(&header);
+    while !cmsg.is_null() {
+        if (*cmsg).cmsg_level == libc::SOL_SOCKET && (*cmsg).cmsg_type == libc::SCM_RIGHTS {
+            let data_size = (*cmsg).cmsg_len as usize - libc::CMSG_LEN(0) as usize;
+            let data = libc::CMSG_DATA(cmsg) as *const RawFd;
+            for index in 0..(data_size / mem::size_of::<RawFd>()) {
+                let fd = ptr::read_unaligned(data.add(index));
+                fds.push(OwnedFd::from_raw_fd(fd));
+            }
+        }
+        cmsg = libc::CMSG_NXTHDR(&header, cmsg);
+    }
+
+    // Truncated ancillary data means that some file descriptors were dropped, and a truncated
+    // datagram means that the message was longer than any that `send()` produces.
+    if header.msg_flags & (libc::MSG_CTRUNC | libc::MSG_TRUNC) != 0 {
+        return Err(invalid_message());
+    }
+    Ok(received as usize)
+}
+
+fn socket_type(socket: RawFd) -> io::Result<libc::c_int> {
+    let mut socket_type: libc::c_int = 0;
+    let mut length = mem::size_of::<libc::c_int>() as libc::socklen_t;
+    let result = unsafe {
+        libc::getsockopt(
+            socket,
+            libc::SOL_SOCKET,
+            libc::SO_TYPE,
+            &mut socket_type as *mut libc::c_int as *mut c_void,
+            &mut length,
+        )
+    };
+    if result < 0 {
+        return Err(IOError::last_os_error());
+    }
+    Ok(socket_type)
+}
+
+fn invalid_message() -> IOError {
+    IOError::new(ErrorKind::InvalidData, "malformed surface handle message")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::surface::{DmaBufDescriptor, DmaBufPlane};
+    use super::SurfaceHandle;
+
+    use euclid::default::Size2D;
+    use std::fs::File;
+    use std::io::{ErrorKind, Read, Write};
+    use std::os::unix::io::{FromRawFd, OwnedFd};
+    use std::os::unix::net::{UnixDatagram, UnixStream};
+
+    // Returns the read and write ends of a new pipe.
+    fn pipe() -> (OwnedFd, OwnedFd) {
+        let mut fds = [0; 2];
+        unsafe {
+            assert_eq!(libc::pipe(fds.as_mut_ptr()), 0);
+            (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1]))
+        }
+    }
+
+    // Makes a two-plane handle whose "buffers" are the ends of a pipe, so that we can check that
+    // the received file descriptors refer to the same files.
+    fn pipe_handle() -> (SurfaceHandle, File, File) {
+        let (read_end, write_end) = pipe();
+        let (their_read_end, their_write_end) = (
+            read_end.try_clone().unwrap(),
+            write_end.try_clone().unwrap(),
+        );
+        let handle = SurfaceHandle::from_dmabuf(DmaBufDescriptor {
+            size: Size2D::new(640, 480),
+            fourcc: 0x34324241,
+            modifier: 0x0100_0000_0000_0002,
+            planes: vec![
+                DmaBufPlane {
+                    fd: read_end,
+                    offset: 0,
+                    stride: 2560,
+                },
+                DmaBufPlane {
+                    fd: write_end,
+                    offset: 1228800,
+                    stride: 64,
+                },
+            ],
+        });
+        (
+            handle,
+            File::from(their_read_end),
+            File::from(their_write_end),
+        )
+    }
+
+    fn check_received_handle(handle: SurfaceHandle, mut read_end: File, mut write_end: File) {
+        let descriptor = handle.into_dmabuf();
+        assert_eq!(descriptor.size, Size2D::new(640, 480));
+        assert_eq!(descriptor.fourcc, 0x34324241);
+        assert_eq!(descriptor.modifier, 0x0100_0000_0000_0002);
+        assert_eq!(descriptor.planes.len(), 2);
+        assert_eq!(
+            (descriptor.planes[0].offset, descriptor.planes[0].stride),
+            (0, 2560)
+        );
+        assert_eq!(
+            (descriptor.planes[1].offset, descriptor.planes[1].stride),
+            (1228800, 64)
+        );
+
+        // Data written through the received write end comes out of the original read end, and
+        // vice versa.
+        let mut planes = descriptor.planes.into_iter();
+        let mut received_read_end = File::from(planes.next().unwrap().fd);
+        let mut received_write_end = File::from(planes.next().unwrap().fd);
+        let mut buffer = [0; 5];
+        received_write_end.write_all(b"hello").unwrap();
+        read_end.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"hello");
+        write_end.write_all(b"world").unwrap();
+        received_read_end.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"world");
+    }
+
+    #[test]
+    fn test_send_and_recv_over_datagram_socket() {
+        let (sender, receiver) = UnixDatagram::pair().unwrap();
+        let (handle, read_end, write_end) = pipe_handle();
+        handle.send(&sender).unwrap();
+        drop(handle);
+        check_received_handle(SurfaceHandle::recv(&receiver).unwrap(), read_end, write_end);
+    }
+
+    #[test]
+    fn test_send_and_recv_over_stream_socket() {
+        let (sender, receiver) = UnixStream::pair().unwrap();
+        let (handle, read_end, write_end) = pipe_handle();
+        handle.send(&sender).unwrap();
+        drop(handle);
+        check_received_handle(SurfaceHandle::recv(&receiver).unwrap(), read_end, write_end);
+    }
+
+    #[test]
+    fn test_recv_rejects_short_datagrams() {
+        let (sender, receiver) = UnixDatagram::pair().unwrap();
+        sender.send(&[0; 8]).unwrap();
+        let err = SurfaceHandle::recv(&receiver).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_recv_reports_eof_in_the_middle_of_a_handle() {
+        let (mut sender, receiver) = UnixStream::pair().unwrap();
+        sender.write_all(&[0; 8]).unwrap();
+        drop(sender);
+        let err = SurfaceHandle::recv(&receiver).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnexpectedEof);
+    }
+}
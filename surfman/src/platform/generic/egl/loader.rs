@@ -0,0 +1,564 @@
+// surfman/surfman/src/platform/generic/egl/loader.rs
+//
+//! Locates and loads the EGL library on Linux.
+//!
+//! The library is looked for in the following places, in order:
+//!
+//! 1. The path passed to `set_egl_library_path()`, if any.
+//!
+//! 2. The path in the `SURFMAN_EGL_LIBRARY` environment variable, if set.
+//!
+//! 3. `libEGL.so.1`, then `libEGL.so`, in the dynamic linker's search path. The unversioned name
+//!    is usually only present when development packages are installed.
+//!
+//! 4. The GLVND vendor libraries (for example, `libEGL_mesa.so.0`) listed in the vendor JSON
+//!    files, for systems that ship a vendor library without the GLVND dispatcher.
+//!
+//! If a path was given explicitly, via either of the first two methods, nothing else is tried.
+//!
+//! A vendor library exports only `__egl_Main()`, through which it hands out its entry points once
+//! it has been given a table of callbacks. Those callbacks serve the dispatch stubs that the
+//! vendor provides to the GLVND dispatcher, and they report dispatcher state, such as the current
+//! context, that surfman doesn't track. surfman doesn't fill that state in; it calls the vendor's
+//! own entry points instead of the stubs, so the callbacks are never reached. Only Mesa's vendor
+//! library is known to work this way, so other vendor libraries aren't loaded.
+
+use crate::egl;
+use crate::egl::types::{EGLAttrib, EGLint};
+use crate::egl::types::{EGLBoolean, EGLContext, EGLDeviceEXT, EGLDisplay, EGLSurface, EGLenum};
+use crate::Error;
+
+use libc::{dlclose, dlopen, dlsym, RTLD_LAZY};
+use std::env;
+use std::ffi::{CStr, CString, OsStr};
+use std::fs;
+use std::mem;
+use std::os::raw::{c_char, c_int, c_void};
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+const EGL_LIBRARY_PATH_ENV_VAR: &str = "SURFMAN_EGL_LIBRARY";
+const EGL_LIBRARY_NAMES: [&str; 2] = ["libEGL.so.1", "libEGL.so"];
+
+const GLVND_VENDOR_FILENAMES_ENV_VAR: &str = "__EGL_VENDOR_LIBRARY_FILENAMES";
+const GLVND_VENDOR_DIRS_ENV_VAR: &str = "__EGL_VENDOR_LIBRARY_DIRS";
+const GLVND_VENDOR_DIRS: [&str; 2] = ["/etc/glvnd/egl_vendor.d", "/usr/share/glvnd/egl_vendor.d"];
+
+// The file names of the vendor libraries that can be loaded without the GLVND dispatcher.
+const GLVND_SUPPORTED_VENDOR_PREFIXES: [&str; 1] = ["libEGL_mesa.so"];
+
+// Version 0.2 of the GLVND EGL vendor ABI, from `glvnd/libeglabi.h`.
+const GLVND_EGL_VENDOR_ABI_VERSION: u32 = 2;
+const GLVND_VENDOR_STRING_PLATFORM_EXTENSIONS: c_int = 0;
+
+lazy_static! {
+    static ref EGL_LIBRARY_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+    // This is `None` if no EGL library could be loaded.
+    static ref EGL_LIBRARY: Option<EGLLibrary> = unsafe { EGLLibrary::load() };
+    // The client extensions of a GLVND vendor library, including its platform extensions.
+    static ref GLVND_CLIENT_EXTENSIONS: CString = unsafe { glvnd_client_extensions() };
+}
+
+// Set once the library has been searched for, after which the path can no longer change.
+static EGL_LIBRARY_LOAD_STARTED: AtomicBool = AtomicBool::new(false);
+
+/// Sets the path that the EGL library is loaded from, taking precedence over the
+/// `SURFMAN_EGL_LIBRARY` environment variable and the default search.
+///
+/// The path may name either an EGL library such as `libEGL.so.1` or a GLVND vendor library such
+/// as `libEGL_mesa.so.0`. It must be set before the first connection is opened; afterward, this
+/// returns `Failed`.
+pub fn set_egl_library_path<P>(path: P) -> Result<(), Error>
+where
+    P: Into<PathBuf>,
+{
+    let mut egl_library_path = EGL_LIBRARY_PATH.lock().unwrap();
+    if EGL_LIBRARY_LOAD_STARTED.load(Ordering::SeqCst) {
+        return Err(Error::Failed);
+    }
+    *egl_library_path = Some(path.into());
+    Ok(())
+}
+
+/// Returns `NoGLLibraryFound` if no EGL library could be loaded.
+///
+/// Every EGL call panics in that case, so connections check this before making any.
+pub(crate) fn egl_library_loaded() -> Result<(), Error> {
+    EGL_LIBRARY
+        .as_ref()
+        .map(|_| ())
+        .ok_or(Error::NoGLLibraryFound)
+}
+
+pub(crate) fn get_proc_address(symbol_name: &str) -> *const c_void {
+    match *EGL_LIBRARY {
+        Some(ref library) => unsafe { library.get_proc_address(symbol_name) },
+        None => ptr::null(),
+    }
+}
+
+struct EGLLibrary {
+    handle: *mut c_void,
+    glvnd_imports: Option<Box<GLVNDImports>>,
+}
+
+unsafe impl Send for EGLLibrary {}
+unsafe impl Sync for EGLLibrary {}
+
+impl EGLLibrary {
+    unsafe fn load() -> Option<EGLLibrary> {
+        let explicit_path = {
+            let egl_library_path = EGL_LIBRARY_PATH.lock().unwrap();
+            EGL_LIBRARY_LOAD_STARTED.store(true, Ordering::SeqCst);
+            egl_library_path
+                .clone()
+                .or_else(|| env::var_os(EGL_LIBRARY_PATH_ENV_VAR).map(PathBuf::from))
+        };
+
+        if let Some(path) = explicit_path {
+            let library = EGLLibrary::open(&path);
+            if library.is_none() {
+                warn!("Couldn't load the EGL library at {}", path.display());
+            }
+            return library;
+        }
+
+        let library = EGL_LIBRARY_NAMES
+            .iter()
+            .map(Path::new)
+            .chain(glvnd_vendor_libraries().iter().map(PathBuf::as_path))
+            .find_map(|path| EGLLibrary::open(path));
+        if library.is_none() {
+            warn!("Couldn't find an EGL library");
+        }
+        library
+    }
+
+    unsafe fn open(path: &Path) -> Option<EGLLibrary> {
+        let c_path = CString::new(path.as_os_str().as_bytes()).ok()?;
+        let handle = dlopen(c_path.as_ptr(), RTLD_LAZY);
+        if handle.is_null() {
+            return None;
+        }
+
+        if !dlsym(handle, b"eglGetProcAddress\0".as_ptr() as *const c_char).is_null() {
+            debug!("Loaded the EGL library {:?}", path);
+            return Some(EGLLibrary {
+                handle,
+                glvnd_imports: None,
+            });
+        }
+
+        // Not a regular EGL library, so see whether it's a GLVND vendor library we can use.
+        let egl_main = dlsym(handle, b"__egl_Main\0".as_ptr() as *const c_char);
+        if egl_main.is_null() {
+            dlclose(handle);
+            return None;
+        }
+        if !is_supported_glvnd_vendor(path) {
+            debug!("Skipping the unsupported GLVND vendor library {:?}", path);
+            dlclose(handle);
+            return None;
+        }
+        let egl_main: GLVNDMainProc = mem::transmute(egl_main);
+
+        let mut imports: Box<GLVNDImports> = Box::new(mem::zeroed());
+        let ok = egl_main(
+            GLVND_EGL_VENDOR_ABI_VERSION,
+            &GLVND_EXPORTS,
+            &GLVND_VENDOR as *const u8 as *mut c_void,
+            &mut *imports,
+        );
+        if ok == egl::FALSE || imports.get_proc_address.is_none() {
+            dlclose(handle);
+            return None;
+        }
+
+        debug!("Loaded the GLVND vendor library {:?}", path);
+        Some(EGLLibrary {
+            handle,
+            glvnd_imports: Some(imports),
+        })
+    }
+
+    unsafe fn get_proc_address(&self, symbol_name: &str) -> *const c_void {
+        let symbol_name = CString::new(symbol_name).unwrap();
+        match self.glvnd_imports {
+            None => dlsym(self.handle, symbol_name.as_ptr()) as *const c_void,
+            Some(ref imports) => {
+                // Vendors leave platform extensions out of their client extension string, since
+                // the dispatcher normally appends them.
+                if symbol_name.as_bytes() == b"eglQueryString" {
+                    return glvnd_query_string as *const c_void;
+                }
+                let get_proc_address = imports.get_proc_address.unwrap();
+                get_proc_address(symbol_name.as_ptr()) as *const c_void
+            }
+        }
+    }
+}
+
+fn is_supported_glvnd_vendor(path: &Path) -> bool {
+    let file_name = match path.file_name() {
+        Some(file_name) => file_name.to_string_lossy(),
+        None => return false,
+    };
+    GLVND_SUPPORTED_VENDOR_PREFIXES
+        .iter()
+        .any(|prefix| file_name.starts_with(prefix))
+}
+
+// Returns the vendor libraries listed in the GLVND vendor JSON files, in the order that the GLVND
+// dispatcher would try them.
+fn glvnd_vendor_libraries() -> Vec<PathBuf> {
+    let json_paths: Vec<PathBuf> = match env::var_os(GLVND_VENDOR_FILENAMES_ENV_VAR) {
+        Some(filenames) => env::split_paths(&filenames).collect(),
+        None => {
+            let dirs: Vec<PathBuf> = match env::var_os(GLVND_VENDOR_DIRS_ENV_VAR) {
+                Some(dirs) => env::split_paths(&dirs).collect(),
+                None => GLVND_VENDOR_DIRS.iter().map(PathBuf::from).collect(),
+            };
+            glvnd_vendor_json_paths(&dirs)
+        }
+    };
+    glvnd_vendor_libraries_from_json(&json_paths)
+}
+
+// Returns the JSON files in the given directories, in directory order and then sorted by name
+// within each directory. Missing directories are skipped.
+fn glvnd_vendor_json_paths(dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let mut json_paths = vec![];
+    for dir in dirs {
+        let mut dir_json_paths: Vec<PathBuf> = match fs::read_dir(dir) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension() == Some(OsStr::new("json")))
+                .collect(),
+            Err(_) => continue,
+        };
+        dir_json_paths.sort();
+        json_paths.extend(dir_json_paths);
+    }
+    json_paths
+}
+
+// Returns the library paths named by the given JSON files, skipping files that can't be read or
+// don't name a library.
+fn glvnd_vendor_libraries_from_json(json_paths: &[PathBuf]) -> Vec<PathBuf> {
+    json_paths
+        .iter()
+        .filter_map(|json_path| {
+            let json = fs::read_to_string(json_path).ok()?;
+            let library_path = PathBuf::from(json_string_value(&json, "library_path")?);
+            // Relative paths other than bare library names are relative to the JSON file.
+            if library_path.is_relative() && library_path.components().count() > 1 {
+                Some(json_path.parent()?.join(library_path))
+            } else {
+                Some(library_path)
+            }
+        })
+        .collect()
+}
+
+// Extracts the string value of the given key from a GLVND vendor JSON file.
+//
+// The files are simple enough that this doesn't need a full JSON parser. Escape sequences aren't
+// handled, as library paths don't contain them in practice.
+fn json_string_value<'a>(json: &'a str, key: &str) -> Option<&'a str> {
+    let key_start = json.find(&format!("\"{}\"", key))?;
+    let rest = &json[key_start + key.len() + 2..];
+    let rest = rest.trim_start().strip_prefix(':')?.trim_start();
+    let rest = rest.strip_prefix('"')?;
+    Some(&rest[..rest.find('"')?])
+}
+
+unsafe fn glvnd_client_extensions() -> CString {
+    let library = EGL_LIBRARY.as_ref().unwrap();
+    let imports = library.glvnd_imports.as_ref().unwrap();
+
+    let query_string: Option<extern "C" fn(EGLDisplay, EGLint) -> *const c_char> = mem::transmute(
+        (imports.get_proc_address.unwrap())(b"eglQueryString\0".as_ptr() as *const c_char),
+    );
+    let mut extensions = vec![];
+    if let Some(query_string) = query_string {
+        let client_extensions = query_string(egl::NO_DISPLAY, egl::EXTENSIONS as EGLint);
+        if !client_extensions.is_null() {
+            extensions.extend_from_slice(CStr::from_ptr(client_extensions).to_bytes());
+        }
+    }
+    if let Some(get_vendor_string) = imports.get_vendor_string {
+        let platform_extensions = get_vendor_string(GLVND_VENDOR_STRING_PLATFORM_EXTENSIONS);
+        if !platform_extensions.is_null() {
+            extensions.push(b' ');
+            extensions.extend_from_slice(CStr::from_ptr(platform_extensions).to_bytes());
+        }
+    }
+    CString::new(extensions).unwrap_or_default()
+}
+
+extern "C" fn glvnd_query_string(egl_display: EGLDisplay, name: EGLint) -> *const c_char {
+    unsafe {
+        if egl_display == egl::NO_DISPLAY && name == egl::EXTENSIONS as EGLint {
+            return GLVND_CLIENT_EXTENSIONS.as_ptr();
+        }
+        let imports = EGL_LIBRARY
+            .as_ref()
+            .unwrap()
+            .glvnd_imports
+            .as_ref()
+            .unwrap();
+        let query_string: Option<extern "C" fn(EGLDisplay, EGLint) -> *const c_char> =
+            mem::transmute((imports.get_proc_address.unwrap())(
+                b"eglQueryString\0".as_ptr() as *const c_char,
+            ));
+        match query_string {
+            Some(query_string) => query_string(egl_display, name),
+            None => ptr::null(),
+        }
+    }
+}
+
+// The GLVND vendor ABI, from `glvnd/libeglabi.h`.
+
+type GLVNDMainProc = extern "C" fn(
+    version: u32,
+    exports: *const GLVNDExports,
+    vendor: *mut c_void,
+    imports: *mut GLVNDImports,
+) -> EGLBoolean;
+
+#[repr(C)]
+struct GLVNDExports {
+    thread_init: extern "C" fn(),
+    get_current_api: extern "C" fn() -> EGLenum,
+    get_current_vendor: extern "C" fn() -> *mut c_void,
+    get_current_context: extern "C" fn() -> EGLContext,
+    get_current_display: extern "C" fn() -> EGLDisplay,
+    get_current_surface: extern "C" fn(read_draw: EGLint) -> EGLSurface,
+    fetch_dispatch_entry: extern "C" fn(vendor: *mut c_void, index: c_int) -> *mut c_void,
+    set_egl_error: extern "C" fn(error: EGLint),
+    set_last_vendor: extern "C" fn(vendor: *mut c_void) -> EGLBoolean,
+    get_vendor_from_display: extern "C" fn(display: EGLDisplay) -> *mut c_void,
+    get_vendor_from_device: extern "C" fn(device: EGLDeviceEXT) -> *mut c_void,
+    set_vendor_for_device: extern "C" fn(device: EGLDeviceEXT, vendor: *mut c_void) -> EGLBoolean,
+}
+
+#[repr(C)]
+struct GLVNDImports {
+    get_platform_display: Option<
+        extern "C" fn(
+            platform: EGLenum,
+            native_display: *mut c_void,
+            attrib_list: *const EGLAttrib,
+        ) -> EGLDisplay,
+    >,
+    get_supports_api: Option<extern "C" fn(api: EGLenum) -> EGLBoolean>,
+    get_vendor_string: Option<extern "C" fn(name: c_int) -> *const c_char>,
+    get_proc_address: Option<extern "C" fn(proc_name: *const c_char) -> *mut c_void>,
+    get_dispatch_address: Option<extern "C" fn(proc_name: *const c_char) -> *mut c_void>,
+    set_dispatch_index: Option<extern "C" fn(proc_name: *const c_char, index: c_int)>,
+    is_patch_supported: Option<extern "C" fn(stub_type: c_int, stub_size: c_int) -> u8>,
+    initiate_patch: Option<extern "C" fn(stub_type: c_int, stub_size: c_int, lookup: *mut c_void)>,
+    release_patch: Option<extern "C" fn()>,
+    patch_thread_attach: Option<extern "C" fn()>,
+    find_native_display_platform: Option<extern "C" fn(native_display: *mut c_void) -> EGLenum>,
+}
+
+// The opaque vendor handle that the vendor library passes back to the callbacks. Only one vendor
+// is ever loaded, so its address is all that's needed.
+static GLVND_VENDOR: u8 = 0;
+
+static GLVND_EXPORTS: GLVNDExports = GLVNDExports {
+    thread_init: glvnd_thread_init,
+    get_current_api: glvnd_get_current_api,
+    get_current_vendor: glvnd_get_current_vendor,
+    get_current_context: glvnd_get_current_context,
+    get_current_display: glvnd_get_current_display,
+    get_current_surface: glvnd_get_current_surface,
+    fetch_dispatch_entry: glvnd_fetch_dispatch_entry,
+    set_egl_error: glvnd_set_egl_error,
+    set_last_vendor: glvnd_set_last_vendor,
+    get_vendor_from_display: glvnd_get_vendor,
+    get_vendor_from_device: glvnd_get_vendor,
+    set_vendor_for_device: glvnd_set_vendor_for_device,
+};
+
+// The callbacks below report no current context, display, surface, or dispatch entries, since
+// surfman doesn't track dispatcher state. Mesa only calls them from the dispatch stubs it hands to
+// the GLVND dispatcher, which surfman never uses, so they just need to be safe to call.
+
+extern "C" fn glvnd_thread_init() {}
+
+extern "C" fn glvnd_get_current_api() -> EGLenum {
+    egl::OPENGL_ES_API
+}
+
+extern "C" fn glvnd_get_current_vendor() -> *mut c_void {
+    &GLVND_VENDOR as *const u8 as *mut c_void
+}
+
+extern "C" fn glvnd_get_current_context() -> EGLContext {
+    egl::NO_CONTEXT
+}
+
+extern "C" fn glvnd_get_current_display() -> EGLDisplay {
+    egl::NO_DISPLAY
+}
+
+extern "C" fn glvnd_get_current_surface(_: EGLint) -> EGLSurface {
+    egl::NO_SURFACE
+}
+
+extern "C" fn glvnd_fetch_dispatch_entry(_: *mut c_void, _: c_int) -> *mut c_void {
+    ptr::null_mut()
+}
+
+extern "C" fn glvnd_set_egl_error(_: EGLint) {}
+
+extern "C" fn glvnd_set_last_vendor(_: *mut c_void) -> EGLBoolean {
+    egl::TRUE
+}
+
+extern "C" fn glvnd_get_vendor<T>(_: T) -> *mut c_void {
+    &GLVND_VENDOR as *const u8 as *mut c_void
+}
+
+extern "C" fn glvnd_set_vendor_for_device(_: EGLDeviceEXT, _: *mut c_void) -> EGLBoolean {
+    egl::TRUE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{glvnd_vendor_json_paths, glvnd_vendor_libraries_from_json};
+    use super::{is_supported_glvnd_vendor, json_string_value};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::process;
+
+    const MESA_VENDOR_JSON: &str = r#"{
+        "file_format_version" : "1.0.0",
+        "ICD" : {
+            "library_path" : "libEGL_mesa.so.0"
+        }
+    }"#;
+
+    // Creates an empty scratch directory unique to this process and test.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("surfman-loader-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn write_vendor_json(dir: &Path, file_name: &str, library_path: &str) -> PathBuf {
+        let path = dir.join(file_name);
+        let json = format!(
+            "{{ \"ICD\": {{ \"library_path\": \"{}\" }} }}",
+            library_path
+        );
+        fs::write(&path, json).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_json_string_value() {
+        assert_eq!(
+            json_string_value(MESA_VENDOR_JSON, "library_path"),
+            Some("libEGL_mesa.so.0")
+        );
+        assert_eq!(
+            json_string_value(MESA_VENDOR_JSON, "file_format_version"),
+            Some("1.0.0")
+        );
+        assert_eq!(
+            json_string_value(r#"{"key":"value"}"#, "key"),
+            Some("value")
+        );
+        assert_eq!(json_string_value(r#"{"key": ""}"#, "key"), Some(""));
+    }
+
+    #[test]
+    fn test_json_string_value_malformed() {
+        // Missing key.
+        assert_eq!(json_string_value(MESA_VENDOR_JSON, "api_version"), None);
+        // Missing colon.
+        assert_eq!(json_string_value(r#"{"key" "value"}"#, "key"), None);
+        // Non-string values.
+        assert_eq!(json_string_value(r#"{"key": 1}"#, "key"), None);
+        assert_eq!(
+            json_string_value(r#"{"key": {"key": "value"}}"#, "key"),
+            None
+        );
+        // Unterminated string.
+        assert_eq!(json_string_value(r#"{"key": "value"#, "key"), None);
+        // Truncated after the key.
+        assert_eq!(json_string_value(r#"{"key""#, "key"), None);
+        assert_eq!(json_string_value("", "key"), None);
+    }
+
+    #[test]
+    fn test_glvnd_vendor_json_path_order() {
+        let first_dir = scratch_dir("order-first");
+        let second_dir = scratch_dir("order-second");
+        let missing_dir = second_dir.join("missing");
+
+        let first_b = write_vendor_json(&first_dir, "50_b.json", "libEGL_b.so.0");
+        let first_a = write_vendor_json(&first_dir, "10_a.json", "libEGL_a.so.0");
+        let second_a = write_vendor_json(&second_dir, "00_a.json", "libEGL_c.so.0");
+        fs::write(first_dir.join("README"), "not a vendor file").unwrap();
+        fs::write(first_dir.join("60_c.json.bak"), MESA_VENDOR_JSON).unwrap();
+
+        // Directories are searched in the given order, and files are sorted within each one.
+        let dirs = [first_dir.clone(), missing_dir, second_dir.clone()];
+        assert_eq!(
+            glvnd_vendor_json_paths(&dirs),
+            vec![first_a.clone(), first_b.clone(), second_a.clone()]
+        );
+        let dirs = [second_dir.clone(), first_dir.clone()];
+        assert_eq!(
+            glvnd_vendor_json_paths(&dirs),
+            vec![second_a, first_a, first_b]
+        );
+
+        fs::remove_dir_all(&first_dir).unwrap();
+        fs::remove_dir_all(&second_dir).unwrap();
+    }
+
+    #[test]
+    fn test_glvnd_vendor_libraries_from_json() {
+        let dir = scratch_dir("libraries");
+        let bare = write_vendor_json(&dir, "10_bare.json", "libEGL_mesa.so.0");
+        let relative = write_vendor_json(&dir, "20_relative.json", "lib/libEGL_mesa.so.0");
+        let absolute = write_vendor_json(&dir, "30_absolute.json", "/opt/libEGL_mesa.so.0");
+        let malformed = dir.join("40_malformed.json");
+        fs::write(&malformed, r#"{"ICD": {"library_path": 0}}"#).unwrap();
+        let missing = dir.join("50_missing.json");
+
+        // Bare names are left for the dynamic linker to find, and relative paths are resolved
+        // against the directory of the JSON file. Unreadable and malformed files are skipped.
+        let json_paths = [bare, relative, absolute, malformed, missing];
+        assert_eq!(
+            glvnd_vendor_libraries_from_json(&json_paths),
+            vec![
+                PathBuf::from("libEGL_mesa.so.0"),
+                dir.join("lib/libEGL_mesa.so.0"),
+                PathBuf::from("/opt/libEGL_mesa.so.0"),
+            ]
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_supported_glvnd_vendors() {
+        assert!(is_supported_glvnd_vendor(Path::new("libEGL_mesa.so.0")));
+        assert!(is_supported_glvnd_vendor(Path::new(
+            "/usr/lib/libEGL_mesa.so.0"
+        )));
+        assert!(!is_supported_glvnd_vendor(Path::new("libEGL_nvidia.so.0")));
+        assert!(!is_supported_glvnd_vendor(Path::new("/")));
+    }
+}
@@ -5,7 +5,12 @@
 #![allow(dead_code)]
 
 pub(crate) mod context;
+pub(crate) mod debug;
 pub(crate) mod device;
 pub(crate) mod error;
 pub(crate) mod ffi;
+#[cfg(unix)]
+pub(crate) mod handle;
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+pub(crate) mod loader;
 pub(crate) mod surface;
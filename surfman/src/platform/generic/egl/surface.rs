@@ -3,11 +3,12 @@
 //! Functionality common to backends using EGL surfaces.
 
 use super::context::CurrentContextGuard;
-use super::device::EGL_FUNCTIONS;
+use super::device::{self, EGL_FUNCTIONS};
 use crate::egl;
-use crate::egl::types::{EGLAttrib, EGLConfig, EGLContext, EGLDisplay, EGLSurface, EGLint};
+use crate::egl::types::{EGLAttrib, EGLConfig, EGLContext, EGLDisplay, EGLSurface, EGLenum};
+use crate::egl::types::{EGLNativeWindowType, EGLint};
 use crate::gl;
-use crate::gl::types::{GLint, GLuint};
+use crate::gl::types::{GLint, GLsizeiptr, GLuint};
 use crate::gl_utils;
 use crate::platform::generic::egl::error::ToWindowingApiError;
 use crate::platform::generic::egl::ffi::EGLClientBuffer;
@@ -16,16 +17,52 @@ use crate::platform::generic::egl::ffi::EGL_EXTENSION_FUNCTIONS;
 use crate::platform::generic::egl::ffi::EGL_GL_TEXTURE_2D_KHR;
 use crate::platform::generic::egl::ffi::EGL_IMAGE_PRESERVED_KHR;
 use crate::platform::generic::egl::ffi::EGL_NO_IMAGE_KHR;
-use crate::renderbuffers::Renderbuffers;
+use crate::platform::generic::egl::ffi::EGL_PLATFORM_X11_KHR;
+use crate::renderbuffers::{self, MultisampleFramebuffer, Renderbuffers};
 use crate::Gl;
-use crate::{ContextAttributes, ContextID, Error, SurfaceID, SurfaceInfo};
+use crate::SurfaceID;
+use crate::SurfaceInfo;
+use crate::{ContextAttributes, ContextID, EGLPlatformPath, Error, SurfaceAccess, SurfaceFormat};
 
 use euclid::default::Size2D;
+use std::ffi::CStr;
 use std::fmt::{self, Debug, Formatter};
 use std::marker::PhantomData;
 use std::mem;
-use std::os::raw::c_void;
+use std::os::raw::{c_char, c_ulong, c_void};
 use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[cfg(unix)]
+use crate::platform::generic::egl::ffi::{
+    DMA_BUF_IOCTL_SYNC, DMA_BUF_SYNC_END, DMA_BUF_SYNC_READ, DMA_BUF_SYNC_START,
+    DMA_BUF_SYNC_WRITE, DRM_FORMAT_ABGR16161616F, DRM_FORMAT_ABGR2101010, DRM_FORMAT_ABGR8888,
+    DRM_FORMAT_GR88, DRM_FORMAT_MOD_INVALID, DRM_FORMAT_MOD_LINEAR, DRM_FORMAT_R8,
+    DRM_FORMAT_XBGR16161616F, DRM_FORMAT_XBGR2101010, EGL_DMA_BUF_PLANE0_FD_EXT,
+    EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT, EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT,
+    EGL_DMA_BUF_PLANE0_OFFSET_EXT, EGL_DMA_BUF_PLANE0_PITCH_EXT, EGL_DMA_BUF_PLANE1_FD_EXT,
+    EGL_DMA_BUF_PLANE1_MODIFIER_HI_EXT, EGL_DMA_BUF_PLANE1_MODIFIER_LO_EXT,
+    EGL_DMA_BUF_PLANE1_OFFSET_EXT, EGL_DMA_BUF_PLANE1_PITCH_EXT, EGL_DMA_BUF_PLANE2_FD_EXT,
+    EGL_DMA_BUF_PLANE2_MODIFIER_HI_EXT, EGL_DMA_BUF_PLANE2_MODIFIER_LO_EXT,
+    EGL_DMA_BUF_PLANE2_OFFSET_EXT, EGL_DMA_BUF_PLANE2_PITCH_EXT, EGL_DMA_BUF_PLANE3_FD_EXT,
+    EGL_DMA_BUF_PLANE3_MODIFIER_HI_EXT, EGL_DMA_BUF_PLANE3_MODIFIER_LO_EXT,
+    EGL_DMA_BUF_PLANE3_OFFSET_EXT, EGL_DMA_BUF_PLANE3_PITCH_EXT, EGL_LINUX_DMA_BUF_EXT,
+    EGL_LINUX_DRM_FOURCC_EXT,
+};
+#[cfg(unix)]
+use crate::WindowingApiError;
+#[cfg(unix)]
+use std::io;
+#[cfg(unix)]
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+#[cfg(unix)]
+use std::slice;
+
+// The next ID to give to a surface whose texture isn't wrapped in an EGL image.
+//
+// Other surfaces are identified by the address of their image or window surface, which is never
+// this small.
+static NEXT_TEXTURE_SURFACE_ID: AtomicUsize = AtomicUsize::new(1);
 
 #[allow(dead_code)]
 #[derive(Clone)]
@@ -38,6 +75,8 @@ pub struct EGLBackedSurface {
     pub(crate) context_id: ContextID,
     pub(crate) size: Size2D<i32>,
     pub(crate) objects: EGLSurfaceObjects,
+    pub(crate) access: SurfaceAccess,
+    pub(crate) format: SurfaceFormat,
     pub(crate) destroyed: bool,
 }
 
@@ -56,6 +95,22 @@ pub(crate) enum EGLSurfaceObjects {
         framebuffer_object: GLuint,
         texture_object: GLuint,
         renderbuffers: Renderbuffers,
+        // If present, this is rendered to instead of `framebuffer_object` and resolved into it.
+        multisample_framebuffer: Option<MultisampleFramebuffer>,
+    },
+    // A texture on a display that can't wrap textures in EGL images. Other contexts sample from
+    // copies of its contents.
+    Texture {
+        egl_context: EGLContext,
+        id: usize,
+        framebuffer_object: GLuint,
+        texture_object: GLuint,
+        renderbuffers: Renderbuffers,
+        multisample_framebuffer: Option<MultisampleFramebuffer>,
+        // The contents of the texture as of the last time the surface was unbound from its
+        // context, read with the format and type that `SurfaceFormat::gl_texture_formats()`
+        // returns.
+        contents: Vec<u8>,
     },
     Window {
         native_window: *const c_void,
@@ -75,7 +130,82 @@ impl Debug for EGLSurfaceTexture {
     }
 }
 
+/// Describes a buffer shared via DMA-BUF file descriptors.
+///
+/// The file descriptors are owned by this structure and are closed when it is dropped.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct DmaBufDescriptor {
+    /// The size of the buffer, in pixels.
+    pub size: Size2D<i32>,
+    /// The DRM fourcc code describing the pixel format of the buffer.
+    pub fourcc: u32,
+    /// The DRM format modifier describing the tiling and compression of the buffer.
+    ///
+    /// This is `DRM_FORMAT_MOD_INVALID` (`0x00ffffffffffffff`) if the layout is implied by the
+    /// driver.
+    pub modifier: u64,
+    /// The planes of the buffer, in order.
+    pub planes: Vec<DmaBufPlane>,
+}
+
+/// A single plane of a DMA-BUF buffer.
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct DmaBufPlane {
+    /// The DMA-BUF file descriptor containing this plane.
+    pub fd: OwnedFd,
+    /// The offset of this plane within the file, in bytes.
+    pub offset: u32,
+    /// The number of bytes between the starts of consecutive rows of this plane.
+    pub stride: u32,
+}
+
+// The CPU view of the pixel data of a surface.
+//
+// Backends wrap this in their own `SurfaceDataGuard` type, which calls `unlock()` when dropped.
+#[cfg(unix)]
+pub(crate) struct EGLSurfaceDataGuard<'a> {
+    ptr: *mut u8,
+    len: usize,
+    stride: usize,
+    mapping: SurfaceDataMapping,
+    phantom: PhantomData<&'a mut EGLBackedSurface>,
+}
+
+#[cfg(unix)]
+enum SurfaceDataMapping {
+    // The memory of the surface itself, mapped via its DMA-BUF file descriptor.
+    DmaBuf {
+        fd: OwnedFd,
+        address: *mut c_void,
+        length: usize,
+    },
+    // A pixel buffer object that the contents of the surface were copied into. It belongs to the
+    // context that was current when the surface was locked, and its contents are copied back
+    // into the surface on unlock.
+    PixelBuffer {
+        egl_display: EGLDisplay,
+        egl_context: EGLContext,
+        size: Size2D<i32>,
+        texture_object: GLuint,
+        pixel_buffer_object: GLuint,
+    },
+    // The copy of the contents of a surface whose texture isn't wrapped in an EGL image. It's
+    // copied into the texture, which belongs to `egl_context`, on unlock.
+    Contents {
+        egl_display: EGLDisplay,
+        egl_context: EGLContext,
+        size: Size2D<i32>,
+        texture_object: GLuint,
+    },
+    Unlocked,
+}
+
 impl EGLBackedSurface {
+    // Creates a texture-backed surface. If `texture_images` is false, the texture isn't wrapped in
+    // an EGL image, and surface textures created from it are copies.
+    #[allow(clippy::too_many_arguments, non_snake_case)]
     pub(crate) fn new_generic(
         gl: &Gl,
         egl_display: EGLDisplay,
@@ -83,7 +213,10 @@ impl EGLBackedSurface {
         context_id: ContextID,
         context_attributes: &ContextAttributes,
         size: &Size2D<i32>,
-    ) -> EGLBackedSurface {
+        access: SurfaceAccess,
+        format: SurfaceFormat,
+        texture_images: bool,
+    ) -> Result<EGLBackedSurface, Error> {
         let egl_image_attribs = [
             EGL_IMAGE_PRESERVED_KHR as EGLint,
             egl::FALSE as EGLint,
@@ -92,6 +225,11 @@ impl EGLBackedSurface {
         ];
 
         unsafe {
+            if format != SurfaceFormat::RGBA8 && !gl_utils::surface_format_is_renderable(gl, format)
+            {
+                return Err(Error::UnsupportedSurfaceFormat);
+            }
+
             // Create our texture.
             let mut texture_object = 0;
             gl.GenTextures(1, &mut texture_object);
@@ -107,15 +245,16 @@ impl EGLBackedSurface {
             if unpack_buffer != 0 {
                 gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
             }
+            let (internal_format, pixel_format, pixel_type) = format.gl_texture_formats();
             gl.TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA as GLint,
+                internal_format as GLint,
                 size.width,
                 size.height,
                 0,
-                gl::RGBA,
-                gl::UNSIGNED_BYTE,
+                pixel_format,
+                pixel_type,
                 ptr::null(),
             );
             // Restore the old bindings
@@ -124,16 +263,98 @@ impl EGLBackedSurface {
                 gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, unpack_buffer as _);
             }
 
-            // Create our image.
-            let egl_client_buffer = texture_object as usize as EGLClientBuffer;
-            let egl_image = (EGL_EXTENSION_FUNCTIONS.CreateImageKHR)(
-                egl_display,
-                egl_context,
-                EGL_GL_TEXTURE_2D_KHR,
-                egl_client_buffer,
-                egl_image_attribs.as_ptr(),
+            // Create our image, if possible.
+            let egl_image = match EGL_EXTENSION_FUNCTIONS.CreateImageKHR {
+                Some(eglCreateImageKHR) if texture_images => {
+                    let egl_client_buffer = texture_object as usize as EGLClientBuffer;
+                    Some(eglCreateImageKHR(
+                        egl_display,
+                        egl_context,
+                        EGL_GL_TEXTURE_2D_KHR,
+                        egl_client_buffer,
+                        egl_image_attribs.as_ptr(),
+                    ))
+                }
+                _ => None,
+            };
+
+            // Create the framebuffer, and bind the texture to it.
+            let framebuffer_object =
+                gl_utils::create_and_bind_framebuffer(gl, gl::TEXTURE_2D, texture_object);
+
+            // If multisampling was requested, depth and stencil belong to the multisampled
+            // framebuffer. Otherwise, bind renderbuffers as appropriate.
+            let multisample_framebuffer =
+                MultisampleFramebuffer::new(gl, size, context_attributes, format);
+            let renderbuffers = match multisample_framebuffer {
+                Some(_) => Renderbuffers::IndividualDepthStencil {
+                    depth: 0,
+                    stencil: 0,
+                },
+                None => {
+                    let renderbuffers = Renderbuffers::new(gl, size, context_attributes);
+                    renderbuffers.bind_to_current_framebuffer(gl);
+                    renderbuffers
+                }
+            };
+
+            debug_assert_eq!(
+                gl.CheckFramebufferStatus(gl::FRAMEBUFFER),
+                gl::FRAMEBUFFER_COMPLETE
             );
 
+            let objects = match egl_image {
+                Some(egl_image) => EGLSurfaceObjects::TextureImage {
+                    egl_image,
+                    framebuffer_object,
+                    texture_object,
+                    renderbuffers,
+                    multisample_framebuffer,
+                },
+                None => EGLSurfaceObjects::Texture {
+                    egl_context,
+                    id: NEXT_TEXTURE_SURFACE_ID.fetch_add(1, Ordering::Relaxed),
+                    framebuffer_object,
+                    texture_object,
+                    renderbuffers,
+                    multisample_framebuffer,
+                    contents: vec![
+                        0;
+                        size.width as usize
+                            * size.height as usize
+                            * format.bytes_per_pixel()
+                    ],
+                },
+            };
+
+            Ok(EGLBackedSurface {
+                context_id,
+                size: *size,
+                objects,
+                access,
+                format,
+                destroyed: false,
+            })
+        }
+    }
+
+    // Wraps an existing EGL image of the given format in a texture and a framebuffer object. The
+    // surface takes ownership of the image.
+    //
+    // This fails if `GL_OES_EGL_image` is unavailable, in which case the image is leaked, so
+    // callers should check for it before creating the image.
+    pub(crate) fn new_from_egl_image(
+        gl: &Gl,
+        egl_image: EGLImageKHR,
+        context_id: ContextID,
+        context_attributes: &ContextAttributes,
+        size: &Size2D<i32>,
+        access: SurfaceAccess,
+        format: SurfaceFormat,
+    ) -> Result<EGLBackedSurface, Error> {
+        unsafe {
+            let texture_object = bind_egl_image_to_gl_texture(gl, egl_image)?;
+
             // Create the framebuffer, and bind the texture to it.
             let framebuffer_object =
                 gl_utils::create_and_bind_framebuffer(gl, gl::TEXTURE_2D, texture_object);
@@ -147,7 +368,7 @@ impl EGLBackedSurface {
                 gl::FRAMEBUFFER_COMPLETE
             );
 
-            EGLBackedSurface {
+            Ok(EGLBackedSurface {
                 context_id,
                 size: *size,
                 objects: EGLSurfaceObjects::TextureImage {
@@ -155,38 +376,76 @@ impl EGLBackedSurface {
                     framebuffer_object,
                     texture_object,
                     renderbuffers,
+                    multisample_framebuffer: None,
                 },
+                access,
+                format,
                 destroyed: false,
-            }
+            })
         }
     }
 
+    /// Creates a window surface. `native_window` is the pointer that
+    /// `eglCreatePlatformWindowSurface()` expects for the given platform.
+    #[allow(non_snake_case)]
     pub(crate) fn new_window(
         egl_display: EGLDisplay,
         egl_config: EGLConfig,
+        platform: EGLenum,
         native_window: *mut c_void,
         context_id: ContextID,
         size: &Size2D<i32>,
-    ) -> EGLBackedSurface {
+    ) -> Result<EGLBackedSurface, Error> {
         EGL_FUNCTIONS.with(|egl| unsafe {
-            let window_surface_attribs = [egl::NONE as EGLAttrib];
-            let egl_surface = egl.CreatePlatformWindowSurface(
-                egl_display,
-                egl_config,
-                native_window,
-                window_surface_attribs.as_ptr(),
-            );
-            assert_ne!(egl_surface, egl::NO_SURFACE);
+            let egl_surface = match device::platform_path(egl_display) {
+                EGLPlatformPath::Core => {
+                    let window_surface_attribs = [egl::NONE as EGLAttrib];
+                    egl.CreatePlatformWindowSurface(
+                        egl_display,
+                        egl_config,
+                        native_window,
+                        window_surface_attribs.as_ptr(),
+                    )
+                }
+                EGLPlatformPath::Extension => {
+                    let eglCreatePlatformWindowSurfaceEXT = EGL_EXTENSION_FUNCTIONS
+                        .CreatePlatformWindowSurfaceEXT
+                        .ok_or(Error::RequiredExtensionUnavailable)?;
+                    let window_surface_attribs = [egl::NONE as EGLint];
+                    eglCreatePlatformWindowSurfaceEXT(
+                        egl_display,
+                        egl_config,
+                        native_window,
+                        window_surface_attribs.as_ptr(),
+                    )
+                }
+                EGLPlatformPath::Legacy => {
+                    // The platform functions take a pointer to the X11 `Window`, but
+                    // `eglCreateWindowSurface()` takes the XID itself.
+                    let native_window = if platform == EGL_PLATFORM_X11_KHR {
+                        *(native_window as *const c_ulong) as EGLNativeWindowType
+                    } else {
+                        native_window as EGLNativeWindowType
+                    };
+                    egl.CreateWindowSurface(egl_display, egl_config, native_window, ptr::null())
+                }
+            };
+            if egl_surface == egl::NO_SURFACE {
+                let err = egl.GetError().to_windowing_api_error();
+                return Err(Error::SurfaceCreationFailed(err));
+            }
 
-            EGLBackedSurface {
+            Ok(EGLBackedSurface {
                 context_id,
                 size: *size,
                 objects: EGLSurfaceObjects::Window {
                     native_window,
                     egl_surface,
                 },
+                access: SurfaceAccess::GPUOnly,
+                format: SurfaceFormat::RGBA8,
                 destroyed: false,
-            }
+            })
         })
     }
 
@@ -195,11 +454,18 @@ impl EGLBackedSurface {
         gl: &Gl,
     ) -> Result<EGLSurfaceTexture, (Error, EGLBackedSurface)> {
         unsafe {
-            let egl_image = match self.objects {
-                EGLSurfaceObjects::TextureImage { egl_image, .. } => egl_image,
+            let texture_object = match self.objects {
+                EGLSurfaceObjects::TextureImage { egl_image, .. } => {
+                    match bind_egl_image_to_gl_texture(gl, egl_image) {
+                        Ok(texture_object) => texture_object,
+                        Err(err) => return Err((err, self)),
+                    }
+                }
+                EGLSurfaceObjects::Texture { ref contents, .. } => {
+                    self.upload_to_gl_texture(gl, contents)
+                }
                 EGLSurfaceObjects::Window { .. } => return Err((Error::WidgetAttached, self)),
             };
-            let texture_object = bind_egl_image_to_gl_texture(gl, egl_image);
             Ok(EGLSurfaceTexture {
                 surface: self,
                 texture_object,
@@ -208,6 +474,500 @@ impl EGLBackedSurface {
         }
     }
 
+    // Creates a texture in the current context from a copy of the contents of this surface.
+    unsafe fn upload_to_gl_texture(&self, gl: &Gl, contents: &[u8]) -> GLuint {
+        let (internal_format, pixel_format, pixel_type) = self.format.gl_texture_formats();
+
+        let mut texture_object = 0;
+        gl.GenTextures(1, &mut texture_object);
+
+        let (mut old_texture_object, mut old_unpack_buffer, mut old_unpack_alignment) = (0, 0, 0);
+        gl.GetIntegerv(gl::TEXTURE_BINDING_2D, &mut old_texture_object);
+        gl.GetIntegerv(gl::PIXEL_UNPACK_BUFFER_BINDING, &mut old_unpack_buffer);
+        gl.GetIntegerv(gl::UNPACK_ALIGNMENT, &mut old_unpack_alignment);
+
+        gl.BindTexture(gl::TEXTURE_2D, texture_object);
+        gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+        gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+        gl.TexImage2D(
+            gl::TEXTURE_2D,
+            0,
+            internal_format as GLint,
+            self.size.width,
+            self.size.height,
+            0,
+            pixel_format,
+            pixel_type,
+            contents.as_ptr() as *const c_void,
+        );
+        set_texture_parameters(gl);
+
+        gl.PixelStorei(gl::UNPACK_ALIGNMENT, old_unpack_alignment);
+        gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, old_unpack_buffer as GLuint);
+        gl.BindTexture(gl::TEXTURE_2D, old_texture_object as GLuint);
+        texture_object
+    }
+
+    // Updates the copy of the contents of a surface whose texture isn't wrapped in an EGL image.
+    // The context that the surface belongs to is made current if necessary.
+    //
+    // The contents are read back to the CPU, so this is slow, but it works on any driver.
+    pub(crate) unsafe fn capture_contents(&mut self, gl: &Gl, egl_display: EGLDisplay) {
+        let (_, pixel_format, pixel_type) = self.format.gl_texture_formats();
+        let (egl_context, framebuffer_object, contents) = match self.objects {
+            EGLSurfaceObjects::Texture {
+                egl_context,
+                framebuffer_object,
+                ref mut contents,
+                ..
+            } => (egl_context, framebuffer_object, contents),
+            EGLSurfaceObjects::TextureImage { .. } | EGLSurfaceObjects::Window { .. } => return,
+        };
+
+        let _guard = CurrentContextGuard::new();
+        EGL_FUNCTIONS.with(|egl| {
+            if egl.GetCurrentContext() != egl_context {
+                egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
+            }
+        });
+
+        let (mut old_read_framebuffer, mut old_pack_buffer, mut old_pack_alignment) = (0, 0, 0);
+        gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut old_read_framebuffer);
+        gl.GetIntegerv(gl::PIXEL_PACK_BUFFER_BINDING, &mut old_pack_buffer);
+        gl.GetIntegerv(gl::PACK_ALIGNMENT, &mut old_pack_alignment);
+
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer_object);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl.ReadPixels(
+            0,
+            0,
+            self.size.width,
+            self.size.height,
+            pixel_format,
+            pixel_type,
+            contents.as_mut_ptr() as *mut c_void,
+        );
+
+        gl.PixelStorei(gl::PACK_ALIGNMENT, old_pack_alignment);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, old_pack_buffer as GLuint);
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, old_read_framebuffer as GLuint);
+    }
+
+    #[cfg(unix)]
+    #[allow(non_snake_case)]
+    pub(crate) unsafe fn export_dmabuf(
+        &self,
+        egl_display: EGLDisplay,
+    ) -> Result<DmaBufDescriptor, Error> {
+        let egl_image = match self.objects {
+            EGLSurfaceObjects::TextureImage { egl_image, .. } => egl_image,
+            EGLSurfaceObjects::Texture { .. } => return Err(Error::RequiredExtensionUnavailable),
+            EGLSurfaceObjects::Window { .. } => return Err(Error::WidgetAttached),
+        };
+
+        // `eglGetProcAddress()` may return entry points for extensions that the display doesn't
+        // support, so check the extension string too.
+        let (eglExportDMABUFImageQueryMESA, eglExportDMABUFImageMESA) = match (
+            EGL_EXTENSION_FUNCTIONS.ExportDMABUFImageQueryMESA,
+            EGL_EXTENSION_FUNCTIONS.ExportDMABUFImageMESA,
+        ) {
+            (Some(query), Some(export))
+                if device::has_display_extension(egl_display, "EGL_MESA_image_dma_buf_export") =>
+            {
+                (query, export)
+            }
+            _ => return Err(Error::RequiredExtensionUnavailable),
+        };
+
+        EGL_FUNCTIONS.with(|egl| {
+            // Query the format and the number of planes first, so we know how much to allocate.
+            let (mut fourcc, mut plane_count) = (0, 0);
+            let ok = eglExportDMABUFImageQueryMESA(
+                egl_display,
+                egl_image,
+                &mut fourcc,
+                &mut plane_count,
+                ptr::null_mut(),
+            );
+            if ok == egl::FALSE {
+                let err = egl.GetError().to_windowing_api_error();
+                return Err(Error::SurfaceExportFailed(err));
+            }
+
+            let mut modifiers = vec![DRM_FORMAT_MOD_INVALID; plane_count as usize];
+            let ok = eglExportDMABUFImageQueryMESA(
+                egl_display,
+                egl_image,
+                &mut fourcc,
+                &mut plane_count,
+                modifiers.as_mut_ptr(),
+            );
+            if ok == egl::FALSE {
+                let err = egl.GetError().to_windowing_api_error();
+                return Err(Error::SurfaceExportFailed(err));
+            }
+
+            let plane_count = plane_count as usize;
+            let mut fds = vec![-1; plane_count];
+            let mut strides = vec![0; plane_count];
+            let mut offsets = vec![0; plane_count];
+            let ok = eglExportDMABUFImageMESA(
+                egl_display,
+                egl_image,
+                fds.as_mut_ptr(),
+                strides.as_mut_ptr(),
+                offsets.as_mut_ptr(),
+            );
+            if ok == egl::FALSE {
+                let err = egl.GetError().to_windowing_api_error();
+                return Err(Error::SurfaceExportFailed(err));
+            }
+
+            // Take ownership of every file descriptor before doing anything that can fail, so
+            // that none of them leak if a later plane turns out to be unusable.
+            let fds: Vec<Option<OwnedFd>> = fds
+                .into_iter()
+                .map(|fd| {
+                    if fd < 0 {
+                        None
+                    } else {
+                        Some(OwnedFd::from_raw_fd(fd))
+                    }
+                })
+                .collect();
+
+            let mut planes: Vec<DmaBufPlane> = Vec::with_capacity(plane_count);
+            for ((fd, stride), offset) in fds.into_iter().zip(strides).zip(offsets) {
+                // Planes stored in the same buffer as the previous plane may not get a file
+                // descriptor of their own.
+                let fd = match (fd, planes.last()) {
+                    (Some(fd), _) => fd,
+                    (None, Some(previous_plane)) => previous_plane
+                        .fd
+                        .try_clone()
+                        .map_err(|_| Error::SurfaceExportFailed(WindowingApiError::Failed))?,
+                    (None, None) => {
+                        return Err(Error::SurfaceExportFailed(WindowingApiError::Failed))
+                    }
+                };
+                planes.push(DmaBufPlane {
+                    fd,
+                    offset: offset as u32,
+                    stride: stride as u32,
+                });
+            }
+
+            Ok(DmaBufDescriptor {
+                size: self.size,
+                fourcc: fourcc as u32,
+                modifier: modifiers.first().cloned().unwrap_or(DRM_FORMAT_MOD_INVALID),
+                planes,
+            })
+        })
+    }
+
+    // Maps the contents of a generic surface for CPU access.
+    //
+    // Linear RGBA buffers that can be exported as DMA-BUFs are mapped directly. Otherwise, the
+    // contents are staged through a pixel buffer object, which requires a current context.
+    #[cfg(unix)]
+    pub(crate) unsafe fn lock_data(
+        &mut self,
+        gl: &Gl,
+        egl_display: EGLDisplay,
+    ) -> Result<EGLSurfaceDataGuard<'_>, Error> {
+        if !self.access.cpu_access_allowed() {
+            return Err(Error::SurfaceDataInaccessible);
+        }
+        if self.format != SurfaceFormat::RGBA8 {
+            return Err(Error::UnsupportedSurfaceFormat);
+        }
+        let egl_image = match self.objects {
+            EGLSurfaceObjects::TextureImage { egl_image, .. } => egl_image,
+            EGLSurfaceObjects::Texture {
+                egl_context,
+                texture_object,
+                ref mut contents,
+                ..
+            } => {
+                // The copy of the contents is always up to date while the surface isn't bound to
+                // its context, so it can be handed out directly.
+                return Ok(EGLSurfaceDataGuard {
+                    ptr: contents.as_mut_ptr(),
+                    len: contents.len(),
+                    stride: self.size.width as usize * 4,
+                    mapping: SurfaceDataMapping::Contents {
+                        egl_display,
+                        egl_context,
+                        size: self.size,
+                        texture_object,
+                    },
+                    phantom: PhantomData,
+                });
+            }
+            EGLSurfaceObjects::Window { .. } => return Err(Error::WidgetAttached),
+        };
+
+        // Submit any pending rendering before the CPU touches the data.
+        let egl_context = EGL_FUNCTIONS.with(|egl| egl.GetCurrentContext());
+        if egl_context != egl::NO_CONTEXT {
+            gl.Flush();
+        }
+
+        if let Some(guard) = self.map_dmabuf(egl_display) {
+            return Ok(guard);
+        }
+
+        if egl_context == egl::NO_CONTEXT {
+            return Err(Error::NoCurrentContext);
+        }
+        self.map_pixel_buffer(gl, egl_display, egl_context, egl_image)
+    }
+
+    #[cfg(unix)]
+    unsafe fn map_dmabuf<'a>(&self, egl_display: EGLDisplay) -> Option<EGLSurfaceDataGuard<'a>> {
+        let descriptor = self.export_dmabuf(egl_display).ok()?;
+        if descriptor.planes.len() != 1
+            || descriptor.modifier != DRM_FORMAT_MOD_LINEAR
+            || descriptor.fourcc != DRM_FORMAT_ABGR8888
+        {
+            return None;
+        }
+
+        let plane = descriptor.planes.into_iter().next()?;
+        let stride = plane.stride as usize;
+        let len = stride * self.size.height as usize;
+        let length = plane.offset as usize + len;
+        let address = libc::mmap(
+            ptr::null_mut(),
+            length,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            plane.fd.as_raw_fd(),
+            0,
+        );
+        if address == libc::MAP_FAILED {
+            return None;
+        }
+        sync_dmabuf(&plane.fd, DMA_BUF_SYNC_START);
+
+        Some(EGLSurfaceDataGuard {
+            ptr: (address as *mut u8).add(plane.offset as usize),
+            len,
+            stride,
+            mapping: SurfaceDataMapping::DmaBuf {
+                fd: plane.fd,
+                address,
+                length,
+            },
+            phantom: PhantomData,
+        })
+    }
+
+    #[cfg(unix)]
+    unsafe fn map_pixel_buffer<'a>(
+        &self,
+        gl: &Gl,
+        egl_display: EGLDisplay,
+        egl_context: EGLContext,
+        egl_image: EGLImageKHR,
+    ) -> Result<EGLSurfaceDataGuard<'a>, Error> {
+        let stride = self.size.width as usize * 4;
+        let len = stride * self.size.height as usize;
+        let usage = match self.access {
+            SurfaceAccess::GPUCPUWriteCombined => gl::STREAM_DRAW,
+            SurfaceAccess::GPUOnly | SurfaceAccess::GPUCPU => gl::STREAM_READ,
+        };
+
+        let (mut old_draw_framebuffer, mut old_read_framebuffer) = (0, 0);
+        let (mut old_pack_buffer, mut old_pack_alignment) = (0, 0);
+        gl.GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut old_draw_framebuffer);
+        gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut old_read_framebuffer);
+        gl.GetIntegerv(gl::PIXEL_PACK_BUFFER_BINDING, &mut old_pack_buffer);
+        gl.GetIntegerv(gl::PACK_ALIGNMENT, &mut old_pack_alignment);
+
+        // The surface's own texture belongs to its context, so wrap the image in a new one that
+        // is usable from the current context.
+        let texture_object = bind_egl_image_to_gl_texture(gl, egl_image)?;
+        let framebuffer_object =
+            gl_utils::create_and_bind_framebuffer(gl, gl::TEXTURE_2D, texture_object);
+
+        let mut pixel_buffer_object = 0;
+        gl.GenBuffers(1, &mut pixel_buffer_object);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, pixel_buffer_object);
+        gl.BufferData(gl::PIXEL_PACK_BUFFER, len as GLsizeiptr, ptr::null(), usage);
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
+        gl.ReadPixels(
+            0,
+            0,
+            self.size.width,
+            self.size.height,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            ptr::null_mut(),
+        );
+        let ptr = gl.MapBufferRange(
+            gl::PIXEL_PACK_BUFFER,
+            0,
+            len as GLsizeiptr,
+            gl::MAP_READ_BIT | gl::MAP_WRITE_BIT,
+        ) as *mut u8;
+
+        gl.PixelStorei(gl::PACK_ALIGNMENT, old_pack_alignment);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, old_pack_buffer as GLuint);
+        gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, old_draw_framebuffer as GLuint);
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, old_read_framebuffer as GLuint);
+        gl.DeleteFramebuffers(1, &framebuffer_object);
+
+        if ptr.is_null() {
+            gl.DeleteBuffers(1, &pixel_buffer_object);
+            gl.DeleteTextures(1, &texture_object);
+            return Err(Error::SurfaceLockFailed);
+        }
+
+        Ok(EGLSurfaceDataGuard {
+            ptr,
+            len,
+            stride,
+            mapping: SurfaceDataMapping::PixelBuffer {
+                egl_display,
+                egl_context,
+                size: self.size,
+                texture_object,
+                pixel_buffer_object,
+            },
+            phantom: PhantomData,
+        })
+    }
+
+    // Copies the part of the color contents of another generic surface that fits into this one,
+    // anchored at the origin. Both surfaces must belong to the current context.
+    pub(crate) unsafe fn copy_contents_from(
+        &mut self,
+        gl: &Gl,
+        egl_display: EGLDisplay,
+        other: &EGLBackedSurface,
+    ) {
+        let (dest_framebuffer_object, src_framebuffer_object) =
+            match (self.framebuffer_object(), other.framebuffer_object()) {
+                (Some(dest_framebuffer_object), Some(src_framebuffer_object)) => {
+                    (dest_framebuffer_object, src_framebuffer_object)
+                }
+                _ => return,
+            };
+        let size = self.size.min(other.size);
+        renderbuffers::blit_color(gl, src_framebuffer_object, dest_framebuffer_object, &size);
+
+        // Multisampled contents can only be copied between multisampled framebuffers on OpenGL,
+        // since OpenGL ES doesn't allow multisampled blit destinations. Elsewhere, the
+        // destination starts out with the resolved contents only.
+        if let (Some(dest_framebuffer_object), Some(src_framebuffer_object)) = (
+            self.multisample_framebuffer_object(),
+            other.multisample_framebuffer_object(),
+        ) {
+            let version = CStr::from_ptr(gl.GetString(gl::VERSION) as *const c_char);
+            if !version.to_bytes().starts_with(b"OpenGL ES") {
+                renderbuffers::blit_color(
+                    gl,
+                    src_framebuffer_object,
+                    dest_framebuffer_object,
+                    &size,
+                );
+            }
+        }
+
+        self.capture_contents(gl, egl_display);
+    }
+
+    // Resolves multisampled rendering into the surface's texture. The surface must belong to the
+    // given context, which is made current if necessary.
+    pub(crate) fn resolve(&self, gl: &Gl, egl_display: EGLDisplay, egl_context: EGLContext) {
+        let (framebuffer_object, multisample_framebuffer) = match self.objects {
+            EGLSurfaceObjects::TextureImage {
+                framebuffer_object,
+                multisample_framebuffer: Some(ref multisample_framebuffer),
+                ..
+            }
+            | EGLSurfaceObjects::Texture {
+                framebuffer_object,
+                multisample_framebuffer: Some(ref multisample_framebuffer),
+                ..
+            } => (framebuffer_object, multisample_framebuffer),
+            _ => return,
+        };
+
+        unsafe {
+            let _guard = CurrentContextGuard::new();
+            EGL_FUNCTIONS.with(|egl| {
+                if egl.GetCurrentContext() != egl_context {
+                    egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
+                }
+            });
+            multisample_framebuffer.resolve(gl, framebuffer_object, &self.size);
+        }
+    }
+
+    // Returns the framebuffer object that the texture of this surface is attached to, if any.
+    // Surfaces left over from surface textures of imported buffers have none.
+    fn framebuffer_object(&self) -> Option<GLuint> {
+        match self.objects {
+            EGLSurfaceObjects::TextureImage {
+                framebuffer_object, ..
+            }
+            | EGLSurfaceObjects::Texture {
+                framebuffer_object, ..
+            } if framebuffer_object != 0 => Some(framebuffer_object),
+            _ => None,
+        }
+    }
+
+    // Returns true if this surface can be bound to a context and rendered to.
+    pub(crate) fn is_renderable(&self) -> bool {
+        match self.objects {
+            EGLSurfaceObjects::Window { .. } => true,
+            EGLSurfaceObjects::TextureImage { .. } | EGLSurfaceObjects::Texture { .. } => {
+                self.framebuffer_object().is_some()
+            }
+        }
+    }
+
+    fn multisample_framebuffer_object(&self) -> Option<GLuint> {
+        match self.objects {
+            EGLSurfaceObjects::TextureImage {
+                multisample_framebuffer: Some(ref multisample_framebuffer),
+                ..
+            }
+            | EGLSurfaceObjects::Texture {
+                multisample_framebuffer: Some(ref multisample_framebuffer),
+                ..
+            } => Some(multisample_framebuffer.framebuffer_object),
+            _ => None,
+        }
+    }
+
+    // Returns the framebuffer object that rendering to this surface should target.
+    fn render_framebuffer_object(&self) -> GLuint {
+        match self.objects {
+            EGLSurfaceObjects::TextureImage {
+                framebuffer_object,
+                ref multisample_framebuffer,
+                ..
+            }
+            | EGLSurfaceObjects::Texture {
+                framebuffer_object,
+                ref multisample_framebuffer,
+                ..
+            } => multisample_framebuffer
+                .as_ref()
+                .map_or(framebuffer_object, |multisample_framebuffer| {
+                    multisample_framebuffer.framebuffer_object
+                }),
+            EGLSurfaceObjects::Window { .. } => 0,
+        }
+    }
+
+    #[allow(non_snake_case)]
     pub(crate) fn destroy(
         &mut self,
         gl: &Gl,
@@ -225,13 +985,22 @@ impl EGLBackedSurface {
                     ref mut framebuffer_object,
                     ref mut texture_object,
                     ref mut renderbuffers,
+                    ref mut multisample_framebuffer,
                 } => {
+                    // Check this first, so that nothing is destroyed if it's missing.
+                    let eglDestroyImageKHR = EGL_EXTENSION_FUNCTIONS
+                        .DestroyImageKHR
+                        .ok_or(Error::RequiredExtensionUnavailable)?;
+
                     gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
                     gl.DeleteFramebuffers(1, framebuffer_object);
                     *framebuffer_object = 0;
                     renderbuffers.destroy(gl);
+                    if let Some(mut multisample_framebuffer) = multisample_framebuffer.take() {
+                        multisample_framebuffer.destroy(gl);
+                    }
 
-                    let result = (EGL_EXTENSION_FUNCTIONS.DestroyImageKHR)(egl_display, *egl_image);
+                    let result = eglDestroyImageKHR(egl_display, *egl_image);
                     assert_ne!(result, egl::FALSE);
                     *egl_image = EGL_NO_IMAGE_KHR;
 
@@ -241,6 +1010,27 @@ impl EGLBackedSurface {
                     self.destroyed = true;
                     Ok(None)
                 }
+                EGLSurfaceObjects::Texture {
+                    ref mut framebuffer_object,
+                    ref mut texture_object,
+                    ref mut renderbuffers,
+                    ref mut multisample_framebuffer,
+                    ..
+                } => {
+                    gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+                    gl.DeleteFramebuffers(1, framebuffer_object);
+                    *framebuffer_object = 0;
+                    renderbuffers.destroy(gl);
+                    if let Some(mut multisample_framebuffer) = multisample_framebuffer.take() {
+                        multisample_framebuffer.destroy(gl);
+                    }
+
+                    gl.DeleteTextures(1, texture_object);
+                    *texture_object = 0;
+
+                    self.destroyed = true;
+                    Ok(None)
+                }
                 EGLSurfaceObjects::Window {
                     ref mut egl_surface,
                     ref mut native_window,
@@ -283,7 +1073,9 @@ impl EGLBackedSurface {
                         }
                     })
                 }
-                EGLSurfaceObjects::TextureImage { .. } => Err(Error::NoWidgetAttached),
+                EGLSurfaceObjects::TextureImage { .. } | EGLSurfaceObjects::Texture { .. } => {
+                    Err(Error::NoWidgetAttached)
+                }
             }
         }
     }
@@ -293,25 +1085,24 @@ impl EGLBackedSurface {
             size: self.size,
             id: self.id(),
             context_id: self.context_id,
-            framebuffer_object: match self.objects {
-                EGLSurfaceObjects::TextureImage {
-                    framebuffer_object, ..
-                } => framebuffer_object,
-                EGLSurfaceObjects::Window { .. } => 0,
-            },
+            framebuffer_object: self.render_framebuffer_object(),
+            format: self.format,
         }
     }
 
     pub(crate) fn id(&self) -> SurfaceID {
         match self.objects {
             EGLSurfaceObjects::TextureImage { egl_image, .. } => SurfaceID(egl_image as usize),
+            EGLSurfaceObjects::Texture { id, .. } => SurfaceID(id),
             EGLSurfaceObjects::Window { egl_surface, .. } => SurfaceID(egl_surface as usize),
         }
     }
 
     pub(crate) fn native_window(&self) -> Result<*const c_void, Error> {
         match self.objects {
-            EGLSurfaceObjects::TextureImage { .. } => Err(Error::NoWidgetAttached),
+            EGLSurfaceObjects::TextureImage { .. } | EGLSurfaceObjects::Texture { .. } => {
+                Err(Error::NoWidgetAttached)
+            }
             EGLSurfaceObjects::Window { native_window, .. } => Ok(native_window),
         }
     }
@@ -326,13 +1117,9 @@ impl EGLBackedSurface {
 
                 egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
 
-                match self.objects {
-                    EGLSurfaceObjects::TextureImage {
-                        framebuffer_object, ..
-                    } => {
-                        gl_utils::unbind_framebuffer_if_necessary(gl, framebuffer_object);
-                    }
-                    EGLSurfaceObjects::Window { .. } => {}
+                if let Some(framebuffer_object) = self.framebuffer_object() {
+                    gl_utils::unbind_framebuffer_if_necessary(gl, framebuffer_object);
+                    gl_utils::unbind_framebuffer_if_necessary(gl, self.render_framebuffer_object());
                 }
             })
         }
@@ -344,12 +1131,156 @@ impl EGLBackedSurface {
                 draw: egl_surface,
                 read: egl_surface,
             },
-            EGLSurfaceObjects::TextureImage { .. } => ExternalEGLSurfaces::default(),
+            EGLSurfaceObjects::TextureImage { .. } | EGLSurfaceObjects::Texture { .. } => {
+                ExternalEGLSurfaces::default()
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+impl<'a> EGLSurfaceDataGuard<'a> {
+    #[inline]
+    pub(crate) fn stride(&self) -> usize {
+        self.stride
+    }
+
+    #[inline]
+    pub(crate) fn data(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.ptr, self.len) }
+    }
+
+    // Releases the mapping. If the data was staged through a pixel buffer object, it is copied
+    // back into the surface.
+    pub(crate) unsafe fn unlock(&mut self, gl: &Gl) {
+        match mem::replace(&mut self.mapping, SurfaceDataMapping::Unlocked) {
+            SurfaceDataMapping::DmaBuf {
+                fd,
+                address,
+                length,
+            } => {
+                sync_dmabuf(&fd, DMA_BUF_SYNC_END);
+                libc::munmap(address, length);
+            }
+            SurfaceDataMapping::PixelBuffer {
+                egl_display,
+                egl_context,
+                size,
+                texture_object,
+                pixel_buffer_object,
+            } => {
+                // The buffer object is only valid in the context it was created in.
+                let _guard = CurrentContextGuard::new();
+                EGL_FUNCTIONS.with(|egl| {
+                    if egl.GetCurrentContext() != egl_context {
+                        egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
+                    }
+                });
+
+                let (mut old_texture, mut old_unpack_buffer, mut old_unpack_alignment) = (0, 0, 0);
+                gl.GetIntegerv(gl::TEXTURE_BINDING_2D, &mut old_texture);
+                gl.GetIntegerv(gl::PIXEL_UNPACK_BUFFER_BINDING, &mut old_unpack_buffer);
+                gl.GetIntegerv(gl::UNPACK_ALIGNMENT, &mut old_unpack_alignment);
+
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, pixel_buffer_object);
+                gl.UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+                gl.BindTexture(gl::TEXTURE_2D, texture_object);
+                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+                gl.TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    size.width,
+                    size.height,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    ptr::null(),
+                );
+
+                gl.PixelStorei(gl::UNPACK_ALIGNMENT, old_unpack_alignment);
+                gl.BindTexture(gl::TEXTURE_2D, old_texture as GLuint);
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, old_unpack_buffer as GLuint);
+                gl.DeleteBuffers(1, &pixel_buffer_object);
+                gl.DeleteTextures(1, &texture_object);
+            }
+            SurfaceDataMapping::Contents {
+                egl_display,
+                egl_context,
+                size,
+                texture_object,
+            } => {
+                let _guard = CurrentContextGuard::new();
+                EGL_FUNCTIONS.with(|egl| {
+                    if egl.GetCurrentContext() != egl_context {
+                        egl.MakeCurrent(egl_display, egl::NO_SURFACE, egl::NO_SURFACE, egl_context);
+                    }
+                });
+
+                let (mut old_texture, mut old_unpack_buffer, mut old_unpack_alignment) = (0, 0, 0);
+                gl.GetIntegerv(gl::TEXTURE_BINDING_2D, &mut old_texture);
+                gl.GetIntegerv(gl::PIXEL_UNPACK_BUFFER_BINDING, &mut old_unpack_buffer);
+                gl.GetIntegerv(gl::UNPACK_ALIGNMENT, &mut old_unpack_alignment);
+
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+                gl.BindTexture(gl::TEXTURE_2D, texture_object);
+                gl.PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+                gl.TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    size.width,
+                    size.height,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    self.ptr as *const c_void,
+                );
+
+                gl.PixelStorei(gl::UNPACK_ALIGNMENT, old_unpack_alignment);
+                gl.BindTexture(gl::TEXTURE_2D, old_texture as GLuint);
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, old_unpack_buffer as GLuint);
+            }
+            SurfaceDataMapping::Unlocked => {}
         }
     }
 }
 
 impl EGLSurfaceTexture {
+    // Wraps an existing EGL image in a texture, without creating a framebuffer to render to it.
+    // The surface that destroying this surface texture returns only owns the image, and binding
+    // it to a context fails with `SurfaceNotRenderable`.
+    pub(crate) unsafe fn from_egl_image(
+        gl: &Gl,
+        egl_image: EGLImageKHR,
+        context_id: ContextID,
+        size: &Size2D<i32>,
+        format: SurfaceFormat,
+    ) -> Result<EGLSurfaceTexture, Error> {
+        let texture_object = bind_egl_image_to_gl_texture(gl, egl_image)?;
+        Ok(EGLSurfaceTexture {
+            surface: EGLBackedSurface {
+                context_id,
+                size: *size,
+                objects: EGLSurfaceObjects::TextureImage {
+                    egl_image,
+                    framebuffer_object: 0,
+                    texture_object: 0,
+                    renderbuffers: Renderbuffers::IndividualDepthStencil {
+                        depth: 0,
+                        stencil: 0,
+                    },
+                    multisample_framebuffer: None,
+                },
+                access: SurfaceAccess::GPUOnly,
+                format,
+                destroyed: false,
+            },
+            texture_object,
+            phantom: PhantomData,
+        })
+    }
+
     pub(crate) fn destroy(mut self, gl: &Gl) -> EGLBackedSurface {
         unsafe {
             gl.DeleteTextures(1, &self.texture_object);
@@ -397,8 +1328,15 @@ pub(crate) unsafe fn create_pbuffer_surface(
     })
 }
 
-#[allow(dead_code)]
-pub(crate) unsafe fn bind_egl_image_to_gl_texture(gl: &Gl, egl_image: EGLImageKHR) -> GLuint {
+#[allow(dead_code, non_snake_case)]
+pub(crate) unsafe fn bind_egl_image_to_gl_texture(
+    gl: &Gl,
+    egl_image: EGLImageKHR,
+) -> Result<GLuint, Error> {
+    let glEGLImageTargetTexture2DOES = EGL_EXTENSION_FUNCTIONS
+        .ImageTargetTexture2DOES
+        .ok_or(Error::RequiredExtensionUnavailable)?;
+
     let mut texture = 0;
     gl.GenTextures(1, &mut texture);
     debug_assert_ne!(texture, 0);
@@ -408,7 +1346,16 @@ pub(crate) unsafe fn bind_egl_image_to_gl_texture(gl: &Gl, egl_image: EGLImageKH
 
     // FIXME(pcwalton): Should this be `GL_TEXTURE_EXTERNAL_OES`?
     gl.BindTexture(gl::TEXTURE_2D, texture);
-    (EGL_EXTENSION_FUNCTIONS.ImageTargetTexture2DOES)(gl::TEXTURE_2D, egl_image);
+    glEGLImageTargetTexture2DOES(gl::TEXTURE_2D, egl_image);
+    set_texture_parameters(gl);
+    gl.BindTexture(gl::TEXTURE_2D, texture_binding as GLuint);
+
+    debug_assert_eq!(gl.GetError(), gl::NO_ERROR);
+    Ok(texture)
+}
+
+// Sets up sampling of the texture bound to `GL_TEXTURE_2D` from surface textures.
+unsafe fn set_texture_parameters(gl: &Gl) {
     gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
     gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
     gl.TexParameteri(
@@ -421,8 +1368,140 @@ pub(crate) unsafe fn bind_egl_image_to_gl_texture(gl: &Gl, egl_image: EGLImageKH
         gl::TEXTURE_WRAP_T,
         gl::CLAMP_TO_EDGE as GLint,
     );
-    gl.BindTexture(gl::TEXTURE_2D, texture_binding as GLuint);
+}
 
-    debug_assert_eq!(gl.GetError(), gl::NO_ERROR);
-    texture
+// Imports the given DMA-BUF buffer as an EGL image, via `EGL_EXT_image_dma_buf_import` and, if
+// the buffer has an explicit modifier, `EGL_EXT_image_dma_buf_import_modifiers`.
+//
+// EGL doesn't take ownership of the file descriptors, so the caller may close them afterward.
+#[cfg(unix)]
+#[allow(non_snake_case)]
+pub(crate) unsafe fn create_dmabuf_egl_image(
+    egl_display: EGLDisplay,
+    descriptor: &DmaBufDescriptor,
+) -> Result<EGLImageKHR, Error> {
+    const PLANE_ATTRIBUTES: [[EGLenum; 5]; 4] = [
+        [
+            EGL_DMA_BUF_PLANE0_FD_EXT,
+            EGL_DMA_BUF_PLANE0_OFFSET_EXT,
+            EGL_DMA_BUF_PLANE0_PITCH_EXT,
+            EGL_DMA_BUF_PLANE0_MODIFIER_LO_EXT,
+            EGL_DMA_BUF_PLANE0_MODIFIER_HI_EXT,
+        ],
+        [
+            EGL_DMA_BUF_PLANE1_FD_EXT,
+            EGL_DMA_BUF_PLANE1_OFFSET_EXT,
+            EGL_DMA_BUF_PLANE1_PITCH_EXT,
+            EGL_DMA_BUF_PLANE1_MODIFIER_LO_EXT,
+            EGL_DMA_BUF_PLANE1_MODIFIER_HI_EXT,
+        ],
+        [
+            EGL_DMA_BUF_PLANE2_FD_EXT,
+            EGL_DMA_BUF_PLANE2_OFFSET_EXT,
+            EGL_DMA_BUF_PLANE2_PITCH_EXT,
+            EGL_DMA_BUF_PLANE2_MODIFIER_LO_EXT,
+            EGL_DMA_BUF_PLANE2_MODIFIER_HI_EXT,
+        ],
+        [
+            EGL_DMA_BUF_PLANE3_FD_EXT,
+            EGL_DMA_BUF_PLANE3_OFFSET_EXT,
+            EGL_DMA_BUF_PLANE3_PITCH_EXT,
+            EGL_DMA_BUF_PLANE3_MODIFIER_LO_EXT,
+            EGL_DMA_BUF_PLANE3_MODIFIER_HI_EXT,
+        ],
+    ];
+
+    // The image is bound to a texture afterward, so the `GL_OES_EGL_image` entry point is
+    // needed too.
+    let eglCreateImageKHR = match (
+        EGL_EXTENSION_FUNCTIONS.CreateImageKHR,
+        EGL_EXTENSION_FUNCTIONS.ImageTargetTexture2DOES,
+    ) {
+        (Some(create_image), Some(_))
+            if device::has_display_extension(egl_display, "EGL_EXT_image_dma_buf_import") =>
+        {
+            create_image
+        }
+        _ => return Err(Error::RequiredExtensionUnavailable),
+    };
+    let has_modifier = descriptor.modifier != DRM_FORMAT_MOD_INVALID;
+    if has_modifier
+        && !device::has_display_extension(egl_display, "EGL_EXT_image_dma_buf_import_modifiers")
+    {
+        return Err(Error::RequiredExtensionUnavailable);
+    }
+    // The fourth plane is only importable with `EGL_EXT_image_dma_buf_import_modifiers`.
+    let max_plane_count = if has_modifier { 4 } else { 3 };
+    if descriptor.planes.is_empty() || descriptor.planes.len() > max_plane_count {
+        return Err(Error::SurfaceImportFailed(WindowingApiError::BadParameter));
+    }
+
+    let mut attributes = vec![
+        egl::WIDTH as EGLint,
+        descriptor.size.width,
+        egl::HEIGHT as EGLint,
+        descriptor.size.height,
+        EGL_LINUX_DRM_FOURCC_EXT as EGLint,
+        descriptor.fourcc as EGLint,
+    ];
+    for (plane, plane_attributes) in descriptor.planes.iter().zip(PLANE_ATTRIBUTES.iter()) {
+        attributes.extend_from_slice(&[
+            plane_attributes[0] as EGLint,
+            plane.fd.as_raw_fd(),
+            plane_attributes[1] as EGLint,
+            plane.offset as EGLint,
+            plane_attributes[2] as EGLint,
+            plane.stride as EGLint,
+        ]);
+        if has_modifier {
+            attributes.extend_from_slice(&[
+                plane_attributes[3] as EGLint,
+                descriptor.modifier as u32 as EGLint,
+                plane_attributes[4] as EGLint,
+                (descriptor.modifier >> 32) as u32 as EGLint,
+            ]);
+        }
+    }
+    attributes.push(egl::NONE as EGLint);
+
+    let egl_image = eglCreateImageKHR(
+        egl_display,
+        egl::NO_CONTEXT,
+        EGL_LINUX_DMA_BUF_EXT,
+        ptr::null_mut(),
+        attributes.as_ptr(),
+    );
+    if egl_image == EGL_NO_IMAGE_KHR {
+        let err = EGL_FUNCTIONS.with(|egl| egl.GetError().to_windowing_api_error());
+        return Err(Error::SurfaceImportFailed(err));
+    }
+    Ok(egl_image)
+}
+
+// Returns the surface format that GL sees when sampling from or rendering to a DMA-BUF buffer of
+// the given DRM format.
+//
+// Channel order and padding don't matter to GL, so e.g. `XRGB8888` buffers are `RGBA8`. Formats
+// without a direct equivalent, such as YUV, are sampled as RGBA and are reported as `RGBA8`.
+#[cfg(unix)]
+pub(crate) fn surface_format_for_drm_fourcc(fourcc: u32) -> SurfaceFormat {
+    match fourcc {
+        DRM_FORMAT_ABGR2101010 | DRM_FORMAT_XBGR2101010 => SurfaceFormat::RGB10A2,
+        DRM_FORMAT_ABGR16161616F | DRM_FORMAT_XBGR16161616F => SurfaceFormat::RGBA16F,
+        DRM_FORMAT_R8 => SurfaceFormat::R8,
+        DRM_FORMAT_GR88 => SurfaceFormat::RG8,
+        _ => SurfaceFormat::RGBA8,
+    }
+}
+
+// Brackets CPU access to a DMA-BUF, so that the kernel can wait for pending GPU work and flush
+// caches as necessary.
+#[cfg(unix)]
+unsafe fn sync_dmabuf(fd: &OwnedFd, flags: u64) {
+    let sync = flags | DMA_BUF_SYNC_READ | DMA_BUF_SYNC_WRITE;
+    while libc::ioctl(fd.as_raw_fd(), DMA_BUF_IOCTL_SYNC as _, &sync) < 0 {
+        if io::Error::last_os_error().raw_os_error() != Some(libc::EINTR) {
+            break;
+        }
+    }
 }
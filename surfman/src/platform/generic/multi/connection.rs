@@ -7,7 +7,7 @@ use super::surface::NativeWidget;
 use crate::connection::Connection as ConnectionInterface;
 use crate::device::Device as DeviceInterface;
 use crate::Error;
-use crate::GLApi;
+use crate::{AdapterInfo, GLApi};
 
 use euclid::default::Size2D;
 
@@ -145,6 +145,19 @@ where
         }
     }
 
+    /// Returns information about the hardware that the given adapter renders with.
+    pub fn adapter_info(&self, adapter: &Adapter<Def, Alt>) -> Result<AdapterInfo, Error> {
+        match (self, adapter) {
+            (&Connection::Default(ref connection), &Adapter::Default(ref adapter)) => {
+                connection.adapter_info(adapter)
+            }
+            (&Connection::Alternate(ref connection), &Adapter::Alternate(ref adapter)) => {
+                connection.adapter_info(adapter)
+            }
+            _ => Err(Error::IncompatibleAdapter),
+        }
+    }
+
     /// Opens the hardware device corresponding to the given adapter.
     ///
     /// Device handles are local to a single thread.
@@ -303,6 +316,11 @@ where
         Connection::create_software_adapter(self)
     }
 
+    #[inline]
+    fn adapter_info(&self, adapter: &Adapter<Def, Alt>) -> Result<AdapterInfo, Error> {
+        Connection::adapter_info(self, adapter)
+    }
+
     #[inline]
     fn create_device(&self, adapter: &Adapter<Def, Alt>) -> Result<Device<Def, Alt>, Error> {
         Connection::create_device(self, adapter)
@@ -5,7 +5,9 @@
 use super::device::Device;
 use super::surface::Surface;
 use crate::device::Device as DeviceInterface;
-use crate::{ContextAttributes, ContextID, Error, SurfaceInfo};
+use crate::{ContextAttributes, ContextID, ContextNegotiation, ContextNegotiationReport};
+use crate::{ContextResetStatus, Error, SurfaceInfo};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
 
 use std::os::raw::c_void;
 
@@ -85,6 +87,75 @@ where
         }
     }
 
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// Context descriptors are local to this device.
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor<Def, Alt>>, Error> {
+        match *self {
+            Device::Default(ref device) => {
+                device
+                    .enumerate_context_descriptors(attributes)
+                    .map(|descriptors| {
+                        descriptors
+                            .into_iter()
+                            .map(ContextDescriptor::Default)
+                            .collect()
+                    })
+            }
+            Device::Alternate(ref device) => {
+                device
+                    .enumerate_context_descriptors(attributes)
+                    .map(|descriptors| {
+                        descriptors
+                            .into_iter()
+                            .map(ContextDescriptor::Alternate)
+                            .collect()
+                    })
+            }
+        }
+    }
+
+    /// Creates a context descriptor with the given attributes, choosing the pixel format that
+    /// ranks highest according to the given preferences.
+    ///
+    /// Context descriptors are local to this device.
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor<Def, Alt>, Error> {
+        match *self {
+            Device::Default(ref device) => device
+                .choose_context_descriptor(attributes, preferences)
+                .map(ContextDescriptor::Default),
+            Device::Alternate(ref device) => device
+                .choose_context_descriptor(attributes, preferences)
+                .map(ContextDescriptor::Alternate),
+        }
+    }
+
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor<Def, Alt>, ContextNegotiationReport), Error> {
+        match *self {
+            Device::Default(ref mut device) => device
+                .negotiate_context_descriptor(negotiation)
+                .map(|(descriptor, report)| (ContextDescriptor::Default(descriptor), report)),
+            Device::Alternate(ref mut device) => device
+                .negotiate_context_descriptor(negotiation)
+                .map(|(descriptor, report)| (ContextDescriptor::Alternate(descriptor), report)),
+        }
+    }
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -214,6 +285,25 @@ where
         }
     }
 
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated.
+    pub fn context_reset_status(
+        &self,
+        context: &Context<Def, Alt>,
+    ) -> Result<ContextResetStatus, Error> {
+        match (self, context) {
+            (&Device::Default(ref device), &Context::Default(ref context)) => {
+                device.context_reset_status(context)
+            }
+            (&Device::Alternate(ref device), &Context::Alternate(ref context)) => {
+                device.context_reset_status(context)
+            }
+            _ => Err(Error::IncompatibleContext),
+        }
+    }
+
     /// Attaches a surface to a context for rendering.
     ///
     /// This function takes ownership of the surface. The surface must have been created with this
@@ -285,6 +375,23 @@ where
         }
     }
 
+    /// Returns the properties of the pixel format that the context descriptor selects.
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor<Def, Alt>,
+    ) -> PixelFormatInfo {
+        match (self, context_descriptor) {
+            (&Device::Default(ref device), &ContextDescriptor::Default(ref context_descriptor)) => {
+                device.context_descriptor_pixel_format(context_descriptor)
+            }
+            (
+                &Device::Alternate(ref device),
+                &ContextDescriptor::Alternate(ref context_descriptor),
+            ) => device.context_descriptor_pixel_format(context_descriptor),
+            _ => panic!("Incompatible context!"),
+        }
+    }
+
     /// Fetches the address of an OpenGL function associated with this context.
     ///
     /// OpenGL functions are local to a context. You should not use OpenGL functions on one context
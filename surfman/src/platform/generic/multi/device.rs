@@ -9,7 +9,12 @@ use crate::connection::Connection as ConnectionInterface;
 use crate::context::ContextAttributes;
 use crate::device::Device as DeviceInterface;
 use crate::gl::types::{GLenum, GLuint};
-use crate::{ContextID, Error, GLApi, SurfaceAccess, SurfaceInfo, SurfaceType};
+use crate::{AdapterInfo, ContextID, ContextResetStatus, Error, GLApi, SurfaceAccess};
+use crate::{ContextNegotiation, ContextNegotiationReport, DebugMessage, DebugMessageFilter};
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::{DmaBufDescriptor, SurfaceHandle};
+use crate::{PixelFormatInfo, PixelFormatPreferences, SurfaceType};
+use crate::{SurfaceFormat, SurfaceInfo};
 use euclid::default::Size2D;
 
 use std::os::raw::c_void;
@@ -107,6 +112,49 @@ where
             Device::Alternate(ref device) => device.gl_api(),
         }
     }
+
+    /// Returns information about the hardware that this device renders with.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        match *self {
+            Device::Default(ref device) => device.adapter_info(),
+            Device::Alternate(ref device) => device.adapter_info(),
+        }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        match *self {
+            Device::Default(ref device) => device.supported_surface_formats(),
+            Device::Alternate(ref device) => device.supported_surface_formats(),
+        }
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    pub fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        match *self {
+            Device::Default(ref device) => device.set_debug_message_callback(callback),
+            Device::Alternate(ref device) => device.set_debug_message_callback(callback),
+        }
+    }
+
+    /// Removes the debug message callback, forwarding messages to the `log` crate again.
+    pub fn clear_debug_message_callback(&self) {
+        match *self {
+            Device::Default(ref device) => device.clear_debug_message_callback(),
+            Device::Alternate(ref device) => device.clear_debug_message_callback(),
+        }
+    }
+
+    /// Selects which debug messages of this device are delivered.
+    pub fn set_debug_message_filter(&self, filter: DebugMessageFilter) {
+        match *self {
+            Device::Default(ref device) => device.set_debug_message_filter(filter),
+            Device::Alternate(ref device) => device.set_debug_message_filter(filter),
+        }
+    }
 }
 
 impl<Def, Alt> DeviceInterface for Device<Def, Alt>
@@ -145,6 +193,34 @@ where
         Device::gl_api(self)
     }
 
+    #[inline]
+    fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        Device::adapter_info(self)
+    }
+
+    #[inline]
+    fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Device::supported_surface_formats(self)
+    }
+
+    #[inline]
+    fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        Device::set_debug_message_callback(self, callback)
+    }
+
+    #[inline]
+    fn clear_debug_message_callback(&self) {
+        Device::clear_debug_message_callback(self)
+    }
+
+    #[inline]
+    fn set_debug_message_filter(&self, filter: DebugMessageFilter) {
+        Device::set_debug_message_filter(self, filter)
+    }
+
     // context.rs
 
     #[inline]
@@ -155,6 +231,31 @@ where
         Device::create_context_descriptor(self, attributes)
     }
 
+    #[inline]
+    fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<Self::ContextDescriptor>, Error> {
+        Device::enumerate_context_descriptors(self, attributes)
+    }
+
+    #[inline]
+    fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<Self::ContextDescriptor, Error> {
+        Device::choose_context_descriptor(self, attributes, preferences)
+    }
+
+    #[inline]
+    fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(Self::ContextDescriptor, ContextNegotiationReport), Error> {
+        Device::negotiate_context_descriptor(self, negotiation)
+    }
+
     #[inline]
     fn create_context(
         &mut self,
@@ -197,6 +298,14 @@ where
         Device::make_no_context_current(self)
     }
 
+    #[inline]
+    fn context_reset_status(
+        &self,
+        context: &Context<Def, Alt>,
+    ) -> Result<ContextResetStatus, Error> {
+        Device::context_reset_status(self, context)
+    }
+
     #[inline]
     fn context_descriptor_attributes(
         &self,
@@ -205,6 +314,14 @@ where
         Device::context_descriptor_attributes(self, context_descriptor)
     }
 
+    #[inline]
+    fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor<Def, Alt>,
+    ) -> PixelFormatInfo {
+        Device::context_descriptor_pixel_format(self, context_descriptor)
+    }
+
     #[inline]
     fn get_proc_address(&self, context: &Context<Def, Alt>, symbol_name: &str) -> *const c_void {
         Device::get_proc_address(self, context, symbol_name)
@@ -261,6 +378,26 @@ where
         Device::create_surface_texture(self, context, surface)
     }
 
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Context<Def, Alt>,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Surface<Def, Alt>, Error> {
+        Device::create_surface_from_dmabuf(self, context, descriptor)
+    }
+
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Context<Def, Alt>,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture<Def, Alt>, Error> {
+        Device::create_surface_texture_from_dmabuf(self, context, descriptor)
+    }
+
     #[inline]
     fn destroy_surface(
         &self,
@@ -279,6 +416,31 @@ where
         Device::destroy_surface_texture(self, context, surface_texture)
     }
 
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn export_surface_dmabuf(
+        &self,
+        surface: &Surface<Def, Alt>,
+    ) -> Result<DmaBufDescriptor, Error> {
+        Device::export_surface_dmabuf(self, surface)
+    }
+
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_handle(&self, surface: &Surface<Def, Alt>) -> Result<SurfaceHandle, Error> {
+        Device::create_surface_handle(self, surface)
+    }
+
+    #[inline]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context<Def, Alt>,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture<Def, Alt>, Error> {
+        Device::create_surface_texture_from_handle(self, context, handle)
+    }
+
     #[inline]
     fn surface_gl_texture_target(&self) -> GLenum {
         Device::surface_gl_texture_target(self)
@@ -303,6 +465,16 @@ where
         Device::resize_surface(self, context, surface, size)
     }
 
+    #[inline]
+    fn resize_surface_preserving_contents(
+        &self,
+        context: &Context<Def, Alt>,
+        surface: &mut Surface<Def, Alt>,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        Device::resize_surface_preserving_contents(self, context, surface, size)
+    }
+
     #[inline]
     fn surface_info(&self, surface: &Surface<Def, Alt>) -> SurfaceInfo {
         Device::surface_info(self, surface)
@@ -7,6 +7,8 @@ use super::device::Device;
 use crate::connection::Connection as ConnectionInterface;
 use crate::device::Device as DeviceInterface;
 use crate::gl::types::{GLenum, GLuint};
+#[cfg(all(unix, not(target_os = "macos")))]
+use crate::{DmaBufDescriptor, SurfaceHandle};
 use crate::{Error, SurfaceAccess, SurfaceInfo, SurfaceType};
 use euclid::default::Size2D;
 
@@ -109,7 +111,7 @@ where
         match (&mut *self, context) {
             (&mut Device::Default(ref mut device), &Context::Default(ref context)) => {
                 let surface_type = match surface_type {
-                    SurfaceType::Generic { size } => SurfaceType::Generic { size },
+                    SurfaceType::Generic { size, format } => SurfaceType::Generic { size, format },
                     SurfaceType::Widget {
                         native_widget: NativeWidget::Default(native_widget),
                     } => SurfaceType::Widget { native_widget },
@@ -123,7 +125,7 @@ where
             }
             (&mut Device::Alternate(ref mut device), &Context::Alternate(ref context)) => {
                 let surface_type = match surface_type {
-                    SurfaceType::Generic { size } => SurfaceType::Generic { size },
+                    SurfaceType::Generic { size, format } => SurfaceType::Generic { size, format },
                     SurfaceType::Widget {
                         native_widget: NativeWidget::Alternate(native_widget),
                     } => SurfaceType::Widget { native_widget },
@@ -299,6 +301,118 @@ where
         }
     }
 
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context<Def, Alt>,
+        surface: &mut Surface<Def, Alt>,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        match (self, context) {
+            (&Device::Default(ref device), &Context::Default(ref context)) => match *surface {
+                Surface::Default(ref mut surface) => {
+                    device.resize_surface_preserving_contents(context, surface, size)
+                }
+                _ => Err(Error::IncompatibleSurface),
+            },
+            (&Device::Alternate(ref device), &Context::Alternate(ref context)) => match *surface {
+                Surface::Alternate(ref mut surface) => {
+                    device.resize_surface_preserving_contents(context, surface, size)
+                }
+                _ => Err(Error::IncompatibleSurface),
+            },
+            _ => Err(Error::IncompatibleContext),
+        }
+    }
+
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Context<Def, Alt>,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Surface<Def, Alt>, Error> {
+        match (&mut *self, context) {
+            (&mut Device::Default(ref mut device), &Context::Default(ref context)) => device
+                .create_surface_from_dmabuf(context, descriptor)
+                .map(Surface::Default),
+            (&mut Device::Alternate(ref mut device), &Context::Alternate(ref context)) => device
+                .create_surface_from_dmabuf(context, descriptor)
+                .map(Surface::Alternate),
+            _ => Err(Error::IncompatibleContext),
+        }
+    }
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Context<Def, Alt>,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture<Def, Alt>, Error> {
+        match (self, &mut *context) {
+            (&Device::Default(ref device), &mut Context::Default(ref mut context)) => device
+                .create_surface_texture_from_dmabuf(context, descriptor)
+                .map(SurfaceTexture::Default),
+            (&Device::Alternate(ref device), &mut Context::Alternate(ref mut context)) => device
+                .create_surface_texture_from_dmabuf(context, descriptor)
+                .map(SurfaceTexture::Alternate),
+            _ => Err(Error::IncompatibleContext),
+        }
+    }
+
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn export_surface_dmabuf(
+        &self,
+        surface: &Surface<Def, Alt>,
+    ) -> Result<DmaBufDescriptor, Error> {
+        match (self, surface) {
+            (&Device::Default(ref device), &Surface::Default(ref surface)) => {
+                device.export_surface_dmabuf(surface)
+            }
+            (&Device::Alternate(ref device), &Surface::Alternate(ref surface)) => {
+                device.export_surface_dmabuf(surface)
+            }
+            _ => Err(Error::IncompatibleSurface),
+        }
+    }
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn create_surface_handle(
+        &self,
+        surface: &Surface<Def, Alt>,
+    ) -> Result<SurfaceHandle, Error> {
+        match (self, surface) {
+            (&Device::Default(ref device), &Surface::Default(ref surface)) => {
+                device.create_surface_handle(surface)
+            }
+            (&Device::Alternate(ref device), &Surface::Alternate(ref surface)) => {
+                device.create_surface_handle(surface)
+            }
+            _ => Err(Error::IncompatibleSurface),
+        }
+    }
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    #[cfg(all(unix, not(target_os = "macos")))]
+    pub fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context<Def, Alt>,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture<Def, Alt>, Error> {
+        match (self, &mut *context) {
+            (&Device::Default(ref device), &mut Context::Default(ref mut context)) => device
+                .create_surface_texture_from_handle(context, handle)
+                .map(SurfaceTexture::Default),
+            (&Device::Alternate(ref device), &mut Context::Alternate(ref mut context)) => device
+                .create_surface_texture_from_handle(context, handle)
+                .map(SurfaceTexture::Alternate),
+            _ => Err(Error::IncompatibleContext),
+        }
+    }
+
     /// Returns the OpenGL texture target needed to read from this surface texture.
     ///
     /// This will be `GL_TEXTURE_2D` or `GL_TEXTURE_RECTANGLE`, depending on platform.
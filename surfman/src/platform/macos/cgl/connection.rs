@@ -10,7 +10,7 @@ use crate::platform::macos::system::connection::Connection as SystemConnection;
 use crate::platform::macos::system::device::NativeDevice;
 use crate::platform::macos::system::surface::NativeWidget;
 use crate::Error;
-use crate::GLApi;
+use crate::{AdapterInfo, GLApi};
 
 use euclid::default::Size2D;
 
@@ -83,6 +83,12 @@ impl Connection {
         self.0.create_software_adapter().map(Adapter)
     }
 
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
+    }
+
     /// Opens the hardware device corresponding to the given adapter.
     ///
     /// Device handles are local to a single thread.
@@ -6,10 +6,13 @@ use super::device::Device;
 use super::error::ToWindowingApiError;
 use super::ffi::{CGLReleaseContext, CGLRetainContext};
 use super::surface::Surface;
-use crate::context::{ContextID, CREATE_CONTEXT_MUTEX};
+use crate::context::{current_context_reset_status, ContextID, CREATE_CONTEXT_MUTEX};
 use crate::gl_utils;
 use crate::surface::Framebuffer;
-use crate::{ContextAttributeFlags, ContextAttributes, Error, GLVersion, Gl, SurfaceInfo};
+use crate::SurfaceInfo;
+use crate::{AdapterInfo, ContextAttributeFlags, ContextAttributes, Error, GLVersion, Gl};
+use crate::{ContextNegotiation, ContextNegotiationReport, ContextResetStatus};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
 
 use cgl::{kCGLPFAAllowOfflineRenderers, kCGLPFAAlphaSize, kCGLPFADepthSize};
 use cgl::{kCGLPFAOpenGLProfile, kCGLPFAStencilSize};
@@ -143,6 +146,19 @@ impl Device {
             return Err(Error::UnsupportedGLProfile);
         };
 
+        // Robust, debug, forward-compatible, no-error, and configless contexts aren't supported on
+        // this backend.
+        if attributes.flags.intersects(
+            ContextAttributeFlags::ROBUST_ACCESS
+                | ContextAttributeFlags::LOSE_CONTEXT_ON_RESET
+                | ContextAttributeFlags::DEBUG
+                | ContextAttributeFlags::FORWARD_COMPATIBLE
+                | ContextAttributeFlags::NO_ERROR
+                | ContextAttributeFlags::NO_CONFIG,
+        ) {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+
         let profile = if attributes.version.major >= 4 {
             kCGLOGLPVersion_GL4_Core
         } else if attributes.version.major == 3 {
@@ -208,6 +224,43 @@ impl Device {
         }
     }
 
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// CGL chooses a single pixel format for each set of attributes, so this returns the descriptor
+    /// that `create_context_descriptor()` does.
+    #[inline]
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor>, Error> {
+        Ok(vec![self.create_context_descriptor(attributes)?])
+    }
+
+    /// Creates a context descriptor with the given attributes.
+    ///
+    /// There is only one pixel format to choose from, so the preferences are ignored.
+    #[inline]
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        _: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor, Error> {
+        self.create_context_descriptor(attributes)
+    }
+
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    #[inline]
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor, ContextNegotiationReport), Error> {
+        crate::context::negotiate_context_descriptor(self, negotiation)
+    }
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -265,6 +318,37 @@ impl Device {
         Ok(context)
     }
 
+    // Describes the adapter from the OpenGL strings of a temporary legacy context.
+    pub(crate) fn query_adapter_info(&self) -> Result<AdapterInfo, Error> {
+        let descriptor = self.create_context_descriptor(&ContextAttributes::zeroed())?;
+        unsafe {
+            let mut cgl_context = ptr::null_mut();
+            let err = CGLCreateContext(
+                descriptor.cgl_pixel_format,
+                ptr::null_mut(),
+                &mut cgl_context,
+            );
+            if err != kCGLNoError {
+                return Err(Error::ContextCreationFailed(err.to_windowing_api_error()));
+            }
+
+            let result = {
+                let _guard = CurrentContextGuard::new();
+                let err = CGLSetCurrentContext(cgl_context);
+                if err != kCGLNoError {
+                    Err(Error::MakeCurrentFailed(err.to_windowing_api_error()))
+                } else {
+                    Ok(AdapterInfo::from_current_context(&Gl::load_with(
+                        get_proc_address,
+                    )))
+                }
+            };
+
+            CGLReleaseContext(cgl_context);
+            result
+        }
+    }
+
     /// Destroys a context.
     ///
     /// The context must have been created on this device.
@@ -325,6 +409,20 @@ impl Device {
         }
     }
 
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated. If the driver lacks
+    /// the `glGetGraphicsResetStatus()` function, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    /// Apple's OpenGL implementation doesn't provide that function, so expect that error here.
+    ///
+    /// The context is briefly made current in order to query its status.
+    pub fn context_reset_status(&self, context: &Context) -> Result<ContextResetStatus, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        unsafe { current_context_reset_status(get_proc_address) }
+    }
+
     pub(crate) fn temporarily_make_context_current(
         &self,
         context: &Context,
@@ -426,6 +524,7 @@ impl Device {
             return ContextAttributes {
                 flags: attribute_flags,
                 version,
+                ..ContextAttributes::default()
             };
         }
 
@@ -445,6 +544,17 @@ impl Device {
         }
     }
 
+    /// Returns the properties of the pixel format that the context descriptor selects.
+    ///
+    /// Caveats and sample counts aren't reported on this backend.
+    #[inline]
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> PixelFormatInfo {
+        PixelFormatInfo::from_attributes(&self.context_descriptor_attributes(context_descriptor))
+    }
+
     /// Fetches the address of an OpenGL function associated with this context.
     ///
     /// OpenGL functions are local to a context. You should not use OpenGL functions on one context
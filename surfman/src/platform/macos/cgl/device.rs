@@ -4,7 +4,7 @@
 
 use super::connection::Connection;
 use crate::platform::macos::system::device::{Adapter as SystemAdapter, Device as SystemDevice};
-use crate::GLApi;
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
 
 pub use crate::platform::macos::system::device::NativeDevice;
 
@@ -44,4 +44,42 @@ impl Device {
     pub fn gl_api(&self) -> GLApi {
         GLApi::GL
     }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The vendor and renderer are read from a temporary context. The driver and DRM render node
+    /// are never known on this backend.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        self.query_adapter_info()
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Only `RGBA8` is supported on this backend.
+    #[inline]
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Ok(vec![SurfaceFormat::RGBA8])
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// CGL doesn't support debug contexts, so the callback is never called.
+    #[inline]
+    pub fn set_debug_message_callback<F>(&self, _: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+    }
+
+    /// Removes the debug message callback.
+    ///
+    /// CGL doesn't support debug contexts, so this does nothing.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {}
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// CGL doesn't support debug contexts, so this does nothing.
+    #[inline]
+    pub fn set_debug_message_filter(&self, _: DebugMessageFilter) {}
 }
@@ -9,7 +9,8 @@ use crate::gl::types::{GLenum, GLint, GLuint};
 use crate::gl_utils;
 use crate::platform::macos::system::surface::Surface as SystemSurface;
 use crate::renderbuffers::Renderbuffers;
-use crate::{gl, Error, SurfaceAccess, SurfaceID, SurfaceInfo, SurfaceType, WindowingApiError};
+use crate::WindowingApiError;
+use crate::{gl, Error, SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceInfo, SurfaceType};
 
 use core_foundation::base::TCFType;
 use euclid::default::Size2D;
@@ -357,6 +358,19 @@ impl Device {
         })
     }
 
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    ///
+    /// This isn't implemented on macOS yet, so it returns an `Unimplemented` error.
+    #[inline]
+    pub fn resize_surface_preserving_contents(
+        &self,
+        _: &Context,
+        _: &mut Surface,
+        _: Size2D<i32>,
+    ) -> Result<(), Error> {
+        Err(Error::Unimplemented)
+    }
+
     fn temporarily_bind_framebuffer(&self, new_framebuffer: GLuint) -> FramebufferGuard {
         GL_FUNCTIONS.with(|gl| unsafe {
             let (mut current_draw_framebuffer, mut current_read_framebuffer) = (0, 0);
@@ -384,6 +398,7 @@ impl Device {
             id: system_surface_info.id,
             context_id: surface.context_id,
             framebuffer_object: surface.framebuffer_object,
+            format: SurfaceFormat::RGBA8,
         }
     }
 
@@ -6,7 +6,7 @@ use super::device::Device;
 use super::ffi::{kCVPixelFormatType_32BGRA, kIOMapDefaultCache, IOSurfaceLock, IOSurfaceUnlock};
 use super::ffi::{kCVReturnSuccess, kIOMapWriteCombineCache};
 use super::ffi::{IOSurfaceGetAllocSize, IOSurfaceGetBaseAddress, IOSurfaceGetBytesPerRow};
-use crate::{Error, SurfaceAccess, SurfaceID, SurfaceType, SystemSurfaceInfo};
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceType, SystemSurfaceInfo};
 
 use cocoa::appkit::{NSScreen, NSView as NSViewMethods, NSWindow};
 use cocoa::base::{id, YES};
@@ -116,7 +116,11 @@ impl Device {
     ) -> Result<Surface, Error> {
         unsafe {
             let size = match surface_type {
-                SurfaceType::Generic { size } => size,
+                SurfaceType::Generic {
+                    size,
+                    format: SurfaceFormat::RGBA8,
+                } => size,
+                SurfaceType::Generic { .. } => return Err(Error::UnsupportedSurfaceFormat),
                 SurfaceType::Widget { ref native_widget } => {
                     let window: id = msg_send![native_widget.view.0, window];
                     let bounds = window.convertRectToBacking(native_widget.view.0.bounds());
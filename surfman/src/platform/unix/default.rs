@@ -1,18 +1,40 @@
 // surfman/src/platform/unix/default.rs
 //
-//! The default backend for Unix, which dynamically switches between Wayland, X11 and surfaceless.
+//! The default backend for Unix, which dynamically switches between Wayland, X11, surfaceless,
+//! GBM and OSMesa.
+
+use crate::platform::generic::multi::device::Device as MultiDevice;
+#[cfg(gbm)]
+use crate::platform::unix::gbm::device::Device as GBMDevice;
+use crate::platform::unix::generic::device::Device as SurfacelessDevice;
+#[cfg(osmesa)]
+use crate::platform::unix::osmesa::device::Device as OSMesaDevice;
+use crate::platform::unix::wayland::device::Device as WaylandDevice;
+#[cfg(x11)]
+use crate::platform::unix::x11::device::Device as X11Device;
+
+#[cfg(x11)]
+type HWDevice = MultiDevice<WaylandDevice, X11Device>;
+#[cfg(not(x11))]
+type HWDevice = WaylandDevice;
+
+#[cfg(gbm)]
+type HeadlessDevice = MultiDevice<SurfacelessDevice, GBMDevice>;
+#[cfg(not(gbm))]
+type HeadlessDevice = SurfacelessDevice;
+
+#[cfg(osmesa)]
+type SWDevice = MultiDevice<HeadlessDevice, OSMesaDevice>;
+#[cfg(not(osmesa))]
+type SWDevice = HeadlessDevice;
 
 /// Wayland or X11 display server connections.
 pub mod connection {
+    use super::{HWDevice, SWDevice};
     use crate::platform::generic::multi::connection::Connection as MultiConnection;
     use crate::platform::generic::multi::connection::NativeConnection as MultiNativeConnection;
-    use crate::platform::generic::multi::device::Device as MultiDevice;
-    use crate::platform::unix::generic::device::Device as SWDevice;
-    use crate::platform::unix::wayland::device::Device as WaylandDevice;
-    use crate::platform::unix::x11::device::Device as X11Device;
-    type HWDevice = MultiDevice<WaylandDevice, X11Device>;
 
-    /// Either a Wayland or an X11 display server connection.
+    /// A Wayland or X11 display server connection, or a surfaceless, GBM or OSMesa connection.
     pub type Connection = MultiConnection<HWDevice, SWDevice>;
 
     /// Either a Wayland or an X11 native connection
@@ -21,14 +43,10 @@ pub mod connection {
 
 /// OpenGL rendering contexts.
 pub mod context {
+    use super::{HWDevice, SWDevice};
     use crate::platform::generic::multi::context::Context as MultiContext;
     use crate::platform::generic::multi::context::ContextDescriptor as MultiContextDescriptor;
     use crate::platform::generic::multi::context::NativeContext as MultiNativeContext;
-    use crate::platform::generic::multi::device::Device as MultiDevice;
-    use crate::platform::unix::generic::device::Device as SWDevice;
-    use crate::platform::unix::wayland::device::Device as WaylandDevice;
-    use crate::platform::unix::x11::device::Device as X11Device;
-    type HWDevice = MultiDevice<WaylandDevice, X11Device>;
 
     /// Represents an OpenGL rendering context.
     ///
@@ -60,14 +78,10 @@ pub mod context {
 
 /// Thread-local handles to devices.
 pub mod device {
+    use super::{HWDevice, SWDevice};
     use crate::platform::generic::multi::device::Adapter as MultiAdapter;
-    use crate::platform::generic::multi::device::NativeDevice as MultiNativeDevice;
-    use crate::platform::unix::generic::device::Device as SWDevice;
-    use crate::platform::unix::wayland::device::Device as WaylandDevice;
-    use crate::platform::unix::x11::device::Device as X11Device;
-
     use crate::platform::generic::multi::device::Device as MultiDevice;
-    type HWDevice = MultiDevice<WaylandDevice, X11Device>;
+    use crate::platform::generic::multi::device::NativeDevice as MultiNativeDevice;
 
     /// Represents a hardware display adapter that can be used for rendering (including the CPU).
     ///
@@ -86,14 +100,10 @@ pub mod device {
 
 /// Hardware buffers of pixels.
 pub mod surface {
-    use crate::platform::generic::multi::device::Device as MultiDevice;
+    use super::{HWDevice, SWDevice};
     use crate::platform::generic::multi::surface::NativeWidget as MultiNativeWidget;
     use crate::platform::generic::multi::surface::Surface as MultiSurface;
     use crate::platform::generic::multi::surface::SurfaceTexture as MultiSurfaceTexture;
-    use crate::platform::unix::generic::device::Device as SWDevice;
-    use crate::platform::unix::wayland::device::Device as WaylandDevice;
-    use crate::platform::unix::x11::device::Device as X11Device;
-    type HWDevice = MultiDevice<WaylandDevice, X11Device>;
 
     /// A wrapper for a Wayland surface or an X11 `Window`, as appropriate.
     pub type NativeWidget = MultiNativeWidget<HWDevice, SWDevice>;
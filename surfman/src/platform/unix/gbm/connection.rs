@@ -0,0 +1,330 @@
+// surfman/surfman/src/platform/unix/gbm/connection.rs
+//
+//! A connection to a DRM render node via GBM.
+
+use super::device::{Adapter, Device, NativeDevice};
+use super::ffi::{GBMFunctions, GBM_FUNCTIONS};
+use super::surface::NativeWidget;
+use crate::egl::types::EGLDisplay;
+use crate::info::{AdapterInfo, GLApi};
+use crate::platform::generic::egl::device;
+use crate::platform::generic::egl::ffi::EGL_PLATFORM_GBM_KHR;
+use crate::platform::generic::egl::loader;
+use crate::platform::unix::generic::connection as generic_connection;
+use crate::Error;
+
+use euclid::default::Size2D;
+
+use std::fs::{self, File, OpenOptions};
+use std::os::raw::c_void;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+pub use super::ffi::gbm_device;
+
+#[cfg(feature = "sm-winit")]
+use winit::window::Window;
+
+const DRM_DIR: &str = "/dev/dri";
+const RENDER_NODE_PREFIX: &str = "renderD";
+
+/// A connection to a DRM render node.
+///
+/// All devices opened on this connection share its GBM device and EGL display.
+#[derive(Clone)]
+pub struct Connection {
+    pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) gl_api: GLApi,
+}
+
+unsafe impl Send for Connection {}
+
+pub(crate) struct NativeConnectionWrapper {
+    pub(crate) egl_display: EGLDisplay,
+    pub(crate) gbm_device: *mut gbm_device,
+    pub(crate) gbm: &'static GBMFunctions,
+    // This is `None` if the connection was created from a native connection, in which case the
+    // GBM device and EGL display are owned by the caller.
+    render_node: Option<File>,
+}
+
+unsafe impl Send for NativeConnectionWrapper {}
+unsafe impl Sync for NativeConnectionWrapper {}
+
+/// Wrapper for a GBM device and the EGL display created on it.
+#[derive(Clone)]
+pub struct NativeConnection {
+    /// The EGL display associated with the GBM device.
+    ///
+    /// You can obtain this with `eglGetPlatformDisplay(EGL_PLATFORM_GBM_KHR, ...)`.
+    ///
+    /// It is assumed that this EGL display is already initialized, via `eglInitialize()`.
+    pub egl_display: EGLDisplay,
+    /// The GBM device. This must be present; do not pass NULL.
+    pub gbm_device: *mut gbm_device,
+}
+
+impl Drop for NativeConnectionWrapper {
+    fn drop(&mut self) {
+        unsafe {
+            // The EGL display refers to the GBM device, so it must be terminated first.
+            device::release_display(self.egl_display);
+            if self.render_node.is_some() {
+                (self.gbm.gbm_device_destroy)(self.gbm_device);
+            }
+        }
+    }
+}
+
+impl Connection {
+    /// Opens the first DRM render node on this system.
+    ///
+    /// Returns `NoGLLibraryFound` if `libgbm` or the EGL library couldn't be loaded, or
+    /// `ConnectionFailed` if there is no render node that can be opened.
+    pub fn new() -> Result<Connection, Error> {
+        let render_node = render_nodes()
+            .into_iter()
+            .next()
+            .ok_or(Error::ConnectionFailed)?;
+        Connection::from_render_node(render_node)
+    }
+
+    /// Opens the given DRM render node, usually `/dev/dri/renderD128` or similar.
+    pub fn from_render_node<P>(path: P) -> Result<Connection, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let gbm = GBM_FUNCTIONS.as_ref().ok_or(Error::NoGLLibraryFound)?;
+        let render_node = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(path)
+            .map_err(|_| Error::ConnectionFailed)?;
+
+        unsafe {
+            let gbm_device = (gbm.gbm_create_device)(render_node.as_raw_fd());
+            if gbm_device.is_null() {
+                return Err(Error::ConnectionFailed);
+            }
+
+            let egl_display = match generic_connection::create_egl_display(
+                EGL_PLATFORM_GBM_KHR,
+                gbm_device as *mut c_void,
+                &[],
+            ) {
+                Ok(egl_display) => egl_display,
+                Err(err) => {
+                    (gbm.gbm_device_destroy)(gbm_device);
+                    return Err(err);
+                }
+            };
+
+            Ok(Connection {
+                native_connection: Arc::new(NativeConnectionWrapper {
+                    egl_display,
+                    gbm_device,
+                    gbm,
+                    render_node: Some(render_node),
+                }),
+                gl_api: GLApi::GL,
+            })
+        }
+    }
+
+    /// Wraps an existing GBM device and EGL display in a `Connection`.
+    ///
+    /// Neither is retained or destroyed. Therefore, it is the caller's responsibility to ensure
+    /// that they remain alive as long as this `Connection` object is.
+    ///
+    /// Returns `NoGLLibraryFound` if `libgbm` or the EGL library couldn't be loaded.
+    ///
+    /// # Safety
+    ///
+    /// The GBM device and EGL display must be valid, and the EGL display must have been created
+    /// from that GBM device.
+    #[inline]
+    pub unsafe fn from_native_connection(
+        native_connection: NativeConnection,
+    ) -> Result<Connection, Error> {
+        let gbm = GBM_FUNCTIONS.as_ref().ok_or(Error::NoGLLibraryFound)?;
+        loader::egl_library_loaded()?;
+        device::retain_display(native_connection.egl_display);
+        Ok(Connection {
+            native_connection: Arc::new(NativeConnectionWrapper {
+                egl_display: native_connection.egl_display,
+                gbm_device: native_connection.gbm_device,
+                gbm,
+                render_node: None,
+            }),
+            gl_api: GLApi::GL,
+        })
+    }
+
+    /// Returns the underlying native connection.
+    #[inline]
+    pub fn native_connection(&self) -> NativeConnection {
+        NativeConnection {
+            egl_display: self.native_connection.egl_display,
+            gbm_device: self.native_connection.gbm_device,
+        }
+    }
+
+    /// Returns the OpenGL API flavor that this connection supports (OpenGL or OpenGL ES).
+    ///
+    /// This is OpenGL unless another API was selected with `with_gl_api()`.
+    #[inline]
+    pub fn gl_api(&self) -> GLApi {
+        self.gl_api
+    }
+
+    /// Returns a copy of this connection that creates contexts with the given OpenGL API flavor.
+    ///
+    /// Devices opened on the returned connection report this API from `Device::gl_api()` and use
+    /// it for context descriptors created with `Device::create_context_descriptor()`. This makes
+    /// it possible to run OpenGL ES code on desktop drivers as it would run on mobile platforms.
+    #[inline]
+    pub fn with_gl_api(&self, gl_api: GLApi) -> Connection {
+        Connection {
+            gl_api,
+            ..self.clone()
+        }
+    }
+
+    /// Returns the "best" adapter on this system.
+    ///
+    /// This is an alias for `Connection::create_hardware_adapter()`.
+    #[inline]
+    pub fn create_adapter(&self) -> Result<Adapter, Error> {
+        self.create_hardware_adapter()
+    }
+
+    /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
+    ///
+    /// On the GBM backend, this returns the GPU behind the render node of this connection. To
+    /// render on another GPU, open a connection on its render node.
+    #[inline]
+    pub fn create_hardware_adapter(&self) -> Result<Adapter, Error> {
+        Ok(Adapter)
+    }
+
+    /// Returns the "best" adapter on this system, preferring low-power hardware adapters.
+    ///
+    /// On the GBM backend, this returns the GPU behind the render node of this connection.
+    #[inline]
+    pub fn create_low_power_adapter(&self) -> Result<Adapter, Error> {
+        Ok(Adapter)
+    }
+
+    /// Returns the "best" adapter on this system, preferring software adapters.
+    ///
+    /// On the GBM backend, this returns the GPU behind the render node of this connection.
+    #[inline]
+    pub fn create_software_adapter(&self) -> Result<Adapter, Error> {
+        Ok(Adapter)
+    }
+
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
+    }
+
+    /// Opens the hardware device corresponding to the given adapter.
+    ///
+    /// Device handles are local to a single thread.
+    ///
+    /// Surfaces on this backend are buffer objects wrapped in EGL images, so this returns a
+    /// `RequiredExtensionUnavailable` error if the driver can't create EGL images from textures.
+    #[inline]
+    pub fn create_device(&self, adapter: &Adapter) -> Result<Device, Error> {
+        Device::new(self, adapter)
+    }
+
+    /// An alias for `connection.create_device()` with the adapter wrapped in the given native
+    /// device.
+    ///
+    /// # Safety
+    ///
+    /// The native device must wrap a valid EGL display belonging to this connection.
+    #[inline]
+    pub unsafe fn create_device_from_native_device(
+        &self,
+        native_device: NativeDevice,
+    ) -> Result<Device, Error> {
+        Device::new(self, &native_device.adapter)
+    }
+
+    /// Opens the display connection corresponding to the given `winit` window.
+    #[inline]
+    #[cfg(feature = "sm-winit")]
+    pub fn from_winit_window(_: &Window) -> Result<Connection, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+
+    /// Opens the display connection corresponding to the given raw display handle.
+    #[cfg(feature = "sm-raw-window-handle")]
+    pub fn from_raw_display_handle(
+        _: raw_window_handle::RawDisplayHandle,
+    ) -> Result<Connection, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+
+    /// Creates a native widget type from the given `winit` window.
+    ///
+    /// This type can be later used to create surfaces that render to the window.
+    #[inline]
+    #[cfg(feature = "sm-winit")]
+    pub fn create_native_widget_from_winit_window(
+        &self,
+        _: &Window,
+    ) -> Result<NativeWidget, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+
+    /// Creates a native widget describing a GBM surface of the given size.
+    ///
+    /// The pointer is ignored, since the GBM surface is allocated when the widget surface is
+    /// created.
+    ///
+    /// # Safety
+    ///
+    /// This function is safe to call with any pointer; it is `unsafe` only for consistency with
+    /// the other backends.
+    pub unsafe fn create_native_widget_from_ptr(
+        &self,
+        _raw: *mut c_void,
+        size: Size2D<i32>,
+    ) -> NativeWidget {
+        NativeWidget { size }
+    }
+
+    /// Create a native widget type from the given `raw_window_handle::RawWindowHandle`.
+    #[cfg(feature = "sm-raw-window-handle")]
+    #[inline]
+    pub fn create_native_widget_from_rwh(
+        &self,
+        _: raw_window_handle::RawWindowHandle,
+    ) -> Result<NativeWidget, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+}
+
+/// Returns the paths of all DRM render nodes on this system, in order.
+pub fn render_nodes() -> Vec<PathBuf> {
+    let mut render_nodes: Vec<PathBuf> = match fs::read_dir(DRM_DIR) {
+        Ok(entries) => entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with(RENDER_NODE_PREFIX)
+            })
+            .map(|entry| entry.path())
+            .collect(),
+        Err(_) => vec![],
+    };
+    render_nodes.sort();
+    render_nodes
+}
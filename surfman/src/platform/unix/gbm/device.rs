@@ -0,0 +1,133 @@
+// surfman/surfman/src/platform/unix/gbm/device.rs
+//
+//! A wrapper around GBM `EGLDisplay`s.
+
+use super::connection::{Connection, NativeConnectionWrapper};
+use crate::debug::DebugSink;
+use crate::egl::types::EGLDisplay;
+use crate::platform::generic::egl::debug as egl_debug;
+use crate::platform::generic::egl::device;
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
+
+use std::sync::Arc;
+
+/// Represents a hardware display adapter that can be used for rendering (including the CPU).
+///
+/// On GBM, the adapter is the GPU behind the render node that the connection was opened on.
+///
+/// Adapters can be sent between threads. To render with an adapter, open a thread-local `Device`.
+#[derive(Clone, Debug)]
+pub struct Adapter;
+
+/// A thread-local handle to a device.
+///
+/// Devices contain most of the relevant surface management methods.
+pub struct Device {
+    pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) egl_display: EGLDisplay,
+    pub(crate) adapter: Adapter,
+    pub(crate) gl_api: GLApi,
+    pub(crate) debug_sink: Arc<DebugSink>,
+}
+
+/// Wraps an adapter.
+///
+/// On GBM, devices and adapters are essentially identical types.
+#[derive(Clone)]
+pub struct NativeDevice {
+    /// The hardware adapter corresponding to this device.
+    pub adapter: Adapter,
+}
+
+impl Device {
+    #[inline]
+    pub(crate) fn new(connection: &Connection, adapter: &Adapter) -> Result<Device, Error> {
+        // Buffer objects are only usable as EGL images, so there is nothing to fall back to.
+        if !device::supports_texture_images(connection.native_connection.egl_display) {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+
+        Ok(Device {
+            native_connection: connection.native_connection.clone(),
+            egl_display: connection.native_connection.egl_display,
+            adapter: (*adapter).clone(),
+            gl_api: connection.gl_api,
+            debug_sink: DebugSink::new(),
+        })
+    }
+
+    /// Returns the native device corresponding to this device.
+    ///
+    /// This method is essentially an alias for the `adapter()` method on GBM, since there is
+    /// no explicit concept of a device on this backend.
+    #[inline]
+    pub fn native_device(&self) -> NativeDevice {
+        NativeDevice {
+            adapter: self.adapter(),
+        }
+    }
+
+    /// Returns the display server connection that this device was created with.
+    #[inline]
+    pub fn connection(&self) -> Connection {
+        Connection {
+            native_connection: self.native_connection.clone(),
+            gl_api: self.gl_api,
+        }
+    }
+
+    /// Returns the adapter that this device was created with.
+    #[inline]
+    pub fn adapter(&self) -> Adapter {
+        self.adapter.clone()
+    }
+
+    /// Returns the OpenGL API flavor that this device supports (OpenGL or OpenGL ES).
+    ///
+    /// This is inherited from the connection that the device was opened on.
+    #[inline]
+    pub fn gl_api(&self) -> GLApi {
+        self.gl_api
+    }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The GL strings are read from a temporary context.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        unsafe { device::query_adapter_info(self.egl_display, self.gl_api()) }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Each format is tried out in a temporary context. `RGBA8` is always supported.
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        unsafe { device::query_supported_surface_formats(self.egl_display, self.gl_api()) }
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// Messages come from contexts created with the `DEBUG` flag and, if the `EGL_KHR_debug`
+    /// extension is available, from EGL errors on this thread. The callback replaces any previous
+    /// one. Without a callback, messages are forwarded to the `log` crate.
+    pub fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        self.debug_sink.set_callback(Some(Arc::new(callback)));
+        egl_debug::route_egl_debug_messages_to(&self.debug_sink);
+    }
+
+    /// Removes the debug message callback, forwarding messages to the `log` crate again.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {
+        self.debug_sink.set_callback(None);
+    }
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// By default, messages of `Low` severity and above are delivered from all sources.
+    #[inline]
+    pub fn set_debug_message_filter(&self, filter: DebugMessageFilter) {
+        self.debug_sink.set_filter(filter);
+    }
+}
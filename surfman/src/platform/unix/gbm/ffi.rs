@@ -0,0 +1,110 @@
+// surfman/surfman/src/platform/unix/gbm/ffi.rs
+//
+//! FFI-related functionality for the GBM backend.
+//!
+//! `libgbm` is loaded at runtime so that binaries built with this backend still run on systems
+//! without it.
+
+#![allow(non_camel_case_types)]
+
+use libc::{dlopen, dlsym, RTLD_LAZY};
+use std::mem;
+use std::os::raw::{c_char, c_int, c_void};
+
+/// An opaque GBM device, created from a DRM file descriptor.
+pub enum gbm_device {}
+/// An opaque GBM buffer object.
+pub enum gbm_bo {}
+/// An opaque GBM surface, which hands out buffer objects that EGL has finished rendering to.
+pub enum gbm_surface {}
+
+// Little-endian fourcc codes from `drm_fourcc.h`.
+pub const GBM_FORMAT_ARGB8888: u32 = 0x34325241;
+pub const GBM_FORMAT_ABGR8888: u32 = 0x34324241;
+
+pub const GBM_BO_USE_SCANOUT: u32 = 1 << 0;
+pub const GBM_BO_USE_RENDERING: u32 = 1 << 2;
+pub const GBM_BO_USE_LINEAR: u32 = 1 << 4;
+
+type GBMCreateDeviceFn = unsafe extern "C" fn(fd: c_int) -> *mut gbm_device;
+type GBMDeviceDestroyFn = unsafe extern "C" fn(gbm: *mut gbm_device);
+type GBMBoCreateFn = unsafe extern "C" fn(
+    gbm: *mut gbm_device,
+    width: u32,
+    height: u32,
+    format: u32,
+    flags: u32,
+) -> *mut gbm_bo;
+type GBMBoDestroyFn = unsafe extern "C" fn(bo: *mut gbm_bo);
+type GBMSurfaceCreateFn = unsafe extern "C" fn(
+    gbm: *mut gbm_device,
+    width: u32,
+    height: u32,
+    format: u32,
+    flags: u32,
+) -> *mut gbm_surface;
+type GBMSurfaceDestroyFn = unsafe extern "C" fn(surface: *mut gbm_surface);
+type GBMSurfaceLockFrontBufferFn = unsafe extern "C" fn(surface: *mut gbm_surface) -> *mut gbm_bo;
+type GBMSurfaceReleaseBufferFn = unsafe extern "C" fn(surface: *mut gbm_surface, bo: *mut gbm_bo);
+
+pub(crate) struct GBMFunctions {
+    pub(crate) gbm_create_device: GBMCreateDeviceFn,
+    pub(crate) gbm_device_destroy: GBMDeviceDestroyFn,
+    pub(crate) gbm_bo_create: GBMBoCreateFn,
+    pub(crate) gbm_bo_destroy: GBMBoDestroyFn,
+    pub(crate) gbm_surface_create: GBMSurfaceCreateFn,
+    pub(crate) gbm_surface_destroy: GBMSurfaceDestroyFn,
+    pub(crate) gbm_surface_lock_front_buffer: GBMSurfaceLockFrontBufferFn,
+    pub(crate) gbm_surface_release_buffer: GBMSurfaceReleaseBufferFn,
+}
+
+lazy_static! {
+    // This is `None` if `libgbm` couldn't be loaded.
+    pub(crate) static ref GBM_FUNCTIONS: Option<GBMFunctions> = unsafe { GBMFunctions::load() };
+}
+
+impl GBMFunctions {
+    unsafe fn load() -> Option<GBMFunctions> {
+        let library = dlopen(
+            &b"libgbm.so.1\0"[0] as *const u8 as *const c_char,
+            RTLD_LAZY,
+        );
+        if library.is_null() {
+            return None;
+        }
+
+        let get = |symbol_name: &[u8]| -> Option<*mut c_void> {
+            let symbol = dlsym(library, &symbol_name[0] as *const u8 as *const c_char);
+            if symbol.is_null() {
+                None
+            } else {
+                Some(symbol)
+            }
+        };
+
+        Some(GBMFunctions {
+            gbm_create_device: mem::transmute::<*mut c_void, GBMCreateDeviceFn>(get(
+                b"gbm_create_device\0",
+            )?),
+            gbm_device_destroy: mem::transmute::<*mut c_void, GBMDeviceDestroyFn>(get(
+                b"gbm_device_destroy\0",
+            )?),
+            gbm_bo_create: mem::transmute::<*mut c_void, GBMBoCreateFn>(get(b"gbm_bo_create\0")?),
+            gbm_bo_destroy: mem::transmute::<*mut c_void, GBMBoDestroyFn>(get(
+                b"gbm_bo_destroy\0",
+            )?),
+            gbm_surface_create: mem::transmute::<*mut c_void, GBMSurfaceCreateFn>(get(
+                b"gbm_surface_create\0",
+            )?),
+            gbm_surface_destroy: mem::transmute::<*mut c_void, GBMSurfaceDestroyFn>(get(
+                b"gbm_surface_destroy\0",
+            )?),
+            gbm_surface_lock_front_buffer: mem::transmute::<*mut c_void, GBMSurfaceLockFrontBufferFn>(
+                get(b"gbm_surface_lock_front_buffer\0")?,
+            ),
+            gbm_surface_release_buffer: mem::transmute::<*mut c_void, GBMSurfaceReleaseBufferFn>(
+                get(b"gbm_surface_release_buffer\0")?,
+            ),
+        })
+    }
+}
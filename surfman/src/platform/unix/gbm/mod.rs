@@ -0,0 +1,20 @@
+// surfman/surfman/src/platform/unix/gbm/mod.rs
+//
+//! The GBM backend, which renders on a DRM render node without a display server.
+//!
+//! Generic surfaces are GBM buffer objects. Widget surfaces are GBM surfaces, whose buffers can
+//! be scanned out via KMS.
+
+pub mod connection;
+pub mod context;
+pub mod device;
+pub mod surface;
+
+mod ffi;
+
+#[path = "../../../implementation/mod.rs"]
+mod implementation;
+
+#[cfg(test)]
+#[path = "../../../tests.rs"]
+mod tests;
@@ -0,0 +1,629 @@
+// surfman/surfman/src/platform/unix/gbm/surface.rs
+//
+//! Surfaces backed by GBM buffer objects and GBM surfaces.
+
+use super::context::{Context, GL_FUNCTIONS};
+use super::device::Device;
+use super::ffi::{GBM_BO_USE_LINEAR, GBM_BO_USE_RENDERING, GBM_BO_USE_SCANOUT};
+use super::ffi::{GBM_FORMAT_ABGR8888, GBM_FORMAT_ARGB8888};
+use crate::egl;
+use crate::egl::types::EGLint;
+use crate::gl;
+use crate::gl::types::{GLenum, GLuint};
+use crate::platform::generic::egl::context;
+use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+use crate::platform::generic::egl::error::ToWindowingApiError;
+use crate::platform::generic::egl::ffi::{EGLClientBuffer, EGL_EXTENSION_FUNCTIONS};
+use crate::platform::generic::egl::ffi::{
+    EGL_NATIVE_PIXMAP_KHR, EGL_NO_IMAGE_KHR, EGL_PLATFORM_GBM_KHR,
+};
+use crate::platform::generic::egl::surface::{
+    self, EGLBackedSurface, EGLSurfaceDataGuard, EGLSurfaceTexture,
+};
+use crate::WindowingApiError;
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceInfo, SurfaceType};
+
+use euclid::default::Size2D;
+use std::mem;
+use std::os::raw::c_void;
+use std::ptr;
+
+pub use super::ffi::{gbm_bo, gbm_surface};
+pub use crate::platform::generic::egl::handle::SurfaceHandle;
+pub use crate::platform::generic::egl::surface::{DmaBufDescriptor, DmaBufPlane};
+
+const SURFACE_GL_TEXTURE_TARGET: GLenum = gl::TEXTURE_2D;
+
+/// Represents a hardware buffer of pixels that can be rendered to via the CPU or GPU and either
+/// displayed in a native widget or bound to a texture for reading.
+///
+/// Surfaces come in two varieties: generic and widget surfaces. Generic surfaces are GBM buffer
+/// objects, which can be bound to a texture. Widget surfaces are GBM surfaces, whose front buffer
+/// can be locked after presentation (e.g. for scanout via KMS) but which cannot be bound to a
+/// texture.
+///
+/// Surfaces are specific to a given context and cannot be rendered to from any context other than
+/// the one they were created with. However, they can be *read* from any context on any thread (as
+/// long as that context shares the same adapter and connection), by wrapping them in a
+/// `SurfaceTexture`.
+///
+/// Depending on the platform, each surface may be internally double-buffered.
+///
+/// Surfaces must be destroyed with the `destroy_surface()` method, or a panic will occur.
+#[derive(Debug)]
+pub struct Surface(
+    pub(crate) EGLBackedSurface,
+    // The buffer object backing a generic `RGBA8` surface, or null for other generic surfaces,
+    // widget surfaces, and surfaces imported from DMA-BUFs.
+    pub(crate) *mut gbm_bo,
+);
+
+/// Represents an OpenGL texture that wraps a surface.
+///
+/// Reading from the associated OpenGL texture reads from the surface. It is undefined behavior to
+/// write to such a texture (e.g. by binding it to a framebuffer and rendering to that
+/// framebuffer).
+///
+/// Surface textures are local to a context, but that context does not have to be the same context
+/// as that associated with the underlying surface. The texture must be destroyed with the
+/// `destroy_surface_texture()` method, or a panic will occur.
+#[derive(Debug)]
+pub struct SurfaceTexture(
+    // Boxed so that the `(Error, SurfaceTexture)` pairs that some methods return stay small.
+    pub(crate) Box<EGLSurfaceTexture>,
+    pub(crate) *mut gbm_bo,
+);
+
+/// Describes a GBM surface to be allocated when a widget surface is created.
+#[derive(Clone)]
+pub struct NativeWidget {
+    /// The size of the GBM surface.
+    pub size: Size2D<i32>,
+}
+
+unsafe impl Send for Surface {}
+
+impl Device {
+    /// Creates either a generic or a widget surface, depending on the supplied surface type.
+    ///
+    /// Only the given context may ever render to the surface, but generic surfaces can be wrapped
+    /// up in a `SurfaceTexture` for reading by other contexts.
+    pub fn create_surface(
+        &mut self,
+        context: &Context,
+        access: SurfaceAccess,
+        surface_type: SurfaceType<NativeWidget>,
+    ) -> Result<Surface, Error> {
+        match surface_type {
+            SurfaceType::Generic { size, format } => {
+                self.create_generic_surface(context, access, &size, format)
+            }
+            SurfaceType::Widget { native_widget } => unsafe {
+                self.create_window_surface(context, &native_widget.size)
+            },
+        }
+    }
+
+    #[allow(non_snake_case)]
+    fn create_generic_surface(
+        &self,
+        context: &Context,
+        access: SurfaceAccess,
+        size: &Size2D<i32>,
+        format: SurfaceFormat,
+    ) -> Result<Surface, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        let context_descriptor = self.context_descriptor(context);
+        let context_attributes = self.context_descriptor_attributes(&context_descriptor);
+
+        // Other formats are allocated as textures, as on the other backends. They can still be
+        // exported as DMA-BUFs.
+        if format != SurfaceFormat::RGBA8 {
+            return GL_FUNCTIONS.with(|gl| {
+                EGLBackedSurface::new_generic(
+                    gl,
+                    self.egl_display,
+                    context.0.egl_context,
+                    context.0.id,
+                    &context_attributes,
+                    size,
+                    access,
+                    format,
+                    true,
+                )
+                .map(|surface| Surface(surface, ptr::null_mut()))
+            });
+        }
+
+        // Look these up before allocating anything, so that nothing is leaked if they're missing.
+        let (eglCreateImageKHR, eglDestroyImageKHR) = match (
+            EGL_EXTENSION_FUNCTIONS.CreateImageKHR,
+            EGL_EXTENSION_FUNCTIONS.DestroyImageKHR,
+            EGL_EXTENSION_FUNCTIONS.ImageTargetTexture2DOES,
+        ) {
+            (Some(eglCreateImageKHR), Some(eglDestroyImageKHR), Some(_)) => {
+                (eglCreateImageKHR, eglDestroyImageKHR)
+            }
+            _ => return Err(Error::RequiredExtensionUnavailable),
+        };
+
+        unsafe {
+            // Buffers that the CPU can access are allocated linearly and in RGBA order, so that
+            // `lock_surface_data()` can map them directly.
+            let (format, flags) = if access.cpu_access_allowed() {
+                (
+                    GBM_FORMAT_ABGR8888,
+                    GBM_BO_USE_RENDERING | GBM_BO_USE_LINEAR,
+                )
+            } else {
+                (GBM_FORMAT_ARGB8888, GBM_BO_USE_RENDERING)
+            };
+
+            let gbm = self.native_connection.gbm;
+            let gbm_bo = (gbm.gbm_bo_create)(
+                self.native_connection.gbm_device,
+                size.width as u32,
+                size.height as u32,
+                format,
+                flags,
+            );
+            if gbm_bo.is_null() {
+                return Err(Error::SurfaceCreationFailed(WindowingApiError::BadAlloc));
+            }
+
+            // On the GBM platform, buffer objects are native pixmaps.
+            let egl_image_attributes = [egl::NONE as EGLint];
+            let egl_image = eglCreateImageKHR(
+                self.egl_display,
+                egl::NO_CONTEXT,
+                EGL_NATIVE_PIXMAP_KHR,
+                gbm_bo as EGLClientBuffer,
+                egl_image_attributes.as_ptr(),
+            );
+            if egl_image == EGL_NO_IMAGE_KHR {
+                let err = EGL_FUNCTIONS.with(|egl| egl.GetError().to_windowing_api_error());
+                (gbm.gbm_bo_destroy)(gbm_bo);
+                return Err(Error::SurfaceCreationFailed(err));
+            }
+
+            GL_FUNCTIONS.with(|gl| {
+                let surface = EGLBackedSurface::new_from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &context_attributes,
+                    size,
+                    access,
+                    SurfaceFormat::RGBA8,
+                );
+                match surface {
+                    Ok(surface) => Ok(Surface(surface, gbm_bo)),
+                    Err(err) => {
+                        eglDestroyImageKHR(self.egl_display, egl_image);
+                        (gbm.gbm_bo_destroy)(gbm_bo);
+                        Err(err)
+                    }
+                }
+            })
+        }
+    }
+
+    unsafe fn create_window_surface(
+        &self,
+        context: &Context,
+        size: &Size2D<i32>,
+    ) -> Result<Surface, Error> {
+        let context_descriptor = self.context_descriptor(context);
+        let egl_config =
+            context::egl_config_from_id(self.egl_display, context_descriptor.egl_config_id);
+
+        // GBM configs report the format of their buffers as the native visual ID.
+        let format = match context::get_config_attr(
+            self.egl_display,
+            egl_config,
+            egl::NATIVE_VISUAL_ID as EGLint,
+        ) {
+            0 => GBM_FORMAT_ARGB8888,
+            format => format as u32,
+        };
+
+        let gbm_surface = (self.native_connection.gbm.gbm_surface_create)(
+            self.native_connection.gbm_device,
+            size.width as u32,
+            size.height as u32,
+            format,
+            GBM_BO_USE_SCANOUT | GBM_BO_USE_RENDERING,
+        );
+        if gbm_surface.is_null() {
+            return Err(Error::SurfaceCreationFailed(WindowingApiError::BadAlloc));
+        }
+
+        match EGLBackedSurface::new_window(
+            self.egl_display,
+            egl_config,
+            EGL_PLATFORM_GBM_KHR,
+            gbm_surface as *mut c_void,
+            context.0.id,
+            size,
+        ) {
+            Ok(surface) => Ok(Surface(surface, ptr::null_mut())),
+            Err(err) => {
+                (self.native_connection.gbm.gbm_surface_destroy)(gbm_surface);
+                Err(err)
+            }
+        }
+    }
+
+    /// Creates a surface texture from an existing generic surface for use with the given context.
+    ///
+    /// The surface texture is local to the supplied context and takes ownership of the surface.
+    /// Destroying the surface texture allows you to retrieve the surface again.
+    ///
+    /// *The supplied context does not have to be the same context that the surface is associated
+    /// with.* This allows you to render to a surface in one context and sample from that surface
+    /// in another context.
+    ///
+    /// Calling this method on a widget surface returns a `WidgetAttached` error.
+    pub fn create_surface_texture(
+        &self,
+        context: &mut Context,
+        surface: Surface,
+    ) -> Result<SurfaceTexture, (Error, Surface)> {
+        let _guard = match self.temporarily_make_context_current(context) {
+            Ok(guard) => guard,
+            Err(err) => return Err((err, surface)),
+        };
+
+        let Surface(surface, gbm_bo) = surface;
+        GL_FUNCTIONS.with(|gl| match surface.to_surface_texture(gl) {
+            Ok(surface_texture) => Ok(SurfaceTexture(Box::new(surface_texture), gbm_bo)),
+            Err((err, surface)) => Err((err, Surface(surface, gbm_bo))),
+        })
+    }
+
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    ///
+    /// This requires the `EGL_EXT_image_dma_buf_import` extension, as well as
+    /// `EGL_EXT_image_dma_buf_import_modifiers` if the buffer has an explicit modifier. The file
+    /// descriptors in the descriptor are closed once the buffer has been imported.
+    pub fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Surface, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        let context_descriptor = self.context_descriptor(context);
+        let context_attributes = self.context_descriptor_attributes(&context_descriptor);
+        unsafe {
+            let egl_image = surface::create_dmabuf_egl_image(self.egl_display, &descriptor)?;
+            GL_FUNCTIONS.with(|gl| {
+                let surface = EGLBackedSurface::new_from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &context_attributes,
+                    &descriptor.size,
+                    SurfaceAccess::GPUOnly,
+                    surface::surface_format_for_drm_fourcc(descriptor.fourcc),
+                )?;
+                Ok(Surface(surface, ptr::null_mut()))
+            })
+        }
+    }
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    ///
+    /// This is lighter than `create_surface_from_dmabuf()` followed by `create_surface_texture()`,
+    /// as no framebuffer is created. The surface returned by `destroy_surface_texture()` can't be
+    /// rendered to, so binding it to a context returns a `SurfaceNotRenderable` error. It should
+    /// simply be destroyed.
+    ///
+    /// The same extensions as `create_surface_from_dmabuf()` are required.
+    pub fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        unsafe {
+            let egl_image = surface::create_dmabuf_egl_image(self.egl_display, &descriptor)?;
+            GL_FUNCTIONS.with(|gl| {
+                let surface_texture = EGLSurfaceTexture::from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &descriptor.size,
+                    surface::surface_format_for_drm_fourcc(descriptor.fourcc),
+                )?;
+                Ok(SurfaceTexture(Box::new(surface_texture), ptr::null_mut()))
+            })
+        }
+    }
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    ///
+    /// Send the handle over a Unix domain socket with `SurfaceHandle::send()`, and open it in the
+    /// receiving process with `create_surface_texture_from_handle()`. See the `SurfaceHandle`
+    /// documentation for the rules governing the lifetime of the shared buffer.
+    ///
+    /// The same extensions as `export_surface_dmabuf()` are required.
+    #[inline]
+    pub fn create_surface_handle(&self, surface: &Surface) -> Result<SurfaceHandle, Error> {
+        self.export_surface_dmabuf(surface)
+            .map(SurfaceHandle::from_dmabuf)
+    }
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    ///
+    /// The same extensions as `create_surface_texture_from_dmabuf()` are required.
+    #[inline]
+    pub fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture, Error> {
+        self.create_surface_texture_from_dmabuf(context, handle.into_dmabuf())
+    }
+
+    /// Destroys a surface.
+    ///
+    /// The supplied context must be the context the surface is associated with, or this returns
+    /// an `IncompatibleSurface` error.
+    ///
+    /// You must explicitly call this method to dispose of a surface. Otherwise, a panic occurs in
+    /// the `drop` method.
+    pub fn destroy_surface(
+        &self,
+        context: &mut Context,
+        surface: &mut Surface,
+    ) -> Result<(), Error> {
+        GL_FUNCTIONS.with(|gl| {
+            let egl_display = self.egl_display;
+            let gbm = self.native_connection.gbm;
+            unsafe {
+                match surface.0.destroy(gl, egl_display, context.0.id)? {
+                    Some(gbm_surface) => (gbm.gbm_surface_destroy)(gbm_surface as *mut gbm_surface),
+                    // Surfaces imported from DMA-BUFs have no buffer object of their own.
+                    None if !surface.1.is_null() => {
+                        (gbm.gbm_bo_destroy)(surface.1);
+                        surface.1 = ptr::null_mut();
+                    }
+                    None => {}
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Destroys a surface texture and returns the underlying surface.
+    ///
+    /// The supplied context must be the same context the surface texture was created with, or an
+    /// `IncompatibleSurfaceTexture` error is returned.
+    ///
+    /// All surface textures must be explicitly destroyed with this function, or a panic will
+    /// occur.
+    pub fn destroy_surface_texture(
+        &self,
+        context: &mut Context,
+        surface_texture: SurfaceTexture,
+    ) -> Result<Surface, (Error, SurfaceTexture)> {
+        match self.temporarily_make_context_current(context) {
+            Ok(_guard) => GL_FUNCTIONS.with(|gl| {
+                let SurfaceTexture(surface_texture, gbm_bo) = surface_texture;
+                Ok(Surface(surface_texture.destroy(gl), gbm_bo))
+            }),
+            Err(err) => Err((err, surface_texture)),
+        }
+    }
+
+    /// Displays the contents of a widget surface on screen.
+    ///
+    /// Widget surfaces are internally double-buffered, so changes to them don't show up in their
+    /// associated widgets until this method is called. Afterward, the new front buffer can be
+    /// retrieved with `lock_surface_front_buffer()`.
+    ///
+    /// The supplied context must match the context the surface was created with, or an
+    /// `IncompatibleSurface` error is returned.
+    pub fn present_surface(&self, context: &Context, surface: &mut Surface) -> Result<(), Error> {
+        surface.0.present(self.egl_display, context.0.egl_context)
+    }
+
+    /// Locks and returns the buffer object that was most recently presented to a widget surface.
+    ///
+    /// The buffer object can then be scanned out or shared with another process. It must be
+    /// returned with `release_surface_front_buffer()` once it is no longer in use, as a GBM surface
+    /// has a limited number of buffers to render to.
+    ///
+    /// Calling this method on a generic surface returns a `NoWidgetAttached` error.
+    pub fn lock_surface_front_buffer(&self, surface: &Surface) -> Result<*mut gbm_bo, Error> {
+        let gbm_surface = surface.0.native_window()? as *mut gbm_surface;
+        unsafe {
+            let gbm_bo = (self.native_connection.gbm.gbm_surface_lock_front_buffer)(gbm_surface);
+            if gbm_bo.is_null() {
+                return Err(Error::SurfaceLockFailed);
+            }
+            Ok(gbm_bo)
+        }
+    }
+
+    /// Returns a buffer object locked with `lock_surface_front_buffer()` to its widget surface, so
+    /// that it can be rendered to again.
+    ///
+    /// # Safety
+    ///
+    /// The buffer object must have been locked from this surface and not released since.
+    pub unsafe fn release_surface_front_buffer(
+        &self,
+        surface: &Surface,
+        gbm_bo: *mut gbm_bo,
+    ) -> Result<(), Error> {
+        let gbm_surface = surface.0.native_window()? as *mut gbm_surface;
+        (self.native_connection.gbm.gbm_surface_release_buffer)(gbm_surface, gbm_bo);
+        Ok(())
+    }
+
+    /// Resizes a surface.
+    ///
+    /// Generic surfaces are reallocated at the new size, which discards their contents and
+    /// changes their ID. Use `resize_surface_preserving_contents()` to keep the contents.
+    ///
+    /// Widget surfaces get a new GBM surface of the new size. Buffer objects locked from the old
+    /// one with `lock_surface_front_buffer()` must be released first.
+    ///
+    /// The supplied context must be the context the surface is associated with, or this returns
+    /// an `IncompatibleSurface` error.
+    pub fn resize_surface(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.reallocate_surface(context, surface, size, false)
+    }
+
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    ///
+    /// Contents are anchored at the bottom left corner. Only the color contents are preserved;
+    /// depth and stencil buffers are cleared. Widget surfaces are resized as with
+    /// `resize_surface()`.
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.reallocate_surface(context, surface, size, true)
+    }
+
+    fn reallocate_surface(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+        preserve_contents: bool,
+    ) -> Result<(), Error> {
+        if surface.0.context_id != context.0.id {
+            return Err(Error::IncompatibleSurface);
+        }
+
+        let _guard = self.temporarily_make_context_current(context)?;
+
+        // A GBM surface can't be resized, so widget surfaces get a new one, along with a new EGL
+        // window surface.
+        if surface.0.native_window().is_ok() {
+            let mut new_surface = unsafe { self.create_window_surface(context, &size)? };
+            mem::swap(surface, &mut new_surface);
+            return GL_FUNCTIONS.with(|gl| unsafe {
+                let gbm_surface = new_surface.0.destroy(gl, self.egl_display, context.0.id)?;
+                if let Some(gbm_surface) = gbm_surface {
+                    let gbm_surface = gbm_surface as *mut gbm_surface;
+                    (self.native_connection.gbm.gbm_surface_destroy)(gbm_surface);
+                }
+                Ok(())
+            });
+        }
+
+        let mut new_surface =
+            self.create_generic_surface(context, surface.0.access, &size, surface.0.format)?;
+        GL_FUNCTIONS.with(|gl| {
+            if preserve_contents {
+                unsafe {
+                    new_surface
+                        .0
+                        .copy_contents_from(gl, self.egl_display, &surface.0)
+                };
+            }
+            mem::swap(surface, &mut new_surface);
+            unsafe {
+                let window = new_surface.0.destroy(gl, self.egl_display, context.0.id)?;
+                debug_assert!(window.is_none());
+                // Surfaces imported from DMA-BUFs have no buffer object of their own.
+                if !new_surface.1.is_null() {
+                    (self.native_connection.gbm.gbm_bo_destroy)(new_surface.1);
+                    new_surface.1 = ptr::null_mut();
+                }
+            }
+            Ok(())
+        })
+    }
+
+    /// Returns a pointer to the underlying surface data for reading or writing by the CPU.
+    ///
+    /// The surface must have been created with CPU access, or a `SurfaceDataInaccessible` error is
+    /// returned. Only `RGBA8` surfaces can be accessed; other formats return an
+    /// `UnsupportedSurfaceFormat` error. The buffer objects of such surfaces are linear, so their
+    /// memory is mapped directly if the driver can export them as DMA-BUFs. Otherwise, the data
+    /// is copied into a pixel buffer object, which requires a context on this device to be
+    /// current, and copied back when the guard is dropped.
+    pub fn lock_surface_data<'s>(
+        &self,
+        surface: &'s mut Surface,
+    ) -> Result<SurfaceDataGuard<'s>, Error> {
+        GL_FUNCTIONS.with(move |gl| unsafe {
+            let guard = surface.0.lock_data(gl, self.egl_display)?;
+            Ok(SurfaceDataGuard(guard))
+        })
+    }
+
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    ///
+    /// This requires the `EGL_MESA_image_dma_buf_export` extension. The file descriptors refer to
+    /// the memory of the surface itself, so no copy is made. Rendering commands targeting the
+    /// surface should be flushed first, for example by unbinding it from its context.
+    ///
+    /// Calling this method on a widget surface returns a `WidgetAttached` error.
+    pub fn export_surface_dmabuf(&self, surface: &Surface) -> Result<DmaBufDescriptor, Error> {
+        unsafe { surface.0.export_dmabuf(self.egl_display) }
+    }
+
+    /// Returns the OpenGL texture target needed to read from this surface texture.
+    ///
+    /// This will be `GL_TEXTURE_2D` or `GL_TEXTURE_RECTANGLE`, depending on platform.
+    #[inline]
+    pub fn surface_gl_texture_target(&self) -> GLenum {
+        SURFACE_GL_TEXTURE_TARGET
+    }
+
+    /// Returns various information about the surface, including the framebuffer object needed to
+    /// render to this surface.
+    ///
+    /// Before rendering to a surface attached to a context, you must call `glBindFramebuffer()`
+    /// on the framebuffer object returned by this function. This framebuffer object may or not be
+    /// 0, the default framebuffer, depending on platform.
+    pub fn surface_info(&self, surface: &Surface) -> SurfaceInfo {
+        surface.0.info()
+    }
+
+    /// Returns the OpenGL texture object containing the contents of this surface.
+    ///
+    /// It is only legal to read from, not write to, this texture object.
+    #[inline]
+    pub fn surface_texture_object(&self, surface_texture: &SurfaceTexture) -> GLuint {
+        surface_texture.0.texture_object
+    }
+}
+
+/// Represents the CPU view of the pixel data of this surface.
+pub struct SurfaceDataGuard<'a>(EGLSurfaceDataGuard<'a>);
+
+impl<'a> SurfaceDataGuard<'a> {
+    /// Returns the number of bytes per row of the surface.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.0.stride()
+    }
+
+    /// Returns a mutable slice of the pixel data in this surface, in RGBA format.
+    ///
+    /// Rows are stored bottom to top.
+    #[inline]
+    pub fn data(&mut self) -> &mut [u8] {
+        self.0.data()
+    }
+}
+
+impl<'a> Drop for SurfaceDataGuard<'a> {
+    fn drop(&mut self) {
+        GL_FUNCTIONS.with(|gl| unsafe { self.0.unlock(gl) })
+    }
+}
@@ -2,13 +2,14 @@
 //
 //! Represents a connection to a display server.
 
-use super::device::{Adapter, Device, NativeDevice};
+use super::device::{Adapter, Device, EGLDevice, NativeDevice};
 use super::surface::NativeWidget;
 use crate::egl;
-use crate::egl::types::{EGLAttrib, EGLDisplay};
-use crate::info::GLApi;
-use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+use crate::egl::types::{EGLAttrib, EGLDisplay, EGLenum};
+use crate::info::{AdapterInfo, GLApi};
+use crate::platform::generic::egl::device;
 use crate::platform::generic::egl::ffi::EGL_PLATFORM_SURFACELESS_MESA;
+use crate::platform::generic::egl::loader;
 use crate::Error;
 
 use euclid::default::Size2D;
@@ -23,11 +24,15 @@ use winit::window::Window;
 #[derive(Clone)]
 pub struct Connection {
     pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) gl_api: GLApi,
 }
 
-/// Native connections.
-#[derive(Clone)]
-pub struct NativeConnection(Arc<NativeConnectionWrapper>);
+/// An EGL display.
+///
+/// This can be a display that surfman opened, as returned by `Connection::native_connection()`,
+/// or one that was opened by another library.
+#[derive(Clone, Copy)]
+pub struct NativeConnection(pub EGLDisplay);
 
 /// Native connections.
 pub struct NativeConnectionWrapper {
@@ -37,57 +42,106 @@ pub struct NativeConnectionWrapper {
 unsafe impl Send for NativeConnectionWrapper {}
 unsafe impl Sync for NativeConnectionWrapper {}
 
+impl Drop for NativeConnectionWrapper {
+    fn drop(&mut self) {
+        unsafe { device::release_display(self.egl_display) }
+    }
+}
+
 impl Connection {
     /// Opens a surfaceless Mesa display.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded.
     #[inline]
     pub fn new() -> Result<Connection, Error> {
         unsafe {
-            EGL_FUNCTIONS.with(|egl| {
-                let egl_display_attributes = [egl::NONE as EGLAttrib];
-                let egl_display = egl.GetPlatformDisplay(
-                    EGL_PLATFORM_SURFACELESS_MESA,
-                    egl::DEFAULT_DISPLAY as *mut c_void,
-                    egl_display_attributes.as_ptr(),
-                );
-                if egl_display == egl::NO_DISPLAY {
-                    return Err(Error::ConnectionFailed);
-                }
-
-                let (mut egl_major_version, mut egl_minor_version) = (0, 0);
-                let ok =
-                    egl.Initialize(egl_display, &mut egl_major_version, &mut egl_minor_version);
-                if ok == egl::FALSE {
-                    return Err(Error::ConnectionFailed);
-                }
-
-                let native_connection =
-                    NativeConnection(Arc::new(NativeConnectionWrapper { egl_display }));
-
-                Connection::from_native_connection(native_connection)
-            })
+            let egl_display = create_egl_display(
+                EGL_PLATFORM_SURFACELESS_MESA,
+                egl::DEFAULT_DISPLAY as *mut c_void,
+                &[],
+            )?;
+            Ok(Connection::from_egl_display(egl_display))
         }
     }
 
-    /// An alias for `Connection::new()`, present for consistency with other backends.
-    #[inline]
+    /// Wraps an existing EGL display in a connection without taking ownership of it.
+    ///
+    /// The display must be initialized and must remain so as long as the connection and the
+    /// devices created from it are alive. surfman never terminates it, unless it is a display that
+    /// surfman opened itself, in which case the connection keeps it alive.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded.
+    ///
+    /// # Safety
+    ///
+    /// The native connection must be a valid `EGLDisplay`.
     pub unsafe fn from_native_connection(
         native_connection: NativeConnection,
     ) -> Result<Connection, Error> {
-        Ok(Connection {
-            native_connection: native_connection.0,
-        })
+        loader::egl_library_loaded()?;
+        let egl_display = native_connection.0;
+        device::retain_display(egl_display);
+        Ok(Connection::from_egl_display(egl_display))
     }
 
-    /// Returns the underlying native connection.
+    /// Wraps an existing EGL display in a connection, taking ownership of it.
+    ///
+    /// The display is initialized if it isn't already, and it is terminated once the last
+    /// connection and device referring to it is dropped. Other libraries using the display must be
+    /// done with it by then. If the display was opened with `EGL_TRACK_REFERENCES_KHR`, only the
+    /// reference that surfman takes when initializing it is given up.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded, or `ConnectionFailed` if
+    /// the display couldn't be initialized.
+    ///
+    /// # Safety
+    ///
+    /// The native connection must be a valid `EGLDisplay`, and nothing outside surfman may
+    /// terminate it.
+    pub unsafe fn from_owned_native_connection(
+        native_connection: NativeConnection,
+    ) -> Result<Connection, Error> {
+        loader::egl_library_loaded()?;
+        let egl_display = native_connection.0;
+        device::adopt_display(egl_display)?;
+        Ok(Connection::from_egl_display(egl_display))
+    }
+
+    // Takes over a reference to the given display.
+    fn from_egl_display(egl_display: EGLDisplay) -> Connection {
+        Connection {
+            native_connection: Arc::new(NativeConnectionWrapper { egl_display }),
+            gl_api: GLApi::GL,
+        }
+    }
+
+    /// Returns the underlying EGL display.
+    ///
+    /// The display is only guaranteed to stay alive as long as this connection is.
     #[inline]
     pub fn native_connection(&self) -> NativeConnection {
-        NativeConnection(self.native_connection.clone())
+        NativeConnection(self.native_connection.egl_display)
     }
 
     /// Returns the OpenGL API flavor that this connection supports (OpenGL or OpenGL ES).
+    ///
+    /// This is OpenGL unless another API was selected with `with_gl_api()`.
     #[inline]
     pub fn gl_api(&self) -> GLApi {
-        GLApi::GL
+        self.gl_api
+    }
+
+    /// Returns a copy of this connection that creates contexts with the given OpenGL API flavor.
+    ///
+    /// Devices opened on the returned connection report this API from `Device::gl_api()` and use
+    /// it for context descriptors created with `Device::create_context_descriptor()`. This makes
+    /// it possible to run OpenGL ES code on desktop drivers as it would run on mobile platforms.
+    #[inline]
+    pub fn with_gl_api(&self, gl_api: GLApi) -> Connection {
+        Connection {
+            gl_api,
+            ..self.clone()
+        }
     }
 
     /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
@@ -100,7 +154,9 @@ impl Connection {
 
     /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
     ///
-    /// On the OSMesa backend, this returns a software adapter.
+    /// If there are several hardware EGL devices, this heuristically chooses the last one
+    /// enumerated, which is usually the discrete GPU. Use `adapters()` to pick a specific device.
+    /// If EGL devices can't be enumerated, the default device of the surfaceless display is used.
     #[inline]
     pub fn create_hardware_adapter(&self) -> Result<Adapter, Error> {
         Ok(Adapter::hardware())
@@ -108,16 +164,39 @@ impl Connection {
 
     /// Returns the "best" adapter on this system, preferring low-power hardware adapters.
     ///
-    /// On the OSMesa backend, this returns a software adapter.
+    /// If EGL devices can't be enumerated, the default device of the surfaceless display is used.
     #[inline]
     pub fn create_low_power_adapter(&self) -> Result<Adapter, Error> {
         Ok(Adapter::low_power())
     }
 
     /// Returns the "best" adapter on this system, preferring software adapters.
+    ///
+    /// This is the software rasterizer EGL device. Returns an error if EGL devices can't be
+    /// enumerated or none of them is the software rasterizer.
     #[inline]
     pub fn create_software_adapter(&self) -> Result<Adapter, Error> {
-        Ok(Adapter::software())
+        Adapter::software()
+    }
+
+    /// Returns one adapter for each EGL device on this system.
+    ///
+    /// Typically, there is one EGL device per DRM render node, plus one for the software
+    /// rasterizer. Devices opened on these adapters render on the corresponding EGL device.
+    ///
+    /// This requires the `EGL_EXT_device_enumeration` and `EGL_EXT_platform_device` extensions.
+    /// If they are unavailable, a `RequiredExtensionUnavailable` error is returned.
+    pub fn adapters(&self) -> Result<Vec<Adapter>, Error> {
+        Ok(EGLDevice::enumerate()?
+            .into_iter()
+            .map(Adapter::Device)
+            .collect())
+    }
+
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
     }
 
     /// Opens the hardware device corresponding to the given adapter.
@@ -183,3 +262,97 @@ impl Connection {
         Err(Error::IncompatibleNativeWidget)
     }
 }
+
+/// Opens and initializes an EGL display, returning a reference to it that must be given up with
+/// `device::release_display()`.
+pub(crate) unsafe fn create_egl_display(
+    platform: EGLenum,
+    native_display: *mut c_void,
+    egl_display_attributes: &[EGLAttrib],
+) -> Result<EGLDisplay, Error> {
+    loader::egl_library_loaded()?;
+    let egl_display =
+        device::get_platform_display(platform, native_display, egl_display_attributes);
+    if egl_display == egl::NO_DISPLAY {
+        return Err(Error::ConnectionFailed);
+    }
+
+    device::initialize_display(egl_display)?;
+    Ok(egl_display)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::device::{Adapter, EGLDevice};
+    use super::{Connection, NativeConnection};
+    use crate::egl;
+    use crate::egl::types::{EGLDisplay, EGLint};
+    use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+    use crate::platform::generic::egl::ffi::{EGL_EXTENSION_FUNCTIONS, EGL_PLATFORM_DEVICE_EXT};
+
+    use std::os::raw::c_void;
+
+    // Opens and initializes a display of the software EGL device outside surfman, or returns
+    // `None` if there is no such device. No other test uses this display, so tests running in
+    // parallel can't keep it initialized.
+    #[allow(non_snake_case)]
+    unsafe fn open_software_display() -> Option<EGLDisplay> {
+        let egl_device = EGLDevice::enumerate()
+            .ok()?
+            .into_iter()
+            .find(EGLDevice::is_software)?;
+        let eglGetPlatformDisplayEXT = EGL_EXTENSION_FUNCTIONS.GetPlatformDisplayEXT?;
+        let attributes = [egl::NONE as EGLint];
+        let egl_display = eglGetPlatformDisplayEXT(
+            EGL_PLATFORM_DEVICE_EXT,
+            egl_device.0 as *mut c_void,
+            attributes.as_ptr(),
+        );
+        if egl_display == egl::NO_DISPLAY {
+            return None;
+        }
+        EGL_FUNCTIONS.with(|egl| {
+            let (mut major_version, mut minor_version) = (0, 0);
+            let ok = egl.Initialize(egl_display, &mut major_version, &mut minor_version);
+            assert_ne!(ok, egl::FALSE);
+        });
+        Some(egl_display)
+    }
+
+    // Only initialized displays have a version string.
+    unsafe fn is_initialized(egl_display: EGLDisplay) -> bool {
+        EGL_FUNCTIONS.with(|egl| {
+            !egl.QueryString(egl_display, egl::VERSION as EGLint)
+                .is_null()
+        })
+    }
+
+    // Tests that a display wrapped without ownership stays initialized after its connection and
+    // devices are dropped, and that an owned one is terminated once the last of them is dropped.
+    #[test]
+    fn test_owned_and_borrowed_native_connections() {
+        unsafe {
+            let egl_display = match open_software_display() {
+                Some(egl_display) => egl_display,
+                None => return,
+            };
+
+            let connection =
+                Connection::from_native_connection(NativeConnection(egl_display)).unwrap();
+            // The default adapter renders with the connection's own display.
+            let adapter = Adapter::Default;
+            let device = connection.create_device(&adapter).unwrap();
+            drop(connection);
+            drop(device);
+            assert!(is_initialized(egl_display));
+
+            let connection =
+                Connection::from_owned_native_connection(NativeConnection(egl_display)).unwrap();
+            let device = connection.create_device(&adapter).unwrap();
+            drop(connection);
+            assert!(is_initialized(egl_display));
+            drop(device);
+            assert!(!is_initialized(egl_display));
+        }
+    }
+}
@@ -6,14 +6,24 @@ use super::device::Device;
 use super::surface::Surface;
 use crate::context::ContextID;
 use crate::egl;
-use crate::egl::types::EGLint;
+use crate::egl::types::{EGLConfig, EGLint};
 use crate::platform::generic::egl::context::{self, CurrentContextGuard, EGLBackedContext};
-use crate::{ContextAttributes, Error, Gl, SurfaceInfo};
+use crate::{ContextAttributes, ContextResetStatus, Error, GLApi, Gl, SurfaceInfo};
+use crate::{ContextNegotiation, ContextNegotiationReport};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
 
 use std::os::raw::c_void;
 
 pub use crate::platform::generic::egl::context::{ContextDescriptor, NativeContext};
 
+// Surfaces on this backend are pbuffers or textures, so contexts need pbuffer-capable RGB configs.
+const CONFIG_ATTRIBUTES: [EGLint; 4] = [
+    egl::SURFACE_TYPE as EGLint,
+    egl::PBUFFER_BIT as EGLint,
+    egl::COLOR_BUFFER_TYPE as EGLint,
+    egl::RGB_BUFFER as EGLint,
+];
+
 thread_local! {
     #[doc(hidden)]
     pub static GL_FUNCTIONS: Gl = Gl::load_with(context::get_proc_address);
@@ -40,31 +50,81 @@ pub struct Context(pub(crate) EGLBackedContext);
 impl Device {
     /// Creates a context descriptor with the given attributes.
     ///
-    /// Context descriptors are local to this device.
+    /// Context descriptors are local to this device. Contexts created from the descriptor use the
+    /// OpenGL API flavor of this device.
     #[inline]
     pub fn create_context_descriptor(
         &self,
         attributes: &ContextAttributes,
     ) -> Result<ContextDescriptor, Error> {
-        // Set environment variables as appropriate.
-        self.adapter.set_environment_variables();
+        self.create_context_descriptor_with_gl_api(attributes, self.gl_api())
+    }
+
+    /// Creates a context descriptor with the given attributes for the given OpenGL API flavor.
+    ///
+    /// This allows OpenGL and OpenGL ES contexts to be mixed on a single device. The version in
+    /// `attributes` is interpreted according to `gl_api`. OpenGL ES has no compatibility profile,
+    /// so requesting one returns an `UnsupportedGLProfile` error.
+    pub fn create_context_descriptor_with_gl_api(
+        &self,
+        attributes: &ContextAttributes,
+        gl_api: GLApi,
+    ) -> Result<ContextDescriptor, Error> {
+        unsafe { ContextDescriptor::new(self.egl_display, attributes, gl_api, &CONFIG_ATTRIBUTES) }
+    }
+
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// The descriptors are in the order that EGL sorts their configs in, which isn't necessarily
+    /// the order that `create_context_descriptor()` prefers. Context descriptors are local to this
+    /// device.
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor>, Error> {
+        unsafe {
+            ContextDescriptor::enumerate(
+                self.egl_display,
+                attributes,
+                self.gl_api(),
+                &CONFIG_ATTRIBUTES,
+            )
+        }
+    }
 
+    /// Creates a context descriptor with the given attributes, choosing the pixel format that
+    /// ranks highest according to the given preferences.
+    ///
+    /// Context descriptors are local to this device.
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor, Error> {
         unsafe {
-            ContextDescriptor::new(
-                self.native_connection.egl_display,
+            ContextDescriptor::choose(
+                self.egl_display,
                 attributes,
-                &[
-                    egl::SURFACE_TYPE as EGLint,
-                    egl::PBUFFER_BIT as EGLint,
-                    egl::RENDERABLE_TYPE as EGLint,
-                    egl::OPENGL_BIT as EGLint,
-                    egl::COLOR_BUFFER_TYPE as EGLint,
-                    egl::RGB_BUFFER as EGLint,
-                ],
+                self.gl_api(),
+                &CONFIG_ATTRIBUTES,
+                preferences,
             )
         }
     }
 
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    #[inline]
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor, ContextNegotiationReport), Error> {
+        crate::context::negotiate_context_descriptor(self, negotiation)
+    }
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -75,15 +135,16 @@ impl Device {
         descriptor: &ContextDescriptor,
         share_with: Option<&Context>,
     ) -> Result<Context, Error> {
-        unsafe {
+        GL_FUNCTIONS.with(|gl| unsafe {
             EGLBackedContext::new(
-                self.native_connection.egl_display,
+                gl,
+                self.egl_display,
                 descriptor,
                 share_with.map(|ctx| &ctx.0),
-                self.gl_api(),
+                &self.debug_sink,
             )
             .map(Context)
-        }
+        })
     }
 
     /// Wraps an `EGLContext` in a native context and returns it.
@@ -97,10 +158,53 @@ impl Device {
         native_context: NativeContext,
     ) -> Result<Context, Error> {
         Ok(Context(EGLBackedContext::from_native_context(
+            self.egl_display,
             native_context,
         )))
     }
 
+    /// Wraps an `EGLContext` created outside surfman, along with the `EGLConfig` that surfaces
+    /// for it are to be created with, and returns it.
+    ///
+    /// This makes it possible to share surfaces with contexts from other libraries, including
+    /// contexts created without a config via `EGL_KHR_no_config_context`. The config must belong
+    /// to this device's display and support pbuffers and RGB color buffers. If the context was
+    /// created with a config, it must be the same one. If the native context has no read or draw
+    /// surface, surfaces can be bound to the returned context.
+    ///
+    /// As with `create_context_from_native_context()`, the context is not retained, and it is not
+    /// destroyed by `destroy_context()`.
+    ///
+    /// # Safety
+    ///
+    /// The native context must be a valid `EGLContext` created on this device's EGL display, and
+    /// the config must be a valid `EGLConfig` of that display.
+    pub unsafe fn create_context_from_native_context_and_config(
+        &self,
+        native_context: NativeContext,
+        egl_config: EGLConfig,
+    ) -> Result<Context, Error> {
+        let surface_type =
+            context::get_config_attr(self.egl_display, egl_config, egl::SURFACE_TYPE as EGLint);
+        let color_buffer_type = context::get_config_attr(
+            self.egl_display,
+            egl_config,
+            egl::COLOR_BUFFER_TYPE as EGLint,
+        );
+        if surface_type & egl::PBUFFER_BIT as EGLint == 0
+            || color_buffer_type != egl::RGB_BUFFER as EGLint
+        {
+            return Err(Error::IncompatibleNativeContext);
+        }
+
+        EGLBackedContext::from_native_context_and_config(
+            self.egl_display,
+            native_context,
+            egl_config,
+        )
+        .map(Context)
+    }
+
     /// Destroys a context.
     ///
     /// The context must have been created on this device.
@@ -110,7 +214,7 @@ impl Device {
         }
 
         unsafe {
-            context.0.destroy(self.native_connection.egl_display);
+            context.0.destroy(self.egl_display);
             Ok(())
         }
     }
@@ -124,13 +228,7 @@ impl Device {
     /// Returns the descriptor that this context was created with.
     #[inline]
     pub fn context_descriptor(&self, context: &Context) -> ContextDescriptor {
-        GL_FUNCTIONS.with(|gl| unsafe {
-            ContextDescriptor::from_egl_context(
-                gl,
-                self.native_connection.egl_display,
-                context.0.egl_context,
-            )
-        })
+        GL_FUNCTIONS.with(|gl| unsafe { context.0.descriptor(gl, self.egl_display) })
     }
 
     /// Makes the context the current OpenGL context for this thread.
@@ -138,7 +236,7 @@ impl Device {
     /// After calling this function, it is valid to use OpenGL rendering commands.
     #[inline]
     pub fn make_context_current(&self, context: &Context) -> Result<(), Error> {
-        unsafe { context.0.make_current(self.native_connection.egl_display) }
+        unsafe { context.0.make_current(self.egl_display) }
     }
 
     /// Removes the current OpenGL context from this thread.
@@ -147,7 +245,19 @@ impl Device {
     /// made current.
     #[inline]
     pub fn make_no_context_current(&self) -> Result<(), Error> {
-        unsafe { context::make_no_context_current(self.native_connection.egl_display) }
+        unsafe { context::make_no_context_current(self.egl_display) }
+    }
+
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated. If the driver lacks
+    /// the `glGetGraphicsResetStatus()` function, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    ///
+    /// The context is briefly made current in order to query its status.
+    pub fn context_reset_status(&self, context: &Context) -> Result<ContextResetStatus, Error> {
+        unsafe { context.0.reset_status(self.egl_display) }
     }
 
     #[inline]
@@ -166,7 +276,22 @@ impl Device {
         &self,
         context_descriptor: &ContextDescriptor,
     ) -> ContextAttributes {
-        unsafe { context_descriptor.attributes(self.native_connection.egl_display) }
+        unsafe { context_descriptor.attributes(self.egl_display) }
+    }
+
+    /// Returns the properties of the EGL config that the context descriptor selects.
+    #[inline]
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> PixelFormatInfo {
+        unsafe { context_descriptor.pixel_format(self.egl_display) }
+    }
+
+    /// Returns the OpenGL API flavor that contexts created from the given descriptor use.
+    #[inline]
+    pub fn context_descriptor_gl_api(&self, context_descriptor: &ContextDescriptor) -> GLApi {
+        context_descriptor.gl_api
     }
 
     /// Fetches the address of an OpenGL function associated with this context.
@@ -200,7 +325,7 @@ impl Device {
         unsafe {
             context
                 .0
-                .bind_surface(self.native_connection.egl_display, surface.0)
+                .bind_surface(self.egl_display, surface.0)
                 .map_err(|(err, surface)| (err, Surface(surface)))
         }
     }
@@ -216,7 +341,7 @@ impl Device {
         GL_FUNCTIONS.with(|gl| unsafe {
             context
                 .0
-                .unbind_surface(gl, self.native_connection.egl_display)
+                .unbind_surface(gl, self.egl_display)
                 .map(|maybe_surface| maybe_surface.map(Surface))
         })
     }
@@ -238,3 +363,320 @@ impl Device {
         context.0.surface_info()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::connection::Connection;
+    use super::super::device::Device;
+    use super::NativeContext;
+    use crate::egl;
+    use crate::egl::types::{EGLConfig, EGLint};
+    use crate::gl;
+    use crate::platform::generic::egl::context;
+    use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+    use crate::{ContextAttributeFlags, ContextAttributes, Error, GLApi, GLVersion, Gl};
+    use crate::{SurfaceAccess, SurfaceType};
+
+    use euclid::default::Size2D;
+    use std::os::raw::c_void;
+    use std::ptr;
+
+    // Returns a device, or `None` if this machine can't run these tests.
+    fn create_device() -> Option<Device> {
+        let connection = Connection::new().unwrap();
+        let adapter = connection.create_low_power_adapter().unwrap();
+        match connection.create_device(&adapter) {
+            Ok(device) => Some(device),
+            Err(Error::RequiredExtensionUnavailable) => None,
+            Err(err) => panic!("Failed to create device: {:?}", err),
+        }
+    }
+
+    // Returns a pbuffer-capable RGB config of the device with exactly the given alpha and depth
+    // sizes, if there is one.
+    unsafe fn find_config(
+        device: &Device,
+        alpha_size: EGLint,
+        depth_size: EGLint,
+    ) -> Option<EGLConfig> {
+        let renderable_type = match device.gl_api() {
+            GLApi::GL => egl::OPENGL_BIT,
+            GLApi::GLES => egl::OPENGL_ES2_BIT,
+        };
+        let attributes = [
+            egl::SURFACE_TYPE as EGLint,
+            egl::PBUFFER_BIT as EGLint,
+            egl::COLOR_BUFFER_TYPE as EGLint,
+            egl::RGB_BUFFER as EGLint,
+            egl::RENDERABLE_TYPE as EGLint,
+            renderable_type as EGLint,
+            egl::RED_SIZE as EGLint,
+            8,
+            egl::GREEN_SIZE as EGLint,
+            8,
+            egl::BLUE_SIZE as EGLint,
+            8,
+            egl::NONE as EGLint,
+        ];
+
+        EGL_FUNCTIONS.with(|egl| {
+            let mut config_count = 0;
+            let result = egl.ChooseConfig(
+                device.egl_display,
+                attributes.as_ptr(),
+                ptr::null_mut(),
+                0,
+                &mut config_count,
+            );
+            assert_ne!(result, egl::FALSE);
+
+            let mut configs = vec![ptr::null(); config_count as usize];
+            let result = egl.ChooseConfig(
+                device.egl_display,
+                attributes.as_ptr(),
+                configs.as_mut_ptr(),
+                config_count,
+                &mut config_count,
+            );
+            assert_ne!(result, egl::FALSE);
+            configs.truncate(config_count as usize);
+
+            configs.into_iter().find(|&egl_config| {
+                let config_attr =
+                    |attr| context::get_config_attr(device.egl_display, egl_config, attr);
+                config_attr(egl::ALPHA_SIZE as EGLint) == alpha_size
+                    && config_attr(egl::DEPTH_SIZE as EGLint) == depth_size
+            })
+        })
+    }
+
+    // Tests that `NO_ERROR` is rejected alongside the flags that rely on errors being reported.
+    #[test]
+    fn test_no_error_flag_conflicts() {
+        let device = match create_device() {
+            Some(device) => device,
+            None => return,
+        };
+        for &flag in &[
+            ContextAttributeFlags::ROBUST_ACCESS,
+            ContextAttributeFlags::LOSE_CONTEXT_ON_RESET,
+            ContextAttributeFlags::DEBUG,
+        ] {
+            let result = device.create_context_descriptor(&ContextAttributes {
+                version: GLVersion::new(3, 0),
+                flags: ContextAttributeFlags::NO_ERROR | flag,
+                ..ContextAttributes::default()
+            });
+            assert!(matches!(
+                result,
+                Err(Error::IncompatibleContextAttributeFlags)
+            ));
+        }
+    }
+
+    // Tests that querying the descriptor of the current context leaves its pending GL errors for
+    // the application to check.
+    #[test]
+    fn test_context_descriptor_preserves_gl_errors() {
+        let mut device = match create_device() {
+            Some(device) => device,
+            None => return,
+        };
+        let context_descriptor = device
+            .create_context_descriptor(&ContextAttributes {
+                version: GLVersion::new(3, 0),
+                flags: ContextAttributeFlags::empty(),
+                ..ContextAttributes::default()
+            })
+            .unwrap();
+        let mut context = device.create_context(&context_descriptor, None).unwrap();
+        device.make_context_current(&context).unwrap();
+        let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+
+        unsafe {
+            gl.ActiveTexture(0);
+            device.context_descriptor(&context);
+            assert_eq!(gl.GetError(), gl::INVALID_ENUM);
+            assert_eq!(gl.GetError(), gl::NO_ERROR);
+        }
+
+        device.destroy_context(&mut context).unwrap();
+    }
+
+    // Tests that a context created without a config can render into pbuffers of two configs that
+    // differ in their alpha and depth buffers.
+    #[test]
+    fn test_no_config_context_with_two_configs() {
+        let mut device = match create_device() {
+            Some(device) => device,
+            None => return,
+        };
+        let context_descriptor = match device.create_context_descriptor(&ContextAttributes {
+            version: GLVersion::new(3, 0),
+            flags: ContextAttributeFlags::NO_CONFIG,
+            ..ContextAttributes::default()
+        }) {
+            Ok(context_descriptor) => context_descriptor,
+            Err(Error::RequiredExtensionUnavailable) => return,
+            Err(err) => panic!("Context descriptor creation failed: {:?}", err),
+        };
+        let mut context = device.create_context(&context_descriptor, None).unwrap();
+        let egl_context = device.native_context(&context).egl_context;
+        let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+
+        unsafe {
+            let egl_configs = match (find_config(&device, 8, 0), find_config(&device, 0, 24)) {
+                (Some(alpha_config), Some(depth_config)) => [alpha_config, depth_config],
+                _ => {
+                    // This driver doesn't have two such configs.
+                    device.destroy_context(&mut context).unwrap();
+                    return;
+                }
+            };
+
+            for &egl_config in &egl_configs {
+                let egl_surface = EGL_FUNCTIONS.with(|egl| {
+                    let attributes = [
+                        egl::WIDTH as EGLint,
+                        64,
+                        egl::HEIGHT as EGLint,
+                        64,
+                        egl::NONE as EGLint,
+                    ];
+                    egl.CreatePbufferSurface(device.egl_display, egl_config, attributes.as_ptr())
+                });
+                assert_ne!(egl_surface, egl::NO_SURFACE);
+
+                // Bind the pbuffer to the configless context by wrapping the two together.
+                let mut wrapped_context = device
+                    .create_context_from_native_context(NativeContext {
+                        egl_context,
+                        egl_read_surface: egl_surface,
+                        egl_draw_surface: egl_surface,
+                    })
+                    .unwrap();
+                device.make_context_current(&wrapped_context).unwrap();
+
+                let mut pixel = [0u8; 4];
+                gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+                gl.Viewport(0, 0, 64, 64);
+                gl.ClearColor(0.0, 1.0, 0.0, 1.0);
+                gl.Clear(gl::COLOR_BUFFER_BIT);
+                gl.ReadPixels(
+                    0,
+                    0,
+                    1,
+                    1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    pixel.as_mut_ptr() as *mut c_void,
+                );
+                assert_eq!(gl.GetError(), gl::NO_ERROR);
+                assert_eq!(pixel, [0, 255, 0, 255]);
+
+                device.make_no_context_current().unwrap();
+                device.destroy_context(&mut wrapped_context).unwrap();
+                EGL_FUNCTIONS.with(|egl| {
+                    let result = egl.DestroySurface(device.egl_display, egl_surface);
+                    assert_ne!(result, egl::FALSE);
+                });
+            }
+        }
+
+        device.destroy_context(&mut context).unwrap();
+    }
+
+    // Tests that a context created outside surfman can be adopted along with its config, and can
+    // then render into a surface that surfman creates for it.
+    #[test]
+    fn test_adopt_native_context_with_config() {
+        let mut device = match create_device() {
+            Some(device) => device,
+            None => return,
+        };
+
+        unsafe {
+            let egl_config = match find_config(&device, 8, 24).or(find_config(&device, 8, 0)) {
+                Some(egl_config) => egl_config,
+                None => return,
+            };
+
+            // Create the foreign context directly through EGL.
+            let egl_context = EGL_FUNCTIONS.with(|egl| {
+                let (egl_api, attributes) = match device.gl_api() {
+                    GLApi::GL => (egl::OPENGL_API, [egl::NONE as EGLint, 0, 0]),
+                    GLApi::GLES => (
+                        egl::OPENGL_ES_API,
+                        [
+                            egl::CONTEXT_CLIENT_VERSION as EGLint,
+                            2,
+                            egl::NONE as EGLint,
+                        ],
+                    ),
+                };
+                assert_ne!(egl.BindAPI(egl_api), egl::FALSE);
+                egl.CreateContext(
+                    device.egl_display,
+                    egl_config,
+                    egl::NO_CONTEXT,
+                    attributes.as_ptr(),
+                )
+            });
+            assert_ne!(egl_context, egl::NO_CONTEXT);
+
+            let mut context = device
+                .create_context_from_native_context_and_config(
+                    NativeContext {
+                        egl_context,
+                        egl_read_surface: egl::NO_SURFACE,
+                        egl_draw_surface: egl::NO_SURFACE,
+                    },
+                    egl_config,
+                )
+                .unwrap();
+            let surface = device
+                .create_surface(
+                    &context,
+                    SurfaceAccess::GPUOnly,
+                    SurfaceType::generic(Size2D::new(64, 64)),
+                )
+                .unwrap();
+            device
+                .bind_surface_to_context(&mut context, surface)
+                .unwrap();
+            device.make_context_current(&context).unwrap();
+
+            let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+            let framebuffer_object = device
+                .context_surface_info(&context)
+                .unwrap()
+                .unwrap()
+                .framebuffer_object;
+            let mut pixel = [0u8; 4];
+            gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer_object);
+            gl.Viewport(0, 0, 64, 64);
+            gl.ClearColor(0.0, 1.0, 0.0, 1.0);
+            gl.Clear(gl::COLOR_BUFFER_BIT);
+            gl.ReadPixels(
+                0,
+                0,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixel.as_mut_ptr() as *mut c_void,
+            );
+            assert_eq!(gl.GetError(), gl::NO_ERROR);
+            assert_eq!(pixel, [0, 255, 0, 255]);
+
+            // Destroying the adopted context destroys the surface but leaves the EGL context
+            // alive, so it's destroyed here.
+            device.make_no_context_current().unwrap();
+            device.destroy_context(&mut context).unwrap();
+            EGL_FUNCTIONS.with(|egl| {
+                let result = egl.DestroyContext(device.egl_display, egl_context);
+                assert_ne!(result, egl::FALSE);
+            });
+        }
+    }
+}
@@ -2,62 +2,164 @@
 //
 //! A wrapper around surfaceless Mesa `EGLDisplay`s.
 
-use super::connection::{Connection, NativeConnectionWrapper};
-use crate::{Error, GLApi};
+use super::connection::{self, Connection, NativeConnectionWrapper};
+use crate::debug::DebugSink;
+use crate::egl;
+use crate::egl::types::{EGLAttrib, EGLDeviceEXT, EGLDisplay, EGLenum, EGLint};
+use crate::platform::generic::egl::debug as egl_debug;
+use crate::platform::generic::egl::device;
+use crate::platform::generic::egl::ffi::{EGL_DEVICE_EXT, EGL_PLATFORM_DEVICE_EXT};
+use crate::platform::generic::egl::ffi::{EGL_EXTENSION_FUNCTIONS, EGL_NO_DEVICE_EXT};
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
 
-use std::env;
+use std::ffi::CStr;
+use std::os::raw::c_void;
+use std::ptr;
 use std::sync::Arc;
 
-static MESA_SOFTWARE_RENDERING_ENV_VAR: &'static str = "LIBGL_ALWAYS_SOFTWARE";
-static MESA_DRI_PRIME_ENV_VAR: &'static str = "DRI_PRIME";
-
 /// Represents a hardware display adapter that can be used for rendering (including the CPU).
 ///
 /// Adapters can be sent between threads. To render with an adapter, open a thread-local `Device`.
 #[derive(Clone, Debug)]
 pub enum Adapter {
-    #[doc(hidden)]
-    Hardware,
-    #[doc(hidden)]
-    HardwarePrime,
-    #[doc(hidden)]
-    Software,
+    /// The device that the EGL display renders with when no device is requested explicitly.
+    Default,
+    /// A specific EGL device, as returned by `Connection::adapters()`.
+    Device(EGLDevice),
 }
 
+/// An EGL device, as enumerated by `EGL_EXT_device_enumeration`.
+///
+/// These are returned by `Connection::adapters()` on the surfaceless backend.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct EGLDevice(pub EGLDeviceEXT);
+
+unsafe impl Send for EGLDevice {}
+unsafe impl Sync for EGLDevice {}
+
 impl Adapter {
-    #[inline]
+    /// Chooses the last hardware EGL device.
+    ///
+    /// This is a heuristic: on systems with an integrated and a discrete GPU, the discrete GPU is
+    /// usually enumerated last, but EGL doesn't guarantee any order.
+    ///
+    /// Falls back to the display's default device if no hardware device can be found.
     pub(crate) fn hardware() -> Adapter {
-        Adapter::HardwarePrime
+        let mut hardware_devices = EGLDevice::enumerate()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|egl_device| !egl_device.is_software());
+        hardware_devices
+            .next_back()
+            .map_or(Adapter::Default, Adapter::Device)
     }
 
-    #[inline]
+    /// Chooses the first hardware EGL device.
+    ///
+    /// Like `hardware()`, this is a heuristic that relies on the integrated GPU usually being
+    /// enumerated first.
+    ///
+    /// Falls back to the display's default device if no hardware device can be found.
     pub(crate) fn low_power() -> Adapter {
-        Adapter::Hardware
+        let mut hardware_devices = EGLDevice::enumerate()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|egl_device| !egl_device.is_software());
+        hardware_devices
+            .next()
+            .map_or(Adapter::Default, Adapter::Device)
     }
 
-    #[inline]
-    pub(crate) fn software() -> Adapter {
-        Adapter::Software
+    /// Chooses the software rasterizer EGL device.
+    ///
+    /// Returns `RequiredExtensionUnavailable` if EGL devices can be enumerated but none of them is
+    /// the software rasterizer.
+    pub(crate) fn software() -> Result<Adapter, Error> {
+        EGLDevice::enumerate()?
+            .into_iter()
+            .find(EGLDevice::is_software)
+            .map(Adapter::Device)
+            .ok_or(Error::RequiredExtensionUnavailable)
     }
 
-    pub(crate) fn set_environment_variables(&self) {
-        match *self {
-            Adapter::Hardware | Adapter::HardwarePrime => {
-                env::remove_var(MESA_SOFTWARE_RENDERING_ENV_VAR);
-            }
-            Adapter::Software => {
-                env::set_var(MESA_SOFTWARE_RENDERING_ENV_VAR, "1");
-            }
+    /// Returns true if displays of native platforms, such as X11 and Wayland, can render with
+    /// EGL devices other than the default one. This requires `EGL_EXT_explicit_device`.
+    pub(crate) fn explicit_device_supported() -> bool {
+        device::has_client_extension("EGL_EXT_explicit_device")
+    }
+
+    /// Opens an EGL display on the given platform that renders with this adapter's EGL device,
+    /// using `EGL_EXT_explicit_device`.
+    ///
+    /// Returns `None` for the default adapter, in which case the native display's default EGL
+    /// display should be used. Returns `RequiredExtensionUnavailable` if the EGL implementation
+    /// can't select devices per display, and `IncompatibleAdapter` if there is no native display
+    /// to open.
+    pub(crate) unsafe fn create_egl_display(
+        &self,
+        platform: EGLenum,
+        native_display: *mut c_void,
+    ) -> Result<Option<EGLDisplay>, Error> {
+        let egl_device = match *self {
+            Adapter::Device(EGLDevice(egl_device)) => egl_device,
+            Adapter::Default => return Ok(None),
+        };
+        if native_display.is_null() {
+            return Err(Error::IncompatibleAdapter);
+        }
+        if !Adapter::explicit_device_supported() {
+            return Err(Error::RequiredExtensionUnavailable);
         }
 
-        match *self {
-            Adapter::Software => {}
-            Adapter::Hardware => {
-                env::remove_var(MESA_DRI_PRIME_ENV_VAR);
-            }
-            Adapter::HardwarePrime => {
-                env::set_var(MESA_DRI_PRIME_ENV_VAR, "1");
+        let egl_display_attributes = [EGL_DEVICE_EXT as EGLAttrib, egl_device as EGLAttrib];
+        connection::create_egl_display(platform, native_display, &egl_display_attributes)
+            .map(Some)
+            .map_err(|_| Error::DeviceOpenFailed)
+    }
+}
+
+impl EGLDevice {
+    /// Returns all EGL devices on this system.
+    ///
+    /// This requires the `EGL_EXT_device_enumeration` and `EGL_EXT_platform_device` extensions.
+    #[allow(non_snake_case)]
+    pub(crate) fn enumerate() -> Result<Vec<EGLDevice>, Error> {
+        let eglQueryDevicesEXT = match EGL_EXTENSION_FUNCTIONS.QueryDevicesEXT {
+            Some(eglQueryDevicesEXT) if device::has_client_extension("EGL_EXT_platform_device") => {
+                eglQueryDevicesEXT
             }
+            _ => return Err(Error::RequiredExtensionUnavailable),
+        };
+
+        let mut device_count = 0;
+        if eglQueryDevicesEXT(0, ptr::null_mut(), &mut device_count) == egl::FALSE {
+            return Err(Error::NoAdapterFound);
+        }
+
+        let mut egl_devices = vec![EGL_NO_DEVICE_EXT; device_count as usize];
+        let ok = eglQueryDevicesEXT(device_count, egl_devices.as_mut_ptr(), &mut device_count);
+        if ok == egl::FALSE {
+            return Err(Error::NoAdapterFound);
+        }
+        egl_devices.truncate(device_count as usize);
+
+        Ok(egl_devices.into_iter().map(EGLDevice).collect())
+    }
+
+    /// Returns true if this is Mesa's software rasterizer device.
+    #[allow(non_snake_case)]
+    pub(crate) fn is_software(&self) -> bool {
+        let eglQueryDeviceStringEXT = match EGL_EXTENSION_FUNCTIONS.QueryDeviceStringEXT {
+            Some(eglQueryDeviceStringEXT) => eglQueryDeviceStringEXT,
+            None => return false,
+        };
+        unsafe {
+            let extensions = eglQueryDeviceStringEXT(self.0, egl::EXTENSIONS as EGLint);
+            !extensions.is_null()
+                && CStr::from_ptr(extensions)
+                    .to_string_lossy()
+                    .split_whitespace()
+                    .any(|extension| extension == "EGL_MESA_device_software")
         }
     }
 }
@@ -67,7 +169,12 @@ impl Adapter {
 /// Devices contain most of the relevant surface management methods.
 pub struct Device {
     pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) egl_display: EGLDisplay,
     pub(crate) adapter: Adapter,
+    pub(crate) gl_api: GLApi,
+    pub(crate) debug_sink: Arc<DebugSink>,
+    // Whether generic surfaces can be shared via EGL images instead of by copying.
+    pub(crate) supports_texture_images: bool,
 }
 
 /// Wraps an adapter.
@@ -79,12 +186,39 @@ pub struct NativeDevice {
     pub adapter: Adapter,
 }
 
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { device::release_display(self.egl_display) }
+    }
+}
+
 impl Device {
     #[inline]
     pub(crate) fn new(connection: &Connection, adapter: &Adapter) -> Result<Device, Error> {
+        // Adapters corresponding to EGL devices get a display of their own.
+        let egl_display = match *adapter {
+            Adapter::Device(EGLDevice(egl_device)) => unsafe {
+                connection::create_egl_display(
+                    EGL_PLATFORM_DEVICE_EXT,
+                    egl_device as *mut c_void,
+                    &[],
+                )
+                .map_err(|_| Error::DeviceOpenFailed)?
+            },
+            Adapter::Default => {
+                let egl_display = connection.native_connection.egl_display;
+                device::retain_display(egl_display);
+                egl_display
+            }
+        };
+
         Ok(Device {
             native_connection: connection.native_connection.clone(),
+            egl_display,
             adapter: (*adapter).clone(),
+            gl_api: connection.gl_api,
+            debug_sink: DebugSink::new(),
+            supports_texture_images: device::supports_texture_images(egl_display),
         })
     }
 
@@ -104,6 +238,7 @@ impl Device {
     pub fn connection(&self) -> Connection {
         Connection {
             native_connection: self.native_connection.clone(),
+            gl_api: self.gl_api,
         }
     }
 
@@ -114,8 +249,51 @@ impl Device {
     }
 
     /// Returns the OpenGL API flavor that this device supports (OpenGL or OpenGL ES).
+    ///
+    /// This is inherited from the connection that the device was opened on.
     #[inline]
     pub fn gl_api(&self) -> GLApi {
-        GLApi::GL
+        self.gl_api
+    }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The GL strings are read from a temporary context.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        unsafe { device::query_adapter_info(self.egl_display, self.gl_api()) }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Each format is tried out in a temporary context. `RGBA8` is always supported.
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        unsafe { device::query_supported_surface_formats(self.egl_display, self.gl_api()) }
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// Messages come from contexts created with the `DEBUG` flag and, if the `EGL_KHR_debug`
+    /// extension is available, from EGL errors on this thread. The callback replaces any previous
+    /// one. Without a callback, messages are forwarded to the `log` crate.
+    pub fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        self.debug_sink.set_callback(Some(Arc::new(callback)));
+        egl_debug::route_egl_debug_messages_to(&self.debug_sink);
+    }
+
+    /// Removes the debug message callback, forwarding messages to the `log` crate again.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {
+        self.debug_sink.set_callback(None);
+    }
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// By default, messages of `Low` severity and above are delivered from all sources.
+    #[inline]
+    pub fn set_debug_message_filter(&self, filter: DebugMessageFilter) {
+        self.debug_sink.set_filter(filter);
     }
 }
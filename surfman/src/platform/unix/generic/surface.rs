@@ -6,11 +6,16 @@ use super::context::{Context, GL_FUNCTIONS};
 use super::device::Device;
 use crate::gl;
 use crate::gl::types::{GLenum, GLuint};
-use crate::platform::generic::egl::surface::{EGLBackedSurface, EGLSurfaceTexture};
-use crate::{Error, SurfaceAccess, SurfaceInfo, SurfaceType};
+use crate::platform::generic::egl::surface::{
+    self, EGLBackedSurface, EGLSurfaceDataGuard, EGLSurfaceTexture,
+};
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceInfo, SurfaceType};
 
 use euclid::default::Size2D;
-use std::marker::PhantomData;
+use std::mem;
+
+pub use crate::platform::generic::egl::handle::SurfaceHandle;
+pub use crate::platform::generic::egl::surface::{DmaBufDescriptor, DmaBufPlane};
 
 // FIXME(pcwalton): Is this right, or should it be `TEXTURE_EXTERNAL_OES`?
 const SURFACE_GL_TEXTURE_TARGET: GLenum = gl::TEXTURE_2D;
@@ -60,32 +65,40 @@ impl Device {
     pub fn create_surface(
         &mut self,
         context: &Context,
-        _: SurfaceAccess,
+        access: SurfaceAccess,
         surface_type: SurfaceType<NativeWidget>,
     ) -> Result<Surface, Error> {
         match surface_type {
-            SurfaceType::Generic { size } => self.create_generic_surface(context, &size),
+            SurfaceType::Generic { size, format } => {
+                self.create_generic_surface(context, access, &size, format)
+            }
             SurfaceType::Widget { .. } => Err(Error::UnsupportedOnThisPlatform),
         }
     }
 
     fn create_generic_surface(
-        &mut self,
+        &self,
         context: &Context,
+        access: SurfaceAccess,
         size: &Size2D<i32>,
+        format: SurfaceFormat,
     ) -> Result<Surface, Error> {
         let _guard = self.temporarily_make_context_current(context)?;
         let context_descriptor = self.context_descriptor(context);
         let context_attributes = self.context_descriptor_attributes(&context_descriptor);
         GL_FUNCTIONS.with(|gl| {
-            Ok(Surface(EGLBackedSurface::new_generic(
+            EGLBackedSurface::new_generic(
                 gl,
-                self.native_connection.egl_display,
+                self.egl_display,
                 context.0.egl_context,
                 context.0.id,
                 &context_attributes,
                 size,
-            )))
+                access,
+                format,
+                self.supports_texture_images,
+            )
+            .map(Surface)
         })
     }
 
@@ -98,6 +111,9 @@ impl Device {
     /// with.* This allows you to render to a surface in one context and sample from that surface
     /// in another context.
     ///
+    /// If the driver can't wrap textures in EGL images, the surface texture is a copy of the
+    /// contents of the surface as of the last time it was unbound from its context.
+    ///
     /// Calling this method on a widget surface returns a `WidgetAttached` error.
     pub fn create_surface_texture(
         &self,
@@ -115,6 +131,90 @@ impl Device {
         })
     }
 
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    ///
+    /// This requires the `EGL_EXT_image_dma_buf_import` extension, as well as
+    /// `EGL_EXT_image_dma_buf_import_modifiers` if the buffer has an explicit modifier. The file
+    /// descriptors in the descriptor are closed once the buffer has been imported.
+    pub fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Surface, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        let context_descriptor = self.context_descriptor(context);
+        let context_attributes = self.context_descriptor_attributes(&context_descriptor);
+        unsafe {
+            let egl_image = surface::create_dmabuf_egl_image(self.egl_display, &descriptor)?;
+            GL_FUNCTIONS.with(|gl| {
+                let surface = EGLBackedSurface::new_from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &context_attributes,
+                    &descriptor.size,
+                    SurfaceAccess::GPUOnly,
+                    surface::surface_format_for_drm_fourcc(descriptor.fourcc),
+                )?;
+                Ok(Surface(surface))
+            })
+        }
+    }
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    ///
+    /// This is lighter than `create_surface_from_dmabuf()` followed by `create_surface_texture()`,
+    /// as no framebuffer is created. The surface returned by `destroy_surface_texture()` can't be
+    /// rendered to, so binding it to a context returns a `SurfaceNotRenderable` error. It should
+    /// simply be destroyed.
+    ///
+    /// The same extensions as `create_surface_from_dmabuf()` are required.
+    pub fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        unsafe {
+            let egl_image = surface::create_dmabuf_egl_image(self.egl_display, &descriptor)?;
+            GL_FUNCTIONS.with(|gl| {
+                let surface_texture = EGLSurfaceTexture::from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &descriptor.size,
+                    surface::surface_format_for_drm_fourcc(descriptor.fourcc),
+                )?;
+                Ok(SurfaceTexture(surface_texture))
+            })
+        }
+    }
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    ///
+    /// Send the handle over a Unix domain socket with `SurfaceHandle::send()`, and open it in the
+    /// receiving process with `create_surface_texture_from_handle()`. See the `SurfaceHandle`
+    /// documentation for the rules governing the lifetime of the shared buffer.
+    ///
+    /// The same extensions as `export_surface_dmabuf()` are required.
+    #[inline]
+    pub fn create_surface_handle(&self, surface: &Surface) -> Result<SurfaceHandle, Error> {
+        self.export_surface_dmabuf(surface)
+            .map(SurfaceHandle::from_dmabuf)
+    }
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    ///
+    /// The same extensions as `create_surface_texture_from_dmabuf()` are required.
+    #[inline]
+    pub fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture, Error> {
+        self.create_surface_texture_from_dmabuf(context, handle.into_dmabuf())
+    }
+
     /// Destroys a surface.
     ///
     /// The supplied context must be the context the surface is associated with, or this returns
@@ -128,7 +228,7 @@ impl Device {
         surface: &mut Surface,
     ) -> Result<(), Error> {
         GL_FUNCTIONS.with(|gl| {
-            let egl_display = self.native_connection.egl_display;
+            let egl_display = self.egl_display;
             let window = surface.0.destroy(gl, egl_display, context.0.id)?;
             debug_assert!(window.is_none());
             Ok(())
@@ -161,26 +261,95 @@ impl Device {
     /// The supplied context must match the context the surface was created with, or an
     /// `IncompatibleSurface` error is returned.
     pub fn present_surface(&self, context: &Context, surface: &mut Surface) -> Result<(), Error> {
-        surface
-            .0
-            .present(self.native_connection.egl_display, context.0.egl_context)
+        surface.0.present(self.egl_display, context.0.egl_context)
     }
 
-    /// Resizes a widget surface.
+    /// Resizes a surface.
+    ///
+    /// Generic surfaces are reallocated at the new size, which discards their contents and
+    /// changes their ID. Use `resize_surface_preserving_contents()` to keep the contents.
+    ///
+    /// The supplied context must be the context the surface is associated with, or this returns
+    /// an `IncompatibleSurface` error.
     pub fn resize_surface(
         &self,
-        _context: &Context,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.reallocate_surface(context, surface, size, false)
+    }
+
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    ///
+    /// Contents are anchored at the bottom left corner. Only the color contents are preserved;
+    /// depth and stencil buffers are cleared. Widget surfaces are resized as with
+    /// `resize_surface()`.
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
         surface: &mut Surface,
         size: Size2D<i32>,
     ) -> Result<(), Error> {
-        surface.0.size = size;
-        Ok(())
+        self.reallocate_surface(context, surface, size, true)
+    }
+
+    fn reallocate_surface(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+        preserve_contents: bool,
+    ) -> Result<(), Error> {
+        if surface.0.context_id != context.0.id {
+            return Err(Error::IncompatibleSurface);
+        }
+
+        let _guard = self.temporarily_make_context_current(context)?;
+        let mut new_surface =
+            self.create_generic_surface(context, surface.0.access, &size, surface.0.format)?;
+        GL_FUNCTIONS.with(|gl| {
+            if preserve_contents {
+                unsafe {
+                    new_surface
+                        .0
+                        .copy_contents_from(gl, self.egl_display, &surface.0)
+                };
+            }
+            mem::swap(surface, &mut new_surface);
+            let window = new_surface.0.destroy(gl, self.egl_display, context.0.id)?;
+            debug_assert!(window.is_none());
+            Ok(())
+        })
     }
 
     /// Returns a pointer to the underlying surface data for reading or writing by the CPU.
-    #[inline]
-    pub fn lock_surface_data<'s>(&self, _: &'s mut Surface) -> Result<SurfaceDataGuard<'s>, Error> {
-        Err(Error::Unimplemented)
+    ///
+    /// The surface must have been created with CPU access, or a `SurfaceDataInaccessible` error is
+    /// returned. Only `RGBA8` surfaces can be accessed; other formats return an
+    /// `UnsupportedSurfaceFormat` error. If the driver can export the surface as a linear
+    /// DMA-BUF, its memory is mapped directly. Otherwise, the data is copied into a pixel buffer
+    /// object, which requires a context on this device to be current, and copied back when the
+    /// guard is dropped.
+    pub fn lock_surface_data<'s>(
+        &self,
+        surface: &'s mut Surface,
+    ) -> Result<SurfaceDataGuard<'s>, Error> {
+        GL_FUNCTIONS.with(move |gl| unsafe {
+            let guard = surface.0.lock_data(gl, self.egl_display)?;
+            Ok(SurfaceDataGuard(guard))
+        })
+    }
+
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    ///
+    /// This requires the `EGL_MESA_image_dma_buf_export` extension. The file descriptors refer to
+    /// the memory of the surface itself, so no copy is made. Rendering commands targeting the
+    /// surface should be flushed first, for example by unbinding it from its context.
+    ///
+    /// Calling this method on a widget surface returns a `WidgetAttached` error.
+    pub fn export_surface_dmabuf(&self, surface: &Surface) -> Result<DmaBufDescriptor, Error> {
+        unsafe { surface.0.export_dmabuf(self.egl_display) }
     }
 
     /// Returns the OpenGL texture target needed to read from this surface texture.
@@ -211,6 +380,141 @@ impl Device {
 }
 
 /// Represents the CPU view of the pixel data of this surface.
-pub struct SurfaceDataGuard<'a> {
-    phantom: PhantomData<&'a ()>,
+pub struct SurfaceDataGuard<'a>(EGLSurfaceDataGuard<'a>);
+
+impl<'a> SurfaceDataGuard<'a> {
+    /// Returns the number of bytes per row of the surface.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.0.stride()
+    }
+
+    /// Returns a mutable slice of the pixel data in this surface, in RGBA format.
+    ///
+    /// Rows are stored bottom to top.
+    #[inline]
+    pub fn data(&mut self) -> &mut [u8] {
+        self.0.data()
+    }
+}
+
+impl<'a> Drop for SurfaceDataGuard<'a> {
+    fn drop(&mut self) {
+        GL_FUNCTIONS.with(|gl| unsafe { self.0.unlock(gl) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::connection::Connection;
+    use super::super::context::Context;
+    use super::super::device::Device;
+    use crate::gl;
+    use crate::gl::types::GLuint;
+    use crate::{ContextAttributeFlags, ContextAttributes, Error, GLVersion, Gl};
+    use crate::{SurfaceAccess, SurfaceType};
+
+    use euclid::default::Size2D;
+    use std::os::raw::c_void;
+
+    fn context_fbo(device: &Device, context: &Context) -> GLuint {
+        device
+            .context_surface_info(context)
+            .unwrap()
+            .unwrap()
+            .framebuffer_object
+    }
+
+    fn get_pixel(gl: &Gl) -> [u8; 4] {
+        let mut pixel = [0; 4];
+        unsafe {
+            gl.ReadPixels(
+                0,
+                0,
+                1,
+                1,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixel.as_mut_ptr() as *mut c_void,
+            );
+            assert_eq!(gl.GetError(), gl::NO_ERROR);
+        }
+        pixel
+    }
+
+    // Tests that surface textures are copies with the contents of their surfaces when textures
+    // can't be shared via EGL images, and that the surfaces keep their contents afterward.
+    #[test]
+    fn test_surface_texture_without_texture_images() {
+        let connection = Connection::new().unwrap();
+        let adapter = connection.create_low_power_adapter().unwrap();
+        let mut device = match connection.create_device(&adapter) {
+            Ok(device) => device,
+            Err(Error::RequiredExtensionUnavailable) => return,
+            Err(err) => panic!("Failed to create device: {:?}", err),
+        };
+        device.supports_texture_images = false;
+
+        let context_descriptor = device
+            .create_context_descriptor(&ContextAttributes {
+                version: GLVersion::new(3, 0),
+                flags: ContextAttributeFlags::empty(),
+                ..ContextAttributes::default()
+            })
+            .unwrap();
+        let mut context = device.create_context(&context_descriptor, None).unwrap();
+        let surface = device
+            .create_surface(
+                &context,
+                SurfaceAccess::GPUOnly,
+                SurfaceType::generic(Size2D::new(64, 64)),
+            )
+            .unwrap();
+        device
+            .bind_surface_to_context(&mut context, surface)
+            .unwrap();
+        device.make_context_current(&context).unwrap();
+        let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+
+        unsafe {
+            gl.BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&device, &context));
+            gl.Viewport(0, 0, 64, 64);
+            gl.ClearColor(0.0, 1.0, 0.0, 1.0);
+            gl.Clear(gl::COLOR_BUFFER_BIT);
+
+            let surface = device
+                .unbind_surface_from_context(&mut context)
+                .unwrap()
+                .unwrap();
+            let surface_texture = device
+                .create_surface_texture(&mut context, surface)
+                .unwrap();
+
+            let mut texture_framebuffer_object = 0;
+            gl.GenFramebuffers(1, &mut texture_framebuffer_object);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, texture_framebuffer_object);
+            gl.FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                device.surface_gl_texture_target(),
+                device.surface_texture_object(&surface_texture),
+                0,
+            );
+            assert_eq!(get_pixel(&gl), [0, 255, 0, 255]);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+            gl.DeleteFramebuffers(1, &texture_framebuffer_object);
+
+            let surface = device
+                .destroy_surface_texture(&mut context, surface_texture)
+                .unwrap();
+            device
+                .bind_surface_to_context(&mut context, surface)
+                .unwrap();
+            device.make_context_current(&context).unwrap();
+            gl.BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&device, &context));
+            assert_eq!(get_pixel(&gl), [0, 255, 0, 255]);
+        }
+
+        device.destroy_context(&mut context).unwrap();
+    }
 }
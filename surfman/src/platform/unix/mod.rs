@@ -2,17 +2,21 @@
 //
 //! Backends specific to Unix-like systems, particularly Linux.
 
-// The default when x11 is enabled
-#[cfg(x11)]
+// The default when x11, GBM or OSMesa is enabled
+#[cfg(any(x11, gbm, osmesa))]
 pub mod default;
 
-// The default when x11 is not enabled
-#[cfg(not(x11))]
+// The default when none of x11, GBM or OSMesa is enabled
+#[cfg(not(any(x11, gbm, osmesa)))]
 pub use wayland as default;
 
+#[cfg(gbm)]
+pub mod gbm;
 #[cfg(linux)]
 pub mod generic;
 
+#[cfg(osmesa)]
+pub mod osmesa;
 #[cfg(linux)]
 pub mod wayland;
 #[cfg(x11)]
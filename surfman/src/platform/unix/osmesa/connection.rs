@@ -0,0 +1,157 @@
+// surfman/surfman/src/platform/unix/osmesa/connection.rs
+//
+//! A no-op connection for the OSMesa backend.
+
+use super::device::{Adapter, Device, NativeDevice};
+use super::surface::NativeWidget;
+use crate::info::{AdapterInfo, GLApi};
+use crate::Error;
+
+use euclid::default::Size2D;
+use osmesa_sys::OsMesa;
+
+use std::os::raw::c_void;
+
+#[cfg(feature = "sm-winit")]
+use winit::window::Window;
+
+/// A no-op connection.
+///
+/// OSMesa has no concept of a display server, so this simply verifies that `libOSMesa` can be
+/// loaded.
+#[derive(Clone)]
+pub struct Connection;
+
+/// A no-op native connection.
+#[derive(Clone)]
+pub struct NativeConnection;
+
+impl Connection {
+    /// Loads the OSMesa library.
+    ///
+    /// Returns `NoGLLibraryFound` if `libOSMesa` could not be loaded.
+    #[inline]
+    pub fn new() -> Result<Connection, Error> {
+        match OsMesa::try_loading() {
+            Ok(_) => Ok(Connection),
+            Err(_) => Err(Error::NoGLLibraryFound),
+        }
+    }
+
+    /// An alias for `Connection::new()`, present for consistency with other backends.
+    #[inline]
+    pub unsafe fn from_native_connection(_: NativeConnection) -> Result<Connection, Error> {
+        Connection::new()
+    }
+
+    /// Returns the underlying native connection.
+    #[inline]
+    pub fn native_connection(&self) -> NativeConnection {
+        NativeConnection
+    }
+
+    /// Returns the OpenGL API flavor that this connection supports (OpenGL or OpenGL ES).
+    #[inline]
+    pub fn gl_api(&self) -> GLApi {
+        GLApi::GL
+    }
+
+    /// Returns the "best" adapter on this system.
+    ///
+    /// This is an alias for `Connection::create_hardware_adapter()`.
+    #[inline]
+    pub fn create_adapter(&self) -> Result<Adapter, Error> {
+        self.create_hardware_adapter()
+    }
+
+    /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
+    ///
+    /// On the OSMesa backend, this returns a software adapter.
+    #[inline]
+    pub fn create_hardware_adapter(&self) -> Result<Adapter, Error> {
+        Ok(Adapter)
+    }
+
+    /// Returns the "best" adapter on this system, preferring low-power hardware adapters.
+    ///
+    /// On the OSMesa backend, this returns a software adapter.
+    #[inline]
+    pub fn create_low_power_adapter(&self) -> Result<Adapter, Error> {
+        Ok(Adapter)
+    }
+
+    /// Returns the "best" adapter on this system, preferring software adapters.
+    #[inline]
+    pub fn create_software_adapter(&self) -> Result<Adapter, Error> {
+        Ok(Adapter)
+    }
+
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
+    }
+
+    /// Opens the device corresponding to the given adapter.
+    ///
+    /// Device handles are local to a single thread.
+    #[inline]
+    pub fn create_device(&self, adapter: &Adapter) -> Result<Device, Error> {
+        Device::new(self, adapter)
+    }
+
+    /// An alias for `connection.create_device()` with the default adapter.
+    #[inline]
+    pub unsafe fn create_device_from_native_device(
+        &self,
+        native_device: NativeDevice,
+    ) -> Result<Device, Error> {
+        Device::new(self, &native_device.adapter)
+    }
+
+    /// Opens the display connection corresponding to the given `winit` window.
+    #[inline]
+    #[cfg(feature = "sm-winit")]
+    pub fn from_winit_window(_: &Window) -> Result<Connection, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+
+    /// Opens the display connection corresponding to the given raw display handle.
+    #[cfg(feature = "sm-raw-window-handle")]
+    pub fn from_raw_display_handle(
+        _: raw_window_handle::RawDisplayHandle,
+    ) -> Result<Connection, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+
+    /// Creates a native widget type from the given `winit` window.
+    ///
+    /// This type can be later used to create surfaces that render to the window.
+    #[inline]
+    #[cfg(feature = "sm-winit")]
+    pub fn create_native_widget_from_winit_window(
+        &self,
+        _: &Window,
+    ) -> Result<NativeWidget, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+
+    /// Create a native widget from a raw pointer
+    pub unsafe fn create_native_widget_from_ptr(
+        &self,
+        _raw: *mut c_void,
+        _size: Size2D<i32>,
+    ) -> NativeWidget {
+        NativeWidget
+    }
+
+    /// Create a native widget type from the given `raw_window_handle::RawWindowHandle`.
+    #[cfg(feature = "sm-raw-window-handle")]
+    #[inline]
+    pub fn create_native_widget_from_rwh(
+        &self,
+        _: raw_window_handle::RawWindowHandle,
+    ) -> Result<NativeWidget, Error> {
+        Err(Error::IncompatibleNativeWidget)
+    }
+}
@@ -0,0 +1,687 @@
+// surfman/surfman/src/platform/unix/osmesa/context.rs
+//
+//! OpenGL rendering contexts on OSMesa.
+
+use super::device::Device;
+use super::surface::Surface;
+use crate::context::{self, ContextID, CREATE_CONTEXT_MUTEX};
+use crate::gl;
+use crate::gl::types::GLenum;
+use crate::surface::Framebuffer;
+use crate::WindowingApiError;
+use crate::{ContextAttributeFlags, ContextAttributes, ContextResetStatus, Error, GLApi};
+use crate::{ContextNegotiation, ContextNegotiationReport};
+use crate::{ContextPriority, ContextReleaseBehavior, GLVersion, Gl, SurfaceInfo};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
+
+use euclid::default::Size2D;
+use osmesa_sys::{OSMesaContext, OSMesaCreateContextAttribs, OSMesaDestroyContext};
+use osmesa_sys::{OSMesaGetColorBuffer, OSMesaGetCurrentContext, OSMesaGetDepthBuffer};
+use osmesa_sys::{OSMesaGetIntegerv, OSMesaGetProcAddress, OSMesaMakeCurrent};
+use osmesa_sys::{OSMESA_ACCUM_BITS, OSMESA_COMPAT_PROFILE, OSMESA_CONTEXT_MAJOR_VERSION};
+use osmesa_sys::{OSMESA_BGR, OSMESA_RGB_565, OSMESA_TYPE};
+use osmesa_sys::{OSMESA_CONTEXT_MINOR_VERSION, OSMESA_CORE_PROFILE, OSMESA_DEPTH_BITS};
+use osmesa_sys::{OSMESA_FORMAT, OSMESA_PROFILE, OSMESA_RGB, OSMESA_RGBA, OSMESA_STENCIL_BITS};
+use std::ffi::CString;
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use std::ptr;
+use std::thread;
+
+// The size of the buffer that a context renders to when no surface is attached. OSMesa requires
+// a color buffer in order to make a context current.
+const DUMMY_BUFFER_SIZE: i32 = 16;
+
+thread_local! {
+    #[doc(hidden)]
+    pub static GL_FUNCTIONS: Gl = Gl::load_with(get_proc_address);
+}
+
+/// Represents an OpenGL rendering context.
+///
+/// A context allows you to issue rendering commands to a surface. When initially created, a
+/// context has no attached surface, so rendering commands will fail or be ignored. Typically, you
+/// attach a surface to the context before rendering.
+///
+/// Contexts take ownership of the surfaces attached to them. In order to mutate a surface in any
+/// way other than rendering to it (e.g. presenting it to a window, which causes a buffer swap), it
+/// must first be detached from its context. Each surface is associated with a single context upon
+/// creation and may not be rendered to from any other context. However, you can wrap a surface in
+/// a surface texture, which allows the surface to be read from another context.
+///
+/// OpenGL objects may not be shared across contexts directly, but surface textures effectively
+/// allow for sharing of texture data. Contexts are local to a single thread and device.
+///
+/// A context must be explicitly destroyed with `destroy_context()`, or a panic will occur.
+pub struct Context {
+    pub(crate) osmesa_context: OSMesaContext,
+    pub(crate) id: ContextID,
+    framebuffer: Framebuffer<Surface, OSMesaBuffer>,
+    descriptor: ContextDescriptor,
+    dummy_buffer: Vec<u32>,
+    context_is_owned: bool,
+}
+
+/// Wrapper for a native `OSMesaContext` and the color buffer it renders to.
+#[derive(Clone, Copy)]
+pub struct NativeContext {
+    /// The OSMesa context.
+    pub osmesa_context: OSMesaContext,
+    /// The color buffer that the context renders to.
+    pub buffer: *mut c_void,
+    /// The data type of each color channel in `buffer` (e.g. `GL_UNSIGNED_BYTE`).
+    pub buffer_type: GLenum,
+    /// The size of `buffer`, in pixels.
+    pub size: Size2D<i32>,
+}
+
+/// Information needed to create a context. Some APIs call this a "config" or a "pixel format".
+///
+/// These are local to a device.
+#[derive(Clone)]
+pub struct ContextDescriptor {
+    pub(crate) attributes: ContextAttributes,
+}
+
+// The color buffer of an externally-managed context.
+#[derive(Clone, Copy)]
+pub(crate) struct OSMesaBuffer {
+    buffer: *mut c_void,
+    buffer_type: GLenum,
+    size: Size2D<i32>,
+}
+
+#[must_use]
+pub(crate) struct CurrentContextGuard {
+    old_context: Option<NativeContext>,
+}
+
+impl Drop for Context {
+    #[inline]
+    fn drop(&mut self) {
+        if !self.osmesa_context.is_null() && !thread::panicking() {
+            panic!("Contexts must be destroyed explicitly with `destroy_context`!")
+        }
+    }
+}
+
+impl Drop for CurrentContextGuard {
+    fn drop(&mut self) {
+        unsafe {
+            match self.old_context {
+                Some(ref old_context) => {
+                    OSMesaMakeCurrent(
+                        old_context.osmesa_context,
+                        old_context.buffer,
+                        old_context.buffer_type,
+                        old_context.size.width,
+                        old_context.size.height,
+                    );
+                }
+                None => {
+                    OSMesaMakeCurrent(ptr::null_mut(), ptr::null_mut(), 0, 0, 0);
+                }
+            }
+        }
+    }
+}
+
+impl Device {
+    /// Creates a context descriptor with the given attributes for the given OpenGL API flavor.
+    ///
+    /// OSMesa only supports OpenGL, so requesting OpenGL ES returns an `UnsupportedGLType` error.
+    #[inline]
+    pub fn create_context_descriptor_with_gl_api(
+        &self,
+        attributes: &ContextAttributes,
+        gl_api: GLApi,
+    ) -> Result<ContextDescriptor, Error> {
+        match gl_api {
+            GLApi::GL => self.create_context_descriptor(attributes),
+            GLApi::GLES => Err(Error::UnsupportedGLType),
+        }
+    }
+
+    /// Creates a context descriptor with the given attributes.
+    ///
+    /// Context descriptors are local to this device. OSMesa surfaces are always RGBA, so the
+    /// descriptor always has the `ALPHA` flag, whether or not it was requested.
+    #[inline]
+    pub fn create_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<ContextDescriptor, Error> {
+        // OSMesa only supports the compatibility profile up to OpenGL 3.0.
+        if attributes
+            .flags
+            .contains(ContextAttributeFlags::COMPATIBILITY_PROFILE)
+            && (attributes.version.major > 3
+                || attributes.version.major == 3 && attributes.version.minor > 0)
+        {
+            return Err(Error::UnsupportedGLProfile);
+        }
+
+        // OSMesa has no way to request robust, debug, forward-compatible, no-error, or configless
+        // contexts.
+        if attributes.flags.intersects(
+            ContextAttributeFlags::ROBUST_ACCESS
+                | ContextAttributeFlags::LOSE_CONTEXT_ON_RESET
+                | ContextAttributeFlags::DEBUG
+                | ContextAttributeFlags::FORWARD_COMPATIBLE
+                | ContextAttributeFlags::NO_ERROR
+                | ContextAttributeFlags::NO_CONFIG,
+        ) {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+
+        // Nor does it have priorities or release behaviors, so those fall back to the defaults.
+        // Multisampled surfaces aren't supported either.
+        Ok(ContextDescriptor {
+            attributes: ContextAttributes {
+                priority: ContextPriority::Medium,
+                release_behavior: ContextReleaseBehavior::Flush,
+                samples: 0,
+                flags: attributes.flags | ContextAttributeFlags::ALPHA,
+                ..*attributes
+            },
+        })
+    }
+
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// OSMesa has a single pixel format for each set of attributes, so this returns the descriptor
+    /// that `create_context_descriptor()` does.
+    #[inline]
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor>, Error> {
+        Ok(vec![self.create_context_descriptor(attributes)?])
+    }
+
+    /// Creates a context descriptor with the given attributes.
+    ///
+    /// There is only one pixel format to choose from, so the preferences are ignored.
+    #[inline]
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        _: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor, Error> {
+        self.create_context_descriptor(attributes)
+    }
+
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    #[inline]
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor, ContextNegotiationReport), Error> {
+        crate::context::negotiate_context_descriptor(self, negotiation)
+    }
+
+    /// Creates a new OpenGL context.
+    ///
+    /// The context initially has no surface attached. Until a surface is bound to it, rendering
+    /// commands will fail or have no effect.
+    pub fn create_context(
+        &mut self,
+        descriptor: &ContextDescriptor,
+        share_with: Option<&Context>,
+    ) -> Result<Context, Error> {
+        let mut next_context_id = CREATE_CONTEXT_MUTEX.lock().unwrap();
+
+        let attributes = &descriptor.attributes;
+        let depth_bits = if attributes.flags.contains(ContextAttributeFlags::DEPTH) {
+            24
+        } else {
+            0
+        };
+        let stencil_bits = if attributes.flags.contains(ContextAttributeFlags::STENCIL) {
+            8
+        } else {
+            0
+        };
+
+        // OpenGL 3.1 and up are only available with the core profile.
+        let profile = if attributes
+            .flags
+            .contains(ContextAttributeFlags::COMPATIBILITY_PROFILE)
+            || attributes.version.major < 3
+            || attributes.version.major == 3 && attributes.version.minor == 0
+        {
+            OSMESA_COMPAT_PROFILE
+        } else {
+            OSMESA_CORE_PROFILE
+        };
+
+        let context_attributes = [
+            OSMESA_FORMAT,
+            OSMESA_RGBA as c_int,
+            OSMESA_DEPTH_BITS,
+            depth_bits,
+            OSMESA_STENCIL_BITS,
+            stencil_bits,
+            OSMESA_ACCUM_BITS,
+            0,
+            OSMESA_PROFILE,
+            profile,
+            OSMESA_CONTEXT_MAJOR_VERSION,
+            attributes.version.major as c_int,
+            OSMESA_CONTEXT_MINOR_VERSION,
+            attributes.version.minor as c_int,
+            0,
+        ];
+
+        unsafe {
+            let osmesa_context = OSMesaCreateContextAttribs(
+                context_attributes.as_ptr(),
+                share_with.map_or(ptr::null_mut(), |context| context.osmesa_context),
+            );
+            if osmesa_context.is_null() {
+                return Err(Error::ContextCreationFailed(WindowingApiError::Failed));
+            }
+
+            let context = Context {
+                osmesa_context,
+                id: *next_context_id,
+                framebuffer: Framebuffer::None,
+                descriptor: (*descriptor).clone(),
+                dummy_buffer: vec![0; (DUMMY_BUFFER_SIZE * DUMMY_BUFFER_SIZE) as usize],
+                context_is_owned: true,
+            };
+            next_context_id.0 += 1;
+            Ok(context)
+        }
+    }
+
+    /// Wraps an `OSMesaContext` in a native context and returns it.
+    ///
+    /// The context is not retained, as there is no way to do this in the OSMesa API. Therefore,
+    /// it is the caller's responsibility to ensure that the returned `Context` object remains
+    /// alive as long as the `OSMesaContext` and its color buffer are.
+    pub unsafe fn create_context_from_native_context(
+        &self,
+        native_context: NativeContext,
+    ) -> Result<Context, Error> {
+        let descriptor = {
+            let _guard = CurrentContextGuard::new();
+            let ok = OSMesaMakeCurrent(
+                native_context.osmesa_context,
+                native_context.buffer,
+                native_context.buffer_type,
+                native_context.size.width,
+                native_context.size.height,
+            );
+            if ok == 0 {
+                return Err(Error::MakeCurrentFailed(WindowingApiError::Failed));
+            }
+            ContextDescriptor::from_current_context(native_context.osmesa_context)
+        };
+
+        let mut next_context_id = CREATE_CONTEXT_MUTEX.lock().unwrap();
+        let context = Context {
+            osmesa_context: native_context.osmesa_context,
+            id: *next_context_id,
+            framebuffer: Framebuffer::External(OSMesaBuffer {
+                buffer: native_context.buffer,
+                buffer_type: native_context.buffer_type,
+                size: native_context.size,
+            }),
+            descriptor,
+            dummy_buffer: vec![0; (DUMMY_BUFFER_SIZE * DUMMY_BUFFER_SIZE) as usize],
+            context_is_owned: false,
+        };
+        next_context_id.0 += 1;
+        Ok(context)
+    }
+
+    /// Destroys a context.
+    ///
+    /// The context must have been created on this device.
+    pub fn destroy_context(&self, context: &mut Context) -> Result<(), Error> {
+        if let Ok(Some(mut surface)) = self.unbind_surface_from_context(context) {
+            self.destroy_surface(context, &mut surface)?;
+        }
+
+        unsafe {
+            if context.is_current() {
+                self.make_no_context_current()?;
+            }
+
+            if context.context_is_owned {
+                OSMesaDestroyContext(context.osmesa_context);
+            }
+
+            context.osmesa_context = ptr::null_mut();
+            Ok(())
+        }
+    }
+
+    /// Given a context, returns its underlying OSMesa context and the buffer it renders to.
+    #[inline]
+    pub fn native_context(&self, context: &Context) -> NativeContext {
+        let (buffer, buffer_type, size) = context.buffer();
+        NativeContext {
+            osmesa_context: context.osmesa_context,
+            buffer,
+            buffer_type,
+            size,
+        }
+    }
+
+    /// Returns the descriptor that this context was created with.
+    #[inline]
+    pub fn context_descriptor(&self, context: &Context) -> ContextDescriptor {
+        context.descriptor.clone()
+    }
+
+    /// Makes the context the current OpenGL context for this thread.
+    ///
+    /// After calling this function, it is valid to use OpenGL rendering commands.
+    #[inline]
+    pub fn make_context_current(&self, context: &Context) -> Result<(), Error> {
+        unsafe { context.make_current() }
+    }
+
+    /// Removes the current OpenGL context from this thread.
+    ///
+    /// After calling this function, OpenGL rendering commands will fail until a new context is
+    /// made current.
+    #[inline]
+    pub fn make_no_context_current(&self) -> Result<(), Error> {
+        unsafe {
+            if OSMesaMakeCurrent(ptr::null_mut(), ptr::null_mut(), 0, 0, 0) == 0 {
+                return Err(Error::MakeCurrentFailed(WindowingApiError::Failed));
+            }
+            Ok(())
+        }
+    }
+
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// OSMesa renders on the CPU, so its contexts are never reset.
+    #[inline]
+    pub fn context_reset_status(&self, _: &Context) -> Result<ContextResetStatus, Error> {
+        Ok(ContextResetStatus::NoError)
+    }
+
+    #[inline]
+    pub(crate) fn temporarily_make_context_current(
+        &self,
+        context: &Context,
+    ) -> Result<CurrentContextGuard, Error> {
+        let guard = CurrentContextGuard::new();
+        self.make_context_current(context)?;
+        Ok(guard)
+    }
+
+    /// Returns the attributes that the context descriptor was created with.
+    #[inline]
+    pub fn context_descriptor_attributes(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> ContextAttributes {
+        context_descriptor.attributes
+    }
+
+    /// Returns the OpenGL API flavor that contexts created from the given descriptor use.
+    ///
+    /// This is always OpenGL on OSMesa.
+    #[inline]
+    pub fn context_descriptor_gl_api(&self, _: &ContextDescriptor) -> GLApi {
+        GLApi::GL
+    }
+
+    /// Returns the properties of the pixel format that the context descriptor selects.
+    ///
+    /// OSMesa can't report the properties of its buffers, so these are derived from the attributes
+    /// that the descriptor was created with.
+    #[inline]
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> PixelFormatInfo {
+        PixelFormatInfo::from_attributes(&context_descriptor.attributes)
+    }
+
+    /// Fetches the address of an OpenGL function associated with this context.
+    ///
+    /// OpenGL functions are local to a context. You should not use OpenGL functions on one context
+    /// with any other context.
+    ///
+    /// This method is typically used with a function like `gl::load_with()` from the `gl` crate to
+    /// load OpenGL function pointers.
+    #[inline]
+    pub fn get_proc_address(&self, _: &Context, symbol_name: &str) -> *const c_void {
+        get_proc_address(symbol_name)
+    }
+
+    /// Attaches a surface to a context for rendering.
+    ///
+    /// This function takes ownership of the surface. The surface must have been created with this
+    /// context, or an `IncompatibleSurface` error is returned.
+    ///
+    /// If this function is called with a surface already bound, a `SurfaceAlreadyBound` error is
+    /// returned. To avoid this error, first unbind the existing surface with
+    /// `unbind_surface_from_context`.
+    ///
+    /// If an error is returned, the surface is returned alongside it.
+    pub fn bind_surface_to_context(
+        &self,
+        context: &mut Context,
+        surface: Surface,
+    ) -> Result<(), (Error, Surface)> {
+        if context.id != surface.context_id {
+            return Err((Error::IncompatibleSurface, surface));
+        }
+
+        match context.framebuffer {
+            Framebuffer::None => context.framebuffer = Framebuffer::Surface(surface),
+            Framebuffer::External(_) => return Err((Error::ExternalRenderTarget, surface)),
+            Framebuffer::Surface(_) => return Err((Error::SurfaceAlreadyBound, surface)),
+        }
+
+        // If we're current, make the context current again to switch to the new color buffer.
+        if context.is_current() {
+            drop(self.make_context_current(context));
+        }
+
+        Ok(())
+    }
+
+    /// Removes and returns any attached surface from this context.
+    ///
+    /// Any pending OpenGL commands targeting this surface will be automatically finished, so the
+    /// surface is safe to read from immediately when this function returns.
+    pub fn unbind_surface_from_context(
+        &self,
+        context: &mut Context,
+    ) -> Result<Option<Surface>, Error> {
+        match context.framebuffer {
+            Framebuffer::None => return Ok(None),
+            Framebuffer::Surface(_) => {}
+            Framebuffer::External(_) => return Err(Error::ExternalRenderTarget),
+        }
+
+        let is_current = context.is_current();
+        if is_current {
+            // The rasterizer may still be writing to the surface's pixels on another thread.
+            GL_FUNCTIONS.with(|gl| unsafe { gl.Finish() });
+        }
+
+        let surface = match mem::replace(&mut context.framebuffer, Framebuffer::None) {
+            Framebuffer::Surface(surface) => surface,
+            Framebuffer::None | Framebuffer::External(_) => unreachable!(),
+        };
+
+        // If we're current, we stay current, but with no surface attached.
+        if is_current {
+            drop(self.make_context_current(context));
+        }
+
+        Ok(Some(surface))
+    }
+
+    /// Returns a unique ID representing a context.
+    ///
+    /// This ID is unique to all currently-allocated contexts. If you destroy a context and create
+    /// a new one, the new context might have the same ID as the destroyed one.
+    #[inline]
+    pub fn context_id(&self, context: &Context) -> ContextID {
+        context.id
+    }
+
+    /// Returns various information about the surface attached to a context.
+    ///
+    /// This includes, most notably, the OpenGL framebuffer object needed to render to the surface.
+    #[inline]
+    pub fn context_surface_info(&self, context: &Context) -> Result<Option<SurfaceInfo>, Error> {
+        match context.framebuffer {
+            Framebuffer::None => Ok(None),
+            Framebuffer::External(_) => Err(Error::ExternalRenderTarget),
+            Framebuffer::Surface(ref surface) => Ok(Some(self.surface_info(surface))),
+        }
+    }
+}
+
+impl Context {
+    fn buffer(&self) -> (*mut c_void, GLenum, Size2D<i32>) {
+        match self.framebuffer {
+            Framebuffer::Surface(ref surface) => (
+                surface.pixels.as_ptr() as *mut c_void,
+                gl::UNSIGNED_BYTE,
+                surface.size,
+            ),
+            Framebuffer::External(ref buffer) => (buffer.buffer, buffer.buffer_type, buffer.size),
+            Framebuffer::None => (
+                self.dummy_buffer.as_ptr() as *mut c_void,
+                gl::UNSIGNED_BYTE,
+                Size2D::new(DUMMY_BUFFER_SIZE, DUMMY_BUFFER_SIZE),
+            ),
+        }
+    }
+
+    unsafe fn make_current(&self) -> Result<(), Error> {
+        let (buffer, buffer_type, size) = self.buffer();
+        let ok = OSMesaMakeCurrent(
+            self.osmesa_context,
+            buffer,
+            buffer_type,
+            size.width,
+            size.height,
+        );
+        if ok == 0 {
+            return Err(Error::MakeCurrentFailed(WindowingApiError::Failed));
+        }
+        Ok(())
+    }
+
+    #[inline]
+    fn is_current(&self) -> bool {
+        unsafe { OSMesaGetCurrentContext() == self.osmesa_context }
+    }
+}
+
+impl NativeContext {
+    /// Returns the current OSMesa context and its color buffer, if applicable.
+    ///
+    /// If there is no current OSMesa context, this returns a `NoCurrentContext` error.
+    pub fn current() -> Result<NativeContext, Error> {
+        unsafe {
+            let osmesa_context = OSMesaGetCurrentContext();
+            if osmesa_context.is_null() {
+                return Err(Error::NoCurrentContext);
+            }
+
+            let (mut width, mut height, mut format) = (0, 0, 0);
+            let mut buffer = ptr::null_mut();
+            if OSMesaGetColorBuffer(
+                osmesa_context,
+                &mut width,
+                &mut height,
+                &mut format,
+                &mut buffer,
+            ) == 0
+            {
+                return Err(Error::NoCurrentContext);
+            }
+
+            let mut buffer_type = 0;
+            OSMesaGetIntegerv(OSMESA_TYPE, &mut buffer_type);
+
+            Ok(NativeContext {
+                osmesa_context,
+                buffer,
+                buffer_type: buffer_type as GLenum,
+                size: Size2D::new(width, height),
+            })
+        }
+    }
+}
+
+impl ContextDescriptor {
+    // Assumes that `osmesa_context` is current.
+    unsafe fn from_current_context(osmesa_context: OSMesaContext) -> ContextDescriptor {
+        let (mut width, mut height, mut format) = (0, 0, 0);
+        let mut buffer = ptr::null_mut();
+        OSMesaGetColorBuffer(
+            osmesa_context,
+            &mut width,
+            &mut height,
+            &mut format,
+            &mut buffer,
+        );
+        let has_alpha = !matches!(format as u32, OSMESA_RGB | OSMESA_BGR | OSMESA_RGB_565);
+
+        let mut bytes_per_depth_value = 0;
+        let mut depth_buffer = ptr::null_mut();
+        let has_depth = OSMesaGetDepthBuffer(
+            osmesa_context,
+            &mut width,
+            &mut height,
+            &mut bytes_per_depth_value,
+            &mut depth_buffer,
+        ) != 0;
+
+        GL_FUNCTIONS.with(|gl| {
+            let mut flags = ContextAttributeFlags::empty();
+            flags.set(ContextAttributeFlags::ALPHA, has_alpha);
+            flags.set(ContextAttributeFlags::DEPTH, has_depth);
+            flags.set(
+                ContextAttributeFlags::COMPATIBILITY_PROFILE,
+                context::current_context_uses_compatibility_profile(gl),
+            );
+
+            ContextDescriptor {
+                attributes: ContextAttributes {
+                    version: GLVersion::current(gl),
+                    flags,
+                    ..ContextAttributes::default()
+                },
+            }
+        })
+    }
+}
+
+impl CurrentContextGuard {
+    pub(crate) fn new() -> CurrentContextGuard {
+        CurrentContextGuard {
+            old_context: NativeContext::current().ok(),
+        }
+    }
+}
+
+pub(crate) fn get_proc_address(symbol_name: &str) -> *const c_void {
+    unsafe {
+        let symbol_name: CString = CString::new(symbol_name).unwrap();
+        match OSMesaGetProcAddress(symbol_name.as_ptr()) {
+            Some(function) => function as *const c_void,
+            None => ptr::null(),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+// surfman/surfman/src/platform/unix/osmesa/device.rs
+//
+//! A wrapper around the OSMesa software rasterizer.
+
+use super::connection::Connection;
+use super::context::{CurrentContextGuard, GL_FUNCTIONS};
+use crate::gl;
+use crate::SurfaceFormat;
+use crate::WindowingApiError;
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, GLVersion};
+
+use osmesa_sys::{OSMesaCreateContextExt, OSMesaDestroyContext, OSMesaMakeCurrent, OSMESA_RGBA};
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_void};
+use std::ptr;
+
+/// Represents a hardware display adapter that can be used for rendering (including the CPU).
+///
+/// OSMesa only ever renders on the CPU, so there is exactly one adapter.
+///
+/// Adapters can be sent between threads. To render with an adapter, open a thread-local `Device`.
+#[derive(Clone, Debug)]
+pub struct Adapter;
+
+/// A thread-local handle to a device.
+///
+/// Devices contain most of the relevant surface management methods.
+pub struct Device {
+    pub(crate) adapter: Adapter,
+}
+
+/// Wraps an adapter.
+///
+/// On OSMesa, devices and adapters are essentially identical types.
+#[derive(Clone)]
+pub struct NativeDevice {
+    /// The adapter corresponding to this device.
+    pub adapter: Adapter,
+}
+
+impl Device {
+    #[inline]
+    pub(crate) fn new(_: &Connection, adapter: &Adapter) -> Result<Device, Error> {
+        Ok(Device {
+            adapter: (*adapter).clone(),
+        })
+    }
+
+    /// Returns the native device corresponding to this device.
+    ///
+    /// This method is essentially an alias for the `adapter()` method on OSMesa, since there is
+    /// no explicit concept of a device on this backend.
+    #[inline]
+    pub fn native_device(&self) -> NativeDevice {
+        NativeDevice {
+            adapter: self.adapter(),
+        }
+    }
+
+    /// Returns the display server connection that this device was created with.
+    #[inline]
+    pub fn connection(&self) -> Connection {
+        Connection
+    }
+
+    /// Returns the adapter that this device was created with.
+    #[inline]
+    pub fn adapter(&self) -> Adapter {
+        self.adapter.clone()
+    }
+
+    /// Returns the OpenGL API flavor that this device supports (OpenGL or OpenGL ES).
+    #[inline]
+    pub fn gl_api(&self) -> GLApi {
+        GLApi::GL
+    }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The GL strings are read from a temporary context.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        unsafe {
+            let osmesa_context = OSMesaCreateContextExt(OSMESA_RGBA, 0, 0, 0, ptr::null_mut());
+            if osmesa_context.is_null() {
+                return Err(Error::ContextCreationFailed(WindowingApiError::Failed));
+            }
+
+            let mut pixel = [0u32; 1];
+            let result = {
+                let _guard = CurrentContextGuard::new();
+                let ok = OSMesaMakeCurrent(
+                    osmesa_context,
+                    pixel.as_mut_ptr() as *mut c_void,
+                    gl::UNSIGNED_BYTE,
+                    1,
+                    1,
+                );
+                if ok == 0 {
+                    Err(Error::MakeCurrentFailed(WindowingApiError::Failed))
+                } else {
+                    Ok(GL_FUNCTIONS.with(|gl| {
+                        let get_string = |name| {
+                            CStr::from_ptr(gl.GetString(name) as *const c_char)
+                                .to_string_lossy()
+                                .into_owned()
+                        };
+                        AdapterInfo {
+                            vendor: get_string(gl::VENDOR),
+                            renderer: get_string(gl::RENDERER),
+                            driver: Some("osmesa".to_owned()),
+                            drm_render_node: None,
+                            software: true,
+                            gl_version: GLVersion::current(gl),
+                            egl_version: None,
+                            egl_platform_path: None,
+                        }
+                    }))
+                }
+            };
+
+            OSMesaDestroyContext(osmesa_context);
+            result
+        }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Only `RGBA8` is supported on this backend.
+    #[inline]
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Ok(vec![SurfaceFormat::RGBA8])
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// OSMesa doesn't support debug contexts, so the callback is never called.
+    #[inline]
+    pub fn set_debug_message_callback<F>(&self, _: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+    }
+
+    /// Removes the debug message callback.
+    ///
+    /// OSMesa doesn't support debug contexts, so this does nothing.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {}
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// OSMesa doesn't support debug contexts, so this does nothing.
+    #[inline]
+    pub fn set_debug_message_filter(&self, _: DebugMessageFilter) {}
+}
@@ -0,0 +1,16 @@
+// surfman/surfman/src/platform/unix/osmesa/mod.rs
+//
+//! The OSMesa software backend, which renders into CPU memory and requires neither a display
+//! server nor `libEGL`.
+
+pub mod connection;
+pub mod context;
+pub mod device;
+pub mod surface;
+
+#[path = "../../../implementation/mod.rs"]
+mod implementation;
+
+#[cfg(test)]
+#[path = "../../../tests.rs"]
+mod tests;
@@ -0,0 +1,455 @@
+// surfman/surfman/src/platform/unix/osmesa/surface.rs
+//
+//! Surfaces in CPU memory, rendered to by OSMesa.
+
+use super::context::{Context, GL_FUNCTIONS};
+use super::device::Device;
+use crate::context::ContextID;
+use crate::gl;
+use crate::gl::types::{GLenum, GLint, GLuint};
+use crate::WindowingApiError;
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceInfo, SurfaceType};
+
+use euclid::default::Size2D;
+use std::fmt::{self, Debug, Formatter};
+use std::marker::PhantomData;
+use std::os::raw::c_void;
+use std::sync::Mutex;
+use std::thread;
+
+pub use crate::platform::generic::egl::handle::SurfaceHandle;
+pub use crate::platform::generic::egl::surface::{DmaBufDescriptor, DmaBufPlane};
+
+const SURFACE_GL_TEXTURE_TARGET: GLenum = gl::TEXTURE_2D;
+
+lazy_static! {
+    // The pixel buffer of a surface moves when it's reallocated, so surface IDs are counted
+    // instead of being derived from it.
+    static ref NEXT_SURFACE_ID: Mutex<SurfaceID> = Mutex::new(SurfaceID(1));
+}
+
+// OSMesa renders RGBA, 8 bits per channel.
+const BYTES_PER_PIXEL: usize = 4;
+
+/// Represents a hardware buffer of pixels that can be rendered to via the CPU or GPU and either
+/// displayed in a native widget or bound to a texture for reading.
+///
+/// Surfaces come in two varieties: generic and widget surfaces. Generic surfaces can be bound to a
+/// texture but cannot be displayed in a widget (without using other APIs such as Core Animation,
+/// DirectComposition, or XPRESENT). Widget surfaces are the opposite: they can be displayed in a
+/// widget but not bound to a texture.
+///
+/// On OSMesa, surfaces live in CPU memory and only the generic variety is supported.
+///
+/// Surfaces are specific to a given context and cannot be rendered to from any context other than
+/// the one they were created with. However, they can be *read* from any context on any thread (as
+/// long as that context shares the same adapter and connection), by wrapping them in a
+/// `SurfaceTexture`.
+///
+/// Surfaces must be destroyed with the `destroy_surface()` method, or a panic will occur.
+pub struct Surface {
+    pub(crate) pixels: Vec<u8>,
+    pub(crate) size: Size2D<i32>,
+    pub(crate) context_id: ContextID,
+    id: SurfaceID,
+    access: SurfaceAccess,
+    destroyed: bool,
+}
+
+/// Represents an OpenGL texture that wraps a surface.
+///
+/// Reading from the associated OpenGL texture reads from the surface. It is undefined behavior to
+/// write to such a texture (e.g. by binding it to a framebuffer and rendering to that
+/// framebuffer).
+///
+/// On OSMesa, the surface contents are uploaded to the texture when the surface texture is
+/// created.
+///
+/// Surface textures are local to a context, but that context does not have to be the same context
+/// as that associated with the underlying surface. The texture must be destroyed with the
+/// `destroy_surface_texture()` method, or a panic will occur.
+pub struct SurfaceTexture {
+    pub(crate) surface: Surface,
+    pub(crate) texture_object: GLuint,
+    phantom: PhantomData<*const ()>,
+}
+
+/// A placeholder wrapper for a native widget.
+#[derive(Clone)]
+pub struct NativeWidget;
+
+/// Represents the CPU view of the pixel data of this surface.
+pub struct SurfaceDataGuard<'a> {
+    surface: &'a mut Surface,
+}
+
+unsafe impl Send for Surface {}
+
+impl Debug for Surface {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "Surface({:x})", self.id().0)
+    }
+}
+
+impl Debug for SurfaceTexture {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), fmt::Error> {
+        write!(f, "SurfaceTexture({:?})", self.surface)
+    }
+}
+
+impl Drop for Surface {
+    fn drop(&mut self) {
+        if !self.destroyed && !thread::panicking() {
+            panic!("Should have destroyed the surface first with `destroy_surface()`!")
+        }
+    }
+}
+
+impl Device {
+    /// Creates either a generic or a widget surface, depending on the supplied surface type.
+    ///
+    /// Only the given context may ever render to the surface, but generic surfaces can be wrapped
+    /// up in a `SurfaceTexture` for reading by other contexts.
+    pub fn create_surface(
+        &mut self,
+        context: &Context,
+        access: SurfaceAccess,
+        surface_type: SurfaceType<NativeWidget>,
+    ) -> Result<Surface, Error> {
+        match surface_type {
+            SurfaceType::Generic {
+                size,
+                format: SurfaceFormat::RGBA8,
+            } => {
+                let pixels = vec![0; pixel_buffer_length(&size)?];
+                let mut next_surface_id = NEXT_SURFACE_ID.lock().unwrap();
+                let surface = Surface {
+                    pixels,
+                    size,
+                    context_id: context.id,
+                    id: *next_surface_id,
+                    access,
+                    destroyed: false,
+                };
+                next_surface_id.0 += 1;
+                Ok(surface)
+            }
+            SurfaceType::Generic { .. } => Err(Error::UnsupportedSurfaceFormat),
+            SurfaceType::Widget { .. } => Err(Error::UnsupportedOnThisPlatform),
+        }
+    }
+
+    /// Creates a surface texture from an existing generic surface for use with the given context.
+    ///
+    /// The surface texture is local to the supplied context and takes ownership of the surface.
+    /// Destroying the surface texture allows you to retrieve the surface again.
+    ///
+    /// *The supplied context does not have to be the same context that the surface is associated
+    /// with.* This allows you to render to a surface in one context and sample from that surface
+    /// in another context.
+    pub fn create_surface_texture(
+        &self,
+        context: &mut Context,
+        surface: Surface,
+    ) -> Result<SurfaceTexture, (Error, Surface)> {
+        let _guard = match self.temporarily_make_context_current(context) {
+            Ok(guard) => guard,
+            Err(err) => return Err((err, surface)),
+        };
+
+        GL_FUNCTIONS.with(|gl| unsafe {
+            let mut texture_object = 0;
+            gl.GenTextures(1, &mut texture_object);
+
+            // Save the current texture binding.
+            let mut old_texture_object = 0;
+            gl.GetIntegerv(gl::TEXTURE_BINDING_2D, &mut old_texture_object);
+            gl.BindTexture(gl::TEXTURE_2D, texture_object);
+
+            // Unbind PIXEL_UNPACK_BUFFER, because if it is bound, `glTexImage2D` would read from
+            // it instead of the surface.
+            let mut unpack_buffer = 0;
+            gl.GetIntegerv(gl::PIXEL_UNPACK_BUFFER_BINDING, &mut unpack_buffer);
+            if unpack_buffer != 0 {
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+            }
+
+            gl.TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA as GLint,
+                surface.size.width,
+                surface.size.height,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                surface.pixels.as_ptr() as *const c_void,
+            );
+            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            gl.TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl.TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_WRAP_S,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+            gl.TexParameteri(
+                gl::TEXTURE_2D,
+                gl::TEXTURE_WRAP_T,
+                gl::CLAMP_TO_EDGE as GLint,
+            );
+
+            // Restore the old bindings.
+            gl.BindTexture(gl::TEXTURE_2D, old_texture_object as GLuint);
+            if unpack_buffer != 0 {
+                gl.BindBuffer(gl::PIXEL_UNPACK_BUFFER, unpack_buffer as GLuint);
+            }
+
+            Ok(SurfaceTexture {
+                surface,
+                texture_object,
+                phantom: PhantomData,
+            })
+        })
+    }
+
+    /// Destroys a surface.
+    ///
+    /// The supplied context must be the context the surface is associated with, or this returns
+    /// an `IncompatibleSurface` error.
+    ///
+    /// You must explicitly call this method to dispose of a surface. Otherwise, a panic occurs in
+    /// the `drop` method.
+    pub fn destroy_surface(
+        &self,
+        context: &mut Context,
+        surface: &mut Surface,
+    ) -> Result<(), Error> {
+        if context.id != surface.context_id {
+            return Err(Error::IncompatibleSurface);
+        }
+
+        surface.pixels = vec![];
+        surface.destroyed = true;
+        Ok(())
+    }
+
+    /// Destroys a surface texture and returns the underlying surface.
+    ///
+    /// The supplied context must be the same context the surface texture was created with, or an
+    /// `IncompatibleSurfaceTexture` error is returned.
+    ///
+    /// All surface textures must be explicitly destroyed with this function, or a panic will
+    /// occur.
+    pub fn destroy_surface_texture(
+        &self,
+        context: &mut Context,
+        mut surface_texture: SurfaceTexture,
+    ) -> Result<Surface, (Error, SurfaceTexture)> {
+        match self.temporarily_make_context_current(context) {
+            Ok(_guard) => GL_FUNCTIONS.with(|gl| unsafe {
+                gl.DeleteTextures(1, &surface_texture.texture_object);
+                surface_texture.texture_object = 0;
+                Ok(surface_texture.surface)
+            }),
+            Err(err) => Err((err, surface_texture)),
+        }
+    }
+
+    /// Displays the contents of a widget surface on screen.
+    ///
+    /// OSMesa surfaces are never attached to widgets, so this always returns a
+    /// `NoWidgetAttached` error.
+    pub fn present_surface(&self, _: &Context, _: &mut Surface) -> Result<(), Error> {
+        Err(Error::NoWidgetAttached)
+    }
+
+    /// Resizes a surface.
+    ///
+    /// The contents of the surface are discarded. The supplied context must be the context the
+    /// surface is associated with, or this returns an `IncompatibleSurface` error.
+    pub fn resize_surface(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        if context.id != surface.context_id {
+            return Err(Error::IncompatibleSurface);
+        }
+
+        surface.pixels = vec![0; pixel_buffer_length(&size)?];
+        surface.size = size;
+        Ok(())
+    }
+
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    ///
+    /// Contents are anchored at the bottom left corner. As with `resize_surface()`, the supplied
+    /// context must be the context the surface is associated with.
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        if context.id != surface.context_id {
+            return Err(Error::IncompatibleSurface);
+        }
+
+        let mut pixels = vec![0; pixel_buffer_length(&size)?];
+        let (old_stride, new_stride) = (
+            surface.size.width as usize * BYTES_PER_PIXEL,
+            size.width as usize * BYTES_PER_PIXEL,
+        );
+        let row_length = old_stride.min(new_stride);
+        if row_length > 0 {
+            let old_rows = surface.pixels.chunks(old_stride);
+            for (old_row, new_row) in old_rows.zip(pixels.chunks_mut(new_stride)) {
+                new_row[..row_length].copy_from_slice(&old_row[..row_length]);
+            }
+        }
+
+        surface.pixels = pixels;
+        surface.size = size;
+        Ok(())
+    }
+
+    /// Returns a pointer to the underlying surface data for reading or writing by the CPU.
+    ///
+    /// The surface must have been created with CPU access, or a `SurfaceDataInaccessible` error is
+    /// returned.
+    #[inline]
+    pub fn lock_surface_data<'s>(
+        &self,
+        surface: &'s mut Surface,
+    ) -> Result<SurfaceDataGuard<'s>, Error> {
+        if !surface.access.cpu_access_allowed() {
+            return Err(Error::SurfaceDataInaccessible);
+        }
+        Ok(SurfaceDataGuard { surface })
+    }
+
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    ///
+    /// OSMesa can't render into GPU buffers, so this always returns a
+    /// `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_from_dmabuf(
+        &mut self,
+        _: &Context,
+        _: DmaBufDescriptor,
+    ) -> Result<Surface, Error> {
+        Err(Error::RequiredExtensionUnavailable)
+    }
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    ///
+    /// OSMesa can't sample from GPU buffers, so this always returns a
+    /// `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_texture_from_dmabuf(
+        &self,
+        _: &mut Context,
+        _: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture, Error> {
+        Err(Error::RequiredExtensionUnavailable)
+    }
+
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    ///
+    /// OSMesa surfaces live in CPU memory, so this always returns a
+    /// `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn export_surface_dmabuf(&self, _: &Surface) -> Result<DmaBufDescriptor, Error> {
+        Err(Error::RequiredExtensionUnavailable)
+    }
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    ///
+    /// Like `export_surface_dmabuf()`, this always returns a `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_handle(&self, surface: &Surface) -> Result<SurfaceHandle, Error> {
+        self.export_surface_dmabuf(surface)
+            .map(SurfaceHandle::from_dmabuf)
+    }
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    ///
+    /// Like `create_surface_texture_from_dmabuf()`, this always returns a
+    /// `RequiredExtensionUnavailable` error.
+    #[inline]
+    pub fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture, Error> {
+        self.create_surface_texture_from_dmabuf(context, handle.into_dmabuf())
+    }
+
+    /// Returns the OpenGL texture target needed to read from this surface texture.
+    ///
+    /// This will be `GL_TEXTURE_2D` or `GL_TEXTURE_RECTANGLE`, depending on platform.
+    #[inline]
+    pub fn surface_gl_texture_target(&self) -> GLenum {
+        SURFACE_GL_TEXTURE_TARGET
+    }
+
+    /// Returns various information about the surface, including the framebuffer object needed to
+    /// render to this surface.
+    ///
+    /// On OSMesa, surfaces are attached directly as the default framebuffer, so the framebuffer
+    /// object is always 0.
+    pub fn surface_info(&self, surface: &Surface) -> SurfaceInfo {
+        SurfaceInfo {
+            size: surface.size,
+            id: surface.id(),
+            context_id: surface.context_id,
+            framebuffer_object: 0,
+            format: SurfaceFormat::RGBA8,
+        }
+    }
+
+    /// Returns the OpenGL texture object containing the contents of this surface.
+    ///
+    /// It is only legal to read from, not write to, this texture object.
+    #[inline]
+    pub fn surface_texture_object(&self, surface_texture: &SurfaceTexture) -> GLuint {
+        surface_texture.texture_object
+    }
+}
+
+impl Surface {
+    fn id(&self) -> SurfaceID {
+        self.id
+    }
+}
+
+impl<'a> SurfaceDataGuard<'a> {
+    /// Returns the number of bytes per row of the surface.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.surface.size.width as usize * BYTES_PER_PIXEL
+    }
+
+    /// Returns a mutable slice of the pixel data in this surface, in RGBA format.
+    ///
+    /// Rows are stored bottom to top.
+    #[inline]
+    pub fn data(&mut self) -> &mut [u8] {
+        &mut self.surface.pixels[..]
+    }
+}
+
+// Returns the length in bytes of the pixel buffer of a surface of the given size, failing if the
+// size is negative or the buffer would be too large to address.
+fn pixel_buffer_length(size: &Size2D<i32>) -> Result<usize, Error> {
+    let invalid_size = Error::SurfaceCreationFailed(WindowingApiError::BadParameter);
+    if size.width < 0 || size.height < 0 {
+        return Err(invalid_size);
+    }
+    (size.width as usize)
+        .checked_mul(size.height as usize)
+        .and_then(|pixel_count| pixel_count.checked_mul(BYTES_PER_PIXEL))
+        .filter(|&length| length <= isize::MAX as usize)
+        .ok_or(invalid_size)
+}
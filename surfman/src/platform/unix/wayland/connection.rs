@@ -5,10 +5,11 @@
 use super::device::{Adapter, Device, NativeDevice};
 use super::surface::NativeWidget;
 use crate::egl;
-use crate::egl::types::{EGLAttrib, EGLDisplay};
-use crate::info::GLApi;
-use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+use crate::egl::types::EGLDisplay;
+use crate::info::{AdapterInfo, GLApi};
+use crate::platform::generic::egl::device::{self, EGL_FUNCTIONS};
 use crate::platform::generic::egl::ffi::EGL_PLATFORM_WAYLAND_KHR;
+use crate::platform::generic::egl::loader;
 use crate::Error;
 
 use euclid::default::Size2D;
@@ -26,11 +27,14 @@ use winit::window::Window;
 #[derive(Clone)]
 pub struct Connection {
     pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) gl_api: GLApi,
 }
 
 pub(crate) struct NativeConnectionWrapper {
     pub(crate) egl_display: EGLDisplay,
-    wayland_display: Option<*mut wl_display>,
+    // This is null if the connection was created from an EGL display.
+    pub(crate) wayland_display: *mut wl_display,
+    wayland_display_is_owned: bool,
 }
 
 /// An EGL display wrapping a Wayland display.
@@ -40,6 +44,8 @@ unsafe impl Send for Connection {}
 
 impl Connection {
     /// Connects to the default Wayland server.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded.
     #[inline]
     pub fn new() -> Result<Connection, Error> {
         unsafe {
@@ -53,10 +59,14 @@ impl Connection {
     /// The display is not retained, as there is no way to do this in the EGL API. Therefore, it is
     /// the caller's responsibility to ensure that the EGL display remains alive as long as the
     /// connection is.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded.
     pub unsafe fn from_native_connection(
         native_connection: NativeConnection,
     ) -> Result<Connection, Error> {
-        Connection::from_egl_display(native_connection.0, None)
+        loader::egl_library_loaded()?;
+        device::retain_display(native_connection.0);
+        Connection::from_egl_display(native_connection.0, ptr::null_mut(), false)
     }
 
     /// Returns the underlying native connection.
@@ -66,9 +76,24 @@ impl Connection {
     }
 
     /// Returns the OpenGL API flavor that this connection supports (OpenGL or OpenGL ES).
+    ///
+    /// This is OpenGL unless another API was selected with `with_gl_api()`.
     #[inline]
     pub fn gl_api(&self) -> GLApi {
-        GLApi::GL
+        self.gl_api
+    }
+
+    /// Returns a copy of this connection that creates contexts with the given OpenGL API flavor.
+    ///
+    /// Devices opened on the returned connection report this API from `Device::gl_api()` and use
+    /// it for context descriptors created with `Device::create_context_descriptor()`. This makes
+    /// it possible to run OpenGL ES code on desktop drivers as it would run on mobile platforms.
+    #[inline]
+    pub fn with_gl_api(&self, gl_api: GLApi) -> Connection {
+        Connection {
+            gl_api,
+            ..self.clone()
+        }
     }
 
     /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
@@ -80,21 +105,51 @@ impl Connection {
     }
 
     /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
+    ///
+    /// The adapter is selected per EGL display via `EGL_EXT_explicit_device`. If that extension
+    /// is unavailable, this returns the default adapter, which renders with whichever device the
+    /// default EGL display of this connection uses.
     #[inline]
     pub fn create_hardware_adapter(&self) -> Result<Adapter, Error> {
+        if self.native_connection.wayland_display.is_null() || !Adapter::explicit_device_supported()
+        {
+            return Ok(Adapter::Default);
+        }
         Ok(Adapter::hardware())
     }
 
     /// Returns the "best" adapter on this system, preferring low-power hardware adapters.
+    ///
+    /// The adapter is selected per EGL display via `EGL_EXT_explicit_device`. If that extension
+    /// is unavailable, this returns the default adapter, which renders with whichever device the
+    /// default EGL display of this connection uses.
     #[inline]
     pub fn create_low_power_adapter(&self) -> Result<Adapter, Error> {
+        if self.native_connection.wayland_display.is_null() || !Adapter::explicit_device_supported()
+        {
+            return Ok(Adapter::Default);
+        }
         Ok(Adapter::low_power())
     }
 
     /// Returns the "best" adapter on this system, preferring software adapters.
+    ///
+    /// The adapter is selected per EGL display via `EGL_EXT_explicit_device`. If that extension
+    /// is unavailable, or if there is no software rasterizer EGL device, this returns a
+    /// `RequiredExtensionUnavailable` error.
     #[inline]
     pub fn create_software_adapter(&self) -> Result<Adapter, Error> {
-        Ok(Adapter::software())
+        if self.native_connection.wayland_display.is_null() || !Adapter::explicit_device_supported()
+        {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+        Adapter::software()
+    }
+
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
     }
 
     /// Opens the hardware device corresponding to the given adapter.
@@ -125,39 +180,29 @@ impl Connection {
             return Err(Error::ConnectionFailed);
         }
 
-        EGL_FUNCTIONS.with(|egl| {
-            let display_attributes = [egl::NONE as EGLAttrib];
-            let egl_display = egl.GetPlatformDisplay(
-                EGL_PLATFORM_WAYLAND_KHR,
-                wayland_display as *mut c_void,
-                display_attributes.as_ptr(),
-            );
-            if egl_display == egl::NO_DISPLAY {
-                return Err(Error::DeviceOpenFailed);
+        match create_egl_display(wayland_display) {
+            Ok(egl_display) => Connection::from_egl_display(egl_display, wayland_display, is_owned),
+            Err(err) => {
+                if is_owned {
+                    (WAYLAND_CLIENT_HANDLE.wl_display_disconnect)(wayland_display);
+                }
+                Err(err)
             }
-
-            let (mut egl_major_version, mut egl_minor_version) = (0, 0);
-            let ok = egl.Initialize(egl_display, &mut egl_major_version, &mut egl_minor_version);
-            assert_ne!(ok, egl::FALSE);
-
-            let owned_display = if is_owned {
-                Some(wayland_display)
-            } else {
-                None
-            };
-            Connection::from_egl_display(egl_display, owned_display)
-        })
+        }
     }
 
     fn from_egl_display(
         egl_display: EGLDisplay,
-        wayland_display: Option<*mut wl_display>,
+        wayland_display: *mut wl_display,
+        wayland_display_is_owned: bool,
     ) -> Result<Connection, Error> {
         Ok(Connection {
             native_connection: Arc::new(NativeConnectionWrapper {
                 egl_display,
                 wayland_display,
+                wayland_display_is_owned,
             }),
+            gl_api: GLApi::GL,
         })
     }
 
@@ -256,8 +301,10 @@ impl Connection {
 impl Drop for NativeConnectionWrapper {
     fn drop(&mut self) {
         unsafe {
-            if let Some(wayland_display) = self.wayland_display {
-                (WAYLAND_CLIENT_HANDLE.wl_display_disconnect)(wayland_display);
+            // The EGL display refers to the Wayland display, so it must be terminated first.
+            device::release_display(self.egl_display);
+            if self.wayland_display_is_owned {
+                (WAYLAND_CLIENT_HANDLE.wl_display_disconnect)(self.wayland_display);
             }
         }
     }
@@ -279,3 +326,18 @@ impl NativeConnection {
         }
     }
 }
+
+unsafe fn create_egl_display(wayland_display: *mut wl_display) -> Result<EGLDisplay, Error> {
+    loader::egl_library_loaded()?;
+    let egl_display = device::get_platform_display(
+        EGL_PLATFORM_WAYLAND_KHR,
+        wayland_display as *mut c_void,
+        &[],
+    );
+    if egl_display == egl::NO_DISPLAY {
+        return Err(Error::DeviceOpenFailed);
+    }
+
+    device::initialize_display(egl_display)?;
+    Ok(egl_display)
+}
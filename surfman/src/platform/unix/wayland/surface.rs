@@ -7,15 +7,21 @@ use super::device::Device;
 use crate::gl;
 use crate::gl::types::{GLenum, GLuint};
 use crate::platform::generic::egl::context;
-use crate::platform::generic::egl::surface::{EGLBackedSurface, EGLSurfaceTexture};
-use crate::{Error, SurfaceAccess, SurfaceInfo, SurfaceType};
+use crate::platform::generic::egl::ffi::EGL_PLATFORM_WAYLAND_KHR;
+use crate::platform::generic::egl::surface::{
+    self, EGLBackedSurface, EGLSurfaceDataGuard, EGLSurfaceTexture,
+};
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceInfo, SurfaceType};
 
 use euclid::default::Size2D;
-use std::marker::PhantomData;
+use std::mem;
 use std::os::raw::c_void;
 use wayland_sys::client::wl_proxy;
 use wayland_sys::egl::{wl_egl_window, WAYLAND_EGL_HANDLE};
 
+pub use crate::platform::generic::egl::handle::SurfaceHandle;
+pub use crate::platform::generic::egl::surface::{DmaBufDescriptor, DmaBufPlane};
+
 // FIXME(pcwalton): Is this right, or should it be `TEXTURE_EXTERNAL_OES`?
 const SURFACE_GL_TEXTURE_TARGET: GLenum = gl::TEXTURE_2D;
 
@@ -67,11 +73,13 @@ impl Device {
     pub fn create_surface(
         &mut self,
         context: &Context,
-        _: SurfaceAccess,
+        access: SurfaceAccess,
         surface_type: SurfaceType<NativeWidget>,
     ) -> Result<Surface, Error> {
         match surface_type {
-            SurfaceType::Generic { size } => self.create_generic_surface(context, &size),
+            SurfaceType::Generic { size, format } => {
+                self.create_generic_surface(context, access, &size, format)
+            }
             SurfaceType::Widget { native_widget } => unsafe {
                 self.create_window_surface(
                     context,
@@ -83,22 +91,28 @@ impl Device {
     }
 
     fn create_generic_surface(
-        &mut self,
+        &self,
         context: &Context,
+        access: SurfaceAccess,
         size: &Size2D<i32>,
+        format: SurfaceFormat,
     ) -> Result<Surface, Error> {
         let _guard = self.temporarily_make_context_current(context)?;
         let context_descriptor = self.context_descriptor(context);
         let context_attributes = self.context_descriptor_attributes(&context_descriptor);
         GL_FUNCTIONS.with(|gl| {
-            Ok(Surface(EGLBackedSurface::new_generic(
+            EGLBackedSurface::new_generic(
                 gl,
-                self.native_connection.egl_display,
+                self.egl_display,
                 context.0.egl_context,
                 context.0.id,
                 &context_attributes,
                 size,
-            )))
+                access,
+                format,
+                self.supports_texture_images,
+            )
+            .map(Surface)
         })
     }
 
@@ -113,18 +127,18 @@ impl Device {
         assert!(!egl_window.is_null());
 
         let context_descriptor = self.context_descriptor(context);
-        let egl_config = context::egl_config_from_id(
-            self.native_connection.egl_display,
-            context_descriptor.egl_config_id,
-        );
+        let egl_config =
+            context::egl_config_from_id(self.egl_display, context_descriptor.egl_config_id);
 
-        Ok(Surface(EGLBackedSurface::new_window(
-            self.native_connection.egl_display,
+        EGLBackedSurface::new_window(
+            self.egl_display,
             egl_config,
+            EGL_PLATFORM_WAYLAND_KHR,
             egl_window as *mut c_void,
             context.0.id,
             size,
-        )))
+        )
+        .map(Surface)
     }
 
     /// Creates a surface texture from an existing generic surface for use with the given context.
@@ -136,6 +150,9 @@ impl Device {
     /// with.* This allows you to render to a surface in one context and sample from that surface
     /// in another context.
     ///
+    /// If the driver can't wrap textures in EGL images, the surface texture is a copy of the
+    /// contents of the surface as of the last time it was unbound from its context.
+    ///
     /// Calling this method on a widget surface returns a `WidgetAttached` error.
     pub fn create_surface_texture(
         &self,
@@ -153,6 +170,90 @@ impl Device {
         })
     }
 
+    /// Creates a generic surface that renders into the given DMA-BUF buffer.
+    ///
+    /// This requires the `EGL_EXT_image_dma_buf_import` extension, as well as
+    /// `EGL_EXT_image_dma_buf_import_modifiers` if the buffer has an explicit modifier. The file
+    /// descriptors in the descriptor are closed once the buffer has been imported.
+    pub fn create_surface_from_dmabuf(
+        &mut self,
+        context: &Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<Surface, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        let context_descriptor = self.context_descriptor(context);
+        let context_attributes = self.context_descriptor_attributes(&context_descriptor);
+        unsafe {
+            let egl_image = surface::create_dmabuf_egl_image(self.egl_display, &descriptor)?;
+            GL_FUNCTIONS.with(|gl| {
+                let surface = EGLBackedSurface::new_from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &context_attributes,
+                    &descriptor.size,
+                    SurfaceAccess::GPUOnly,
+                    surface::surface_format_for_drm_fourcc(descriptor.fourcc),
+                )?;
+                Ok(Surface(surface))
+            })
+        }
+    }
+
+    /// Creates a surface texture that reads from the given DMA-BUF buffer.
+    ///
+    /// This is lighter than `create_surface_from_dmabuf()` followed by `create_surface_texture()`,
+    /// as no framebuffer is created. The surface returned by `destroy_surface_texture()` can't be
+    /// rendered to, so binding it to a context returns a `SurfaceNotRenderable` error. It should
+    /// simply be destroyed.
+    ///
+    /// The same extensions as `create_surface_from_dmabuf()` are required.
+    pub fn create_surface_texture_from_dmabuf(
+        &self,
+        context: &mut Context,
+        descriptor: DmaBufDescriptor,
+    ) -> Result<SurfaceTexture, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        unsafe {
+            let egl_image = surface::create_dmabuf_egl_image(self.egl_display, &descriptor)?;
+            GL_FUNCTIONS.with(|gl| {
+                let surface_texture = EGLSurfaceTexture::from_egl_image(
+                    gl,
+                    egl_image,
+                    context.0.id,
+                    &descriptor.size,
+                    surface::surface_format_for_drm_fourcc(descriptor.fourcc),
+                )?;
+                Ok(SurfaceTexture(surface_texture))
+            })
+        }
+    }
+
+    /// Creates a handle that allows another process to sample from the given surface.
+    ///
+    /// Send the handle over a Unix domain socket with `SurfaceHandle::send()`, and open it in the
+    /// receiving process with `create_surface_texture_from_handle()`. See the `SurfaceHandle`
+    /// documentation for the rules governing the lifetime of the shared buffer.
+    ///
+    /// The same extensions as `export_surface_dmabuf()` are required.
+    #[inline]
+    pub fn create_surface_handle(&self, surface: &Surface) -> Result<SurfaceHandle, Error> {
+        self.export_surface_dmabuf(surface)
+            .map(SurfaceHandle::from_dmabuf)
+    }
+
+    /// Opens a surface handle, usually received from another process, as a surface texture.
+    ///
+    /// The same extensions as `create_surface_texture_from_dmabuf()` are required.
+    #[inline]
+    pub fn create_surface_texture_from_handle(
+        &self,
+        context: &mut Context,
+        handle: SurfaceHandle,
+    ) -> Result<SurfaceTexture, Error> {
+        self.create_surface_texture_from_dmabuf(context, handle.into_dmabuf())
+    }
+
     /// Destroys a surface.
     ///
     /// The supplied context must be the context the surface is associated with, or this returns
@@ -166,7 +267,7 @@ impl Device {
         surface: &mut Surface,
     ) -> Result<(), Error> {
         GL_FUNCTIONS.with(|gl| {
-            let egl_display = self.native_connection.egl_display;
+            let egl_display = self.egl_display;
             if let Some(wayland_egl_window) = surface.0.destroy(gl, egl_display, context.0.id)? {
                 unsafe {
                     let wayland_egl_window = wayland_egl_window as *mut wl_egl_window;
@@ -203,36 +304,111 @@ impl Device {
     /// The supplied context must match the context the surface was created with, or an
     /// `IncompatibleSurface` error is returned.
     pub fn present_surface(&self, context: &Context, surface: &mut Surface) -> Result<(), Error> {
-        surface
-            .0
-            .present(self.native_connection.egl_display, context.0.egl_context)
+        surface.0.present(self.egl_display, context.0.egl_context)
     }
 
-    /// Resizes a widget surface.
+    /// Resizes a surface.
+    ///
+    /// Generic surfaces are reallocated at the new size, which discards their contents and
+    /// changes their ID. Use `resize_surface_preserving_contents()` to keep the contents.
+    ///
+    /// Widget surfaces resize their Wayland EGL window.
+    ///
+    /// The supplied context must be the context the surface is associated with, or this returns
+    /// an `IncompatibleSurface` error.
     pub fn resize_surface(
         &self,
-        _context: &Context,
+        context: &Context,
         surface: &mut Surface,
         size: Size2D<i32>,
     ) -> Result<(), Error> {
-        let wayland_egl_window = surface.0.native_window()? as *mut c_void as *mut wl_egl_window;
-        unsafe {
-            (WAYLAND_EGL_HANDLE.wl_egl_window_resize)(
-                wayland_egl_window,
-                size.width,
-                size.height,
-                0,
-                0,
-            )
-        };
-        surface.0.size = size;
-        Ok(())
+        self.reallocate_surface(context, surface, size, false)
+    }
+
+    /// Resizes a surface, keeping the part of its contents that fits within the new size.
+    ///
+    /// Contents are anchored at the bottom left corner. Only the color contents are preserved;
+    /// depth and stencil buffers are cleared. Widget surfaces are resized as with
+    /// `resize_surface()`.
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.reallocate_surface(context, surface, size, true)
+    }
+
+    fn reallocate_surface(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+        preserve_contents: bool,
+    ) -> Result<(), Error> {
+        if surface.0.context_id != context.0.id {
+            return Err(Error::IncompatibleSurface);
+        }
+        if let Ok(native_window) = surface.0.native_window() {
+            let wayland_egl_window = native_window as *mut c_void as *mut wl_egl_window;
+            unsafe {
+                (WAYLAND_EGL_HANDLE.wl_egl_window_resize)(
+                    wayland_egl_window,
+                    size.width,
+                    size.height,
+                    0,
+                    0,
+                )
+            };
+            surface.0.size = size;
+            return Ok(());
+        }
+
+        let _guard = self.temporarily_make_context_current(context)?;
+        let mut new_surface =
+            self.create_generic_surface(context, surface.0.access, &size, surface.0.format)?;
+        GL_FUNCTIONS.with(|gl| {
+            if preserve_contents {
+                unsafe {
+                    new_surface
+                        .0
+                        .copy_contents_from(gl, self.egl_display, &surface.0)
+                };
+            }
+            mem::swap(surface, &mut new_surface);
+            let window = new_surface.0.destroy(gl, self.egl_display, context.0.id)?;
+            debug_assert!(window.is_none());
+            Ok(())
+        })
     }
 
     /// Returns a pointer to the underlying surface data for reading or writing by the CPU.
-    #[inline]
-    pub fn lock_surface_data<'s>(&self, _: &'s mut Surface) -> Result<SurfaceDataGuard<'s>, Error> {
-        Err(Error::Unimplemented)
+    ///
+    /// The surface must have been created with CPU access, or a `SurfaceDataInaccessible` error is
+    /// returned. Only `RGBA8` surfaces can be accessed; other formats return an
+    /// `UnsupportedSurfaceFormat` error. If the driver can export the surface as a linear
+    /// DMA-BUF, its memory is mapped directly. Otherwise, the data is copied into a pixel buffer
+    /// object, which requires a context on this device to be current, and copied back when the
+    /// guard is dropped.
+    pub fn lock_surface_data<'s>(
+        &self,
+        surface: &'s mut Surface,
+    ) -> Result<SurfaceDataGuard<'s>, Error> {
+        GL_FUNCTIONS.with(move |gl| unsafe {
+            let guard = surface.0.lock_data(gl, self.egl_display)?;
+            Ok(SurfaceDataGuard(guard))
+        })
+    }
+
+    /// Exports the contents of a generic surface as DMA-BUF file descriptors.
+    ///
+    /// This requires the `EGL_MESA_image_dma_buf_export` extension. The file descriptors refer to
+    /// the memory of the surface itself, so no copy is made. Rendering commands targeting the
+    /// surface should be flushed first, for example by unbinding it from its context.
+    ///
+    /// Calling this method on a widget surface returns a `WidgetAttached` error.
+    pub fn export_surface_dmabuf(&self, surface: &Surface) -> Result<DmaBufDescriptor, Error> {
+        unsafe { surface.0.export_dmabuf(self.egl_display) }
     }
 
     /// Returns the OpenGL texture target needed to read from this surface texture.
@@ -263,6 +439,26 @@ impl Device {
 }
 
 /// Represents the CPU view of the pixel data of this surface.
-pub struct SurfaceDataGuard<'a> {
-    phantom: PhantomData<&'a ()>,
+pub struct SurfaceDataGuard<'a>(EGLSurfaceDataGuard<'a>);
+
+impl<'a> SurfaceDataGuard<'a> {
+    /// Returns the number of bytes per row of the surface.
+    #[inline]
+    pub fn stride(&self) -> usize {
+        self.0.stride()
+    }
+
+    /// Returns a mutable slice of the pixel data in this surface, in RGBA format.
+    ///
+    /// Rows are stored bottom to top.
+    #[inline]
+    pub fn data(&mut self) -> &mut [u8] {
+        self.0.data()
+    }
+}
+
+impl<'a> Drop for SurfaceDataGuard<'a> {
+    fn drop(&mut self) {
+        GL_FUNCTIONS.with(|gl| unsafe { self.0.unlock(gl) })
+    }
 }
@@ -5,11 +5,12 @@
 use super::device::{Device, NativeDevice};
 use super::surface::NativeWidget;
 use crate::egl;
-use crate::egl::types::{EGLAttrib, EGLDisplay};
+use crate::egl::types::EGLDisplay;
 use crate::error::Error;
-use crate::info::GLApi;
-use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+use crate::info::{AdapterInfo, GLApi};
+use crate::platform::generic::egl::device;
 use crate::platform::generic::egl::ffi::EGL_PLATFORM_X11_KHR;
+use crate::platform::generic::egl::loader;
 use crate::platform::unix::generic::device::Adapter;
 
 use euclid::default::Size2D;
@@ -37,13 +38,14 @@ lazy_static! {
 #[derive(Clone)]
 pub struct Connection {
     pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) gl_api: GLApi,
 }
 
 unsafe impl Send for Connection {}
 
 pub(crate) struct NativeConnectionWrapper {
     pub(crate) egl_display: EGLDisplay,
-    x11_display: *mut Display,
+    pub(crate) x11_display: *mut Display,
     x11_display_is_owned: bool,
 }
 
@@ -64,6 +66,8 @@ impl Drop for NativeConnectionWrapper {
     #[inline]
     fn drop(&mut self) {
         unsafe {
+            // The EGL display refers to the X11 display, so it must be terminated first.
+            device::release_display(self.egl_display);
             if self.x11_display_is_owned {
                 XCloseDisplay(self.x11_display);
             }
@@ -74,6 +78,8 @@ impl Drop for NativeConnectionWrapper {
 
 impl Connection {
     /// Connects to the default display.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded.
     #[inline]
     pub fn new() -> Result<Connection, Error> {
         unsafe {
@@ -84,15 +90,7 @@ impl Connection {
                 return Err(Error::ConnectionFailed);
             }
 
-            let egl_display = create_egl_display(x11_display);
-
-            Ok(Connection {
-                native_connection: Arc::new(NativeConnectionWrapper {
-                    x11_display,
-                    x11_display_is_owned: true,
-                    egl_display,
-                }),
-            })
+            Connection::from_x11_display(x11_display, true)
         }
     }
 
@@ -105,28 +103,42 @@ impl Connection {
     /// The display is not retained, as there is no way to do that in the X11 API. Therefore, it is
     /// the caller's responsibility to ensure that the display connection is not closed before this
     /// `Connection` object is disposed of.
+    ///
+    /// Returns `NoGLLibraryFound` if the EGL library couldn't be loaded.
     #[inline]
     pub unsafe fn from_native_connection(
         native_connection: NativeConnection,
     ) -> Result<Connection, Error> {
+        loader::egl_library_loaded()?;
+        device::retain_display(native_connection.egl_display);
         Ok(Connection {
             native_connection: Arc::new(NativeConnectionWrapper {
                 egl_display: native_connection.egl_display,
                 x11_display: native_connection.x11_display,
                 x11_display_is_owned: false,
             }),
+            gl_api: GLApi::GL,
         })
     }
 
     fn from_x11_display(x11_display: *mut Display, is_owned: bool) -> Result<Connection, Error> {
         unsafe {
-            let egl_display = create_egl_display(x11_display);
+            let egl_display = match create_egl_display(x11_display) {
+                Ok(egl_display) => egl_display,
+                Err(err) => {
+                    if is_owned {
+                        XCloseDisplay(x11_display);
+                    }
+                    return Err(err);
+                }
+            };
             Ok(Connection {
                 native_connection: Arc::new(NativeConnectionWrapper {
                     egl_display,
                     x11_display,
                     x11_display_is_owned: is_owned,
                 }),
+                gl_api: GLApi::GL,
             })
         }
     }
@@ -141,9 +153,24 @@ impl Connection {
     }
 
     /// Returns the OpenGL API flavor that this connection supports (OpenGL or OpenGL ES).
+    ///
+    /// This is OpenGL unless another API was selected with `with_gl_api()`.
     #[inline]
     pub fn gl_api(&self) -> GLApi {
-        GLApi::GL
+        self.gl_api
+    }
+
+    /// Returns a copy of this connection that creates contexts with the given OpenGL API flavor.
+    ///
+    /// Devices opened on the returned connection report this API from `Device::gl_api()` and use
+    /// it for context descriptors created with `Device::create_context_descriptor()`. This makes
+    /// it possible to run OpenGL ES code on desktop drivers as it would run on mobile platforms.
+    #[inline]
+    pub fn with_gl_api(&self, gl_api: GLApi) -> Connection {
+        Connection {
+            gl_api,
+            ..self.clone()
+        }
     }
 
     /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
@@ -155,21 +182,48 @@ impl Connection {
     }
 
     /// Returns the "best" adapter on this system, preferring high-performance hardware adapters.
+    ///
+    /// The adapter is selected per EGL display via `EGL_EXT_explicit_device`. If that extension
+    /// is unavailable, this returns the default adapter, which renders with whichever device the
+    /// default EGL display of this connection uses.
     #[inline]
     pub fn create_hardware_adapter(&self) -> Result<Adapter, Error> {
+        if !Adapter::explicit_device_supported() {
+            return Ok(Adapter::Default);
+        }
         Ok(Adapter::hardware())
     }
 
     /// Returns the "best" adapter on this system, preferring low-power hardware adapters.
+    ///
+    /// The adapter is selected per EGL display via `EGL_EXT_explicit_device`. If that extension
+    /// is unavailable, this returns the default adapter, which renders with whichever device the
+    /// default EGL display of this connection uses.
     #[inline]
     pub fn create_low_power_adapter(&self) -> Result<Adapter, Error> {
+        if !Adapter::explicit_device_supported() {
+            return Ok(Adapter::Default);
+        }
         Ok(Adapter::low_power())
     }
 
     /// Returns the "best" adapter on this system, preferring software adapters.
+    ///
+    /// The adapter is selected per EGL display via `EGL_EXT_explicit_device`. If that extension
+    /// is unavailable, or if there is no software rasterizer EGL device, this returns a
+    /// `RequiredExtensionUnavailable` error.
     #[inline]
     pub fn create_software_adapter(&self) -> Result<Adapter, Error> {
-        Ok(Adapter::software())
+        if !Adapter::explicit_device_supported() {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+        Adapter::software()
+    }
+
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
     }
 
     /// Opens the hardware device corresponding to the given adapter.
@@ -295,19 +349,14 @@ impl<'a> DisplayGuard<'a> {
     }
 }
 
-unsafe fn create_egl_display(display: *mut Display) -> EGLDisplay {
-    EGL_FUNCTIONS.with(|egl| {
-        let display_attributes = [egl::NONE as EGLAttrib];
-        let egl_display = egl.GetPlatformDisplay(
-            EGL_PLATFORM_X11_KHR,
-            display as *mut c_void,
-            display_attributes.as_ptr(),
-        );
-
-        let (mut egl_major_version, mut egl_minor_version) = (0, 0);
-        let ok = egl.Initialize(egl_display, &mut egl_major_version, &mut egl_minor_version);
-        assert_ne!(ok, egl::FALSE);
-
-        egl_display
-    })
+unsafe fn create_egl_display(display: *mut Display) -> Result<EGLDisplay, Error> {
+    loader::egl_library_loaded()?;
+    let egl_display =
+        device::get_platform_display(EGL_PLATFORM_X11_KHR, display as *mut c_void, &[]);
+    if egl_display == egl::NO_DISPLAY {
+        return Err(Error::ConnectionFailed);
+    }
+
+    device::initialize_display(egl_display)?;
+    Ok(egl_display)
 }
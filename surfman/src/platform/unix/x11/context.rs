@@ -8,12 +8,17 @@ use crate::context::ContextID;
 use crate::egl;
 use crate::egl::types::EGLint;
 use crate::platform::generic::egl::context::{self, CurrentContextGuard, EGLBackedContext};
-use crate::{ContextAttributes, Error, Gl, SurfaceInfo};
+use crate::{ContextAttributes, ContextResetStatus, Error, GLApi, Gl, SurfaceInfo};
+use crate::{ContextNegotiation, ContextNegotiationReport};
+use crate::{PixelFormatInfo, PixelFormatPreferences};
 
 use std::os::raw::c_void;
 
 pub use crate::platform::generic::egl::context::{ContextDescriptor, NativeContext};
 
+// Contexts must be able to render to X11 windows.
+const CONFIG_ATTRIBUTES: [EGLint; 2] = [egl::SURFACE_TYPE as EGLint, egl::WINDOW_BIT as EGLint];
+
 thread_local! {
     #[doc(hidden)]
     pub static GL_FUNCTIONS: Gl = Gl::load_with(context::get_proc_address);
@@ -40,29 +45,81 @@ pub struct Context(pub(crate) EGLBackedContext);
 impl Device {
     /// Creates a context descriptor with the given attributes.
     ///
-    /// Context descriptors are local to this device.
+    /// Context descriptors are local to this device. Contexts created from the descriptor use the
+    /// OpenGL API flavor of this device.
     #[inline]
     pub fn create_context_descriptor(
         &self,
         attributes: &ContextAttributes,
     ) -> Result<ContextDescriptor, Error> {
-        // Set environment variables as appropriate.
-        self.adapter.set_environment_variables();
+        self.create_context_descriptor_with_gl_api(attributes, self.gl_api())
+    }
+
+    /// Creates a context descriptor with the given attributes for the given OpenGL API flavor.
+    ///
+    /// This allows OpenGL and OpenGL ES contexts to be mixed on a single device. The version in
+    /// `attributes` is interpreted according to `gl_api`. OpenGL ES has no compatibility profile,
+    /// so requesting one returns an `UnsupportedGLProfile` error.
+    pub fn create_context_descriptor_with_gl_api(
+        &self,
+        attributes: &ContextAttributes,
+        gl_api: GLApi,
+    ) -> Result<ContextDescriptor, Error> {
+        unsafe { ContextDescriptor::new(self.egl_display, attributes, gl_api, &CONFIG_ATTRIBUTES) }
+    }
+
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// The descriptors are in the order that EGL sorts their configs in, which isn't necessarily
+    /// the order that `create_context_descriptor()` prefers. Context descriptors are local to this
+    /// device.
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor>, Error> {
+        unsafe {
+            ContextDescriptor::enumerate(
+                self.egl_display,
+                attributes,
+                self.gl_api(),
+                &CONFIG_ATTRIBUTES,
+            )
+        }
+    }
 
+    /// Creates a context descriptor with the given attributes, choosing the pixel format that
+    /// ranks highest according to the given preferences.
+    ///
+    /// Context descriptors are local to this device.
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        preferences: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor, Error> {
         unsafe {
-            ContextDescriptor::new(
-                self.native_connection.egl_display,
+            ContextDescriptor::choose(
+                self.egl_display,
                 attributes,
-                &[
-                    egl::SURFACE_TYPE as EGLint,
-                    egl::WINDOW_BIT as EGLint,
-                    egl::RENDERABLE_TYPE as EGLint,
-                    egl::OPENGL_BIT as EGLint,
-                ],
+                self.gl_api(),
+                &CONFIG_ATTRIBUTES,
+                preferences,
             )
         }
     }
 
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    #[inline]
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor, ContextNegotiationReport), Error> {
+        crate::context::negotiate_context_descriptor(self, negotiation)
+    }
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -73,15 +130,16 @@ impl Device {
         descriptor: &ContextDescriptor,
         share_with: Option<&Context>,
     ) -> Result<Context, Error> {
-        unsafe {
+        GL_FUNCTIONS.with(|gl| unsafe {
             EGLBackedContext::new(
-                self.native_connection.egl_display,
+                gl,
+                self.egl_display,
                 descriptor,
                 share_with.map(|ctx| &ctx.0),
-                self.gl_api(),
+                &self.debug_sink,
             )
             .map(Context)
-        }
+        })
     }
 
     /// Wraps an `EGLContext` in a native context and returns it.
@@ -95,6 +153,7 @@ impl Device {
         native_context: NativeContext,
     ) -> Result<Context, Error> {
         Ok(Context(EGLBackedContext::from_native_context(
+            self.egl_display,
             native_context,
         )))
     }
@@ -108,7 +167,7 @@ impl Device {
         }
 
         unsafe {
-            context.0.destroy(self.native_connection.egl_display);
+            context.0.destroy(self.egl_display);
             Ok(())
         }
     }
@@ -122,13 +181,7 @@ impl Device {
     /// Returns the descriptor that this context was created with.
     #[inline]
     pub fn context_descriptor(&self, context: &Context) -> ContextDescriptor {
-        GL_FUNCTIONS.with(|gl| unsafe {
-            ContextDescriptor::from_egl_context(
-                gl,
-                self.native_connection.egl_display,
-                context.0.egl_context,
-            )
-        })
+        GL_FUNCTIONS.with(|gl| unsafe { context.0.descriptor(gl, self.egl_display) })
     }
 
     /// Makes the context the current OpenGL context for this thread.
@@ -136,7 +189,7 @@ impl Device {
     /// After calling this function, it is valid to use OpenGL rendering commands.
     #[inline]
     pub fn make_context_current(&self, context: &Context) -> Result<(), Error> {
-        unsafe { context.0.make_current(self.native_connection.egl_display) }
+        unsafe { context.0.make_current(self.egl_display) }
     }
 
     /// Removes the current OpenGL context from this thread.
@@ -145,7 +198,19 @@ impl Device {
     /// made current.
     #[inline]
     pub fn make_no_context_current(&self) -> Result<(), Error> {
-        unsafe { context::make_no_context_current(self.native_connection.egl_display) }
+        unsafe { context::make_no_context_current(self.egl_display) }
+    }
+
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated. If the driver lacks
+    /// the `glGetGraphicsResetStatus()` function, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    ///
+    /// The context is briefly made current in order to query its status.
+    pub fn context_reset_status(&self, context: &Context) -> Result<ContextResetStatus, Error> {
+        unsafe { context.0.reset_status(self.egl_display) }
     }
 
     #[inline]
@@ -164,7 +229,22 @@ impl Device {
         &self,
         context_descriptor: &ContextDescriptor,
     ) -> ContextAttributes {
-        unsafe { context_descriptor.attributes(self.native_connection.egl_display) }
+        unsafe { context_descriptor.attributes(self.egl_display) }
+    }
+
+    /// Returns the properties of the EGL config that the context descriptor selects.
+    #[inline]
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> PixelFormatInfo {
+        unsafe { context_descriptor.pixel_format(self.egl_display) }
+    }
+
+    /// Returns the OpenGL API flavor that contexts created from the given descriptor use.
+    #[inline]
+    pub fn context_descriptor_gl_api(&self, context_descriptor: &ContextDescriptor) -> GLApi {
+        context_descriptor.gl_api
     }
 
     /// Fetches the address of an OpenGL function associated with this context.
@@ -198,7 +278,7 @@ impl Device {
         unsafe {
             context
                 .0
-                .bind_surface(self.native_connection.egl_display, surface.0)
+                .bind_surface(self.egl_display, surface.0)
                 .map_err(|(err, surface)| (err, Surface(surface)))
         }
     }
@@ -218,7 +298,7 @@ impl Device {
 
                 context
                     .0
-                    .unbind_surface(gl, self.native_connection.egl_display)
+                    .unbind_surface(gl, self.egl_display)
                     .map(|maybe_surface| maybe_surface.map(Surface))
             }
         })
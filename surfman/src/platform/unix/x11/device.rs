@@ -3,8 +3,14 @@
 //! A wrapper around X11 `EGLDisplay`s.
 
 use super::connection::{Connection, NativeConnectionWrapper};
-use crate::{Error, GLApi};
+use crate::debug::DebugSink;
+use crate::egl::types::EGLDisplay;
+use crate::platform::generic::egl::debug as egl_debug;
+use crate::platform::generic::egl::device;
+use crate::platform::generic::egl::ffi::EGL_PLATFORM_X11_KHR;
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
 
+use std::os::raw::c_void;
 use std::sync::Arc;
 
 pub use crate::platform::unix::generic::device::Adapter;
@@ -14,7 +20,12 @@ pub use crate::platform::unix::generic::device::Adapter;
 /// Devices contain most of the relevant surface management methods.
 pub struct Device {
     pub(crate) native_connection: Arc<NativeConnectionWrapper>,
+    pub(crate) egl_display: EGLDisplay,
     pub(crate) adapter: Adapter,
+    pub(crate) gl_api: GLApi,
+    pub(crate) debug_sink: Arc<DebugSink>,
+    // Whether generic surfaces can be shared via EGL images instead of by copying.
+    pub(crate) supports_texture_images: bool,
 }
 
 /// Wraps an adapter.
@@ -26,12 +37,34 @@ pub struct NativeDevice {
     pub adapter: Adapter,
 }
 
+impl Drop for Device {
+    fn drop(&mut self) {
+        unsafe { device::release_display(self.egl_display) }
+    }
+}
+
 impl Device {
     #[inline]
     pub(crate) fn new(connection: &Connection, adapter: &Adapter) -> Result<Device, Error> {
+        let native_connection = &connection.native_connection;
+        let egl_display = unsafe {
+            adapter.create_egl_display(
+                EGL_PLATFORM_X11_KHR,
+                native_connection.x11_display as *mut c_void,
+            )?
+        };
+
+        let egl_display = egl_display.unwrap_or_else(|| {
+            device::retain_display(native_connection.egl_display);
+            native_connection.egl_display
+        });
         Ok(Device {
-            native_connection: connection.native_connection.clone(),
+            native_connection: native_connection.clone(),
+            egl_display,
             adapter: (*adapter).clone(),
+            gl_api: connection.gl_api,
+            debug_sink: DebugSink::new(),
+            supports_texture_images: device::supports_texture_images(egl_display),
         })
     }
 
@@ -51,6 +84,7 @@ impl Device {
     pub fn connection(&self) -> Connection {
         Connection {
             native_connection: self.native_connection.clone(),
+            gl_api: self.gl_api,
         }
     }
 
@@ -61,8 +95,51 @@ impl Device {
     }
 
     /// Returns the OpenGL API flavor that this device supports (OpenGL or OpenGL ES).
+    ///
+    /// This is inherited from the connection that the device was opened on.
     #[inline]
     pub fn gl_api(&self) -> GLApi {
-        GLApi::GL
+        self.gl_api
+    }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The GL strings are read from a temporary context.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        unsafe { device::query_adapter_info(self.egl_display, self.gl_api()) }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Each format is tried out in a temporary context. `RGBA8` is always supported.
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        unsafe { device::query_supported_surface_formats(self.egl_display, self.gl_api()) }
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// Messages come from contexts created with the `DEBUG` flag and, if the `EGL_KHR_debug`
+    /// extension is available, from EGL errors on this thread. The callback replaces any previous
+    /// one. Without a callback, messages are forwarded to the `log` crate.
+    pub fn set_debug_message_callback<F>(&self, callback: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+        self.debug_sink.set_callback(Some(Arc::new(callback)));
+        egl_debug::route_egl_debug_messages_to(&self.debug_sink);
+    }
+
+    /// Removes the debug message callback, forwarding messages to the `log` crate again.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {
+        self.debug_sink.set_callback(None);
+    }
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// By default, messages of `Low` severity and above are delivered from all sources.
+    #[inline]
+    pub fn set_debug_message_filter(&self, filter: DebugMessageFilter) {
+        self.debug_sink.set_filter(filter);
     }
 }
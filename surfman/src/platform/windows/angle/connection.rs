@@ -11,7 +11,7 @@ use super::device::{Adapter, Device, NativeDevice, VendorPreference};
 use super::surface::NativeWidget;
 use crate::egl::types::{EGLDisplay, EGLNativeWindowType};
 use crate::Error;
-use crate::GLApi;
+use crate::{AdapterInfo, GLApi};
 
 use euclid::default::Size2D;
 
@@ -102,6 +102,12 @@ impl Connection {
         Adapter::new(D3D_DRIVER_TYPE_WARP, VendorPreference::None)
     }
 
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
+    }
+
     /// Opens the hardware device corresponding to the given adapter.
     ///
     /// Device handles are local to a single thread.
@@ -5,11 +5,11 @@
 use super::connection::Connection;
 use crate::egl;
 use crate::egl::types::{EGLAttrib, EGLDisplay, EGLint, EGLDeviceEXT};
-use crate::platform::generic::egl::device::EGL_FUNCTIONS;
+use crate::platform::generic::egl::device::{self, EGL_FUNCTIONS};
 use crate::platform::generic::egl::ffi::{EGL_D3D11_DEVICE_ANGLE, EGL_EXTENSION_FUNCTIONS};
 use crate::platform::generic::egl::ffi::{EGL_NO_DEVICE_EXT, EGL_PLATFORM_DEVICE_EXT};
 use crate::platform::generic::egl::ffi::{EGL_DEVICE_EXT};
-use crate::{Error, GLApi};
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
 
 use std::cell::{RefCell, RefMut};
 use std::mem;
@@ -318,6 +318,43 @@ impl Device {
     pub fn gl_api(&self) -> GLApi {
         GLApi::GLES
     }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The GL strings are read from a temporary context.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        unsafe { device::query_adapter_info(self.egl_display, self.gl_api()) }
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Only `RGBA8` is supported on this backend.
+    #[inline]
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Ok(vec![SurfaceFormat::RGBA8])
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// This backend doesn't route debug messages yet, so the callback is never called.
+    #[inline]
+    pub fn set_debug_message_callback<F>(&self, _: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+    }
+
+    /// Removes the debug message callback.
+    ///
+    /// This backend doesn't route debug messages yet, so this does nothing.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {}
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// This backend doesn't route debug messages yet, so this does nothing.
+    #[inline]
+    pub fn set_debug_message_filter(&self, _: DebugMessageFilter) {}
 }
 
 impl Drop for Device {
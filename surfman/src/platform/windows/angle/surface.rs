@@ -16,7 +16,7 @@ use crate::platform::generic::egl::ffi::EGL_D3D_TEXTURE_2D_SHARE_HANDLE_ANGLE;
 use crate::platform::generic::egl::ffi::EGL_D3D_TEXTURE_ANGLE;
 use crate::platform::generic::egl::ffi::EGL_DXGI_KEYED_MUTEX_ANGLE;
 use crate::platform::generic::egl::ffi::EGL_EXTENSION_FUNCTIONS;
-use crate::{Error, SurfaceAccess, SurfaceID, SurfaceInfo, SurfaceType};
+use crate::{Error, SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceInfo, SurfaceType};
 
 use euclid::default::Size2D;
 use std::fmt::{self, Debug, Formatter};
@@ -134,7 +134,11 @@ impl Device {
         surface_type: SurfaceType<NativeWidget>,
     ) -> Result<Surface, Error> {
         match surface_type {
-            SurfaceType::Generic { ref size } => self.create_pbuffer_surface(context, size, None),
+            SurfaceType::Generic {
+                ref size,
+                format: SurfaceFormat::RGBA8,
+            } => self.create_pbuffer_surface(context, size, None),
+            SurfaceType::Generic { .. } => Err(Error::UnsupportedSurfaceFormat),
             SurfaceType::Widget { ref native_widget } => {
                 self.create_window_surface(context, native_widget)
             }
@@ -568,6 +572,20 @@ impl Device {
         Ok(())
     }
 
+    /// Resizes a surface, keeping its contents.
+    ///
+    /// `resize_surface()` never reallocates surfaces on this backend, so this is equivalent to
+    /// it.
+    #[inline]
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.resize_surface(context, surface, size)
+    }
+
     /// Returns various information about the surface, including the framebuffer object needed to
     /// render to this surface.
     ///
@@ -581,6 +599,7 @@ impl Device {
             id: surface.id(),
             context_id: surface.context_id,
             framebuffer_object: 0,
+            format: SurfaceFormat::RGBA8,
         }
     }
 
@@ -7,7 +7,7 @@
 use super::device::{Adapter, Device, NativeDevice};
 use super::surface::NativeWidget;
 use crate::Error;
-use crate::GLApi;
+use crate::{AdapterInfo, GLApi};
 
 use euclid::default::Size2D;
 
@@ -83,6 +83,12 @@ impl Connection {
         self.create_low_power_adapter()
     }
 
+    /// Returns information about the hardware that the given adapter renders with.
+    #[inline]
+    pub fn adapter_info(&self, adapter: &Adapter) -> Result<AdapterInfo, Error> {
+        self.create_device(adapter)?.adapter_info()
+    }
+
     /// Opens a device.
     #[inline]
     pub fn create_device(&self, adapter: &Adapter) -> Result<Device, Error> {
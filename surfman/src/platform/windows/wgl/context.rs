@@ -4,10 +4,13 @@
 
 use super::device::{DCGuard, Device, HiddenWindow};
 use super::surface::{Surface, Win32Objects};
-use crate::context::{self, CREATE_CONTEXT_MUTEX};
+use crate::context::{self, current_context_reset_status, CREATE_CONTEXT_MUTEX};
 use crate::surface::Framebuffer;
-use crate::{ContextAttributeFlags, ContextAttributes, ContextID, Error, GLVersion};
-use crate::{SurfaceInfo, WindowingApiError};
+use crate::ContextResetStatus;
+use crate::GLVersion;
+use crate::{AdapterInfo, ContextAttributeFlags, ContextAttributes, ContextID, Error};
+use crate::{ContextNegotiation, ContextNegotiationReport};
+use crate::{PixelFormatInfo, PixelFormatPreferences, SurfaceInfo, WindowingApiError};
 
 use crate::gl;
 use crate::gl::types::{GLenum, GLint, GLuint};
@@ -185,6 +188,19 @@ impl Device {
         };
         let compatibility_profile = flags.contains(ContextAttributeFlags::COMPATIBILITY_PROFILE);
 
+        // Robust, debug, forward-compatible, no-error, and configless contexts aren't supported on
+        // this backend.
+        if attributes.flags.intersects(
+            ContextAttributeFlags::ROBUST_ACCESS
+                | ContextAttributeFlags::LOSE_CONTEXT_ON_RESET
+                | ContextAttributeFlags::DEBUG
+                | ContextAttributeFlags::FORWARD_COMPATIBLE
+                | ContextAttributeFlags::NO_ERROR
+                | ContextAttributeFlags::NO_CONFIG,
+        ) {
+            return Err(Error::RequiredExtensionUnavailable);
+        }
+
         let attrib_i_list = [
             WGL_DRAW_TO_WINDOW_ARB as c_int,
             gl::TRUE as c_int,
@@ -238,6 +254,43 @@ impl Device {
         }
     }
 
+    /// Returns a context descriptor for each pixel format that satisfies the given attributes.
+    ///
+    /// This backend chooses a single pixel format for each set of attributes, so this returns the
+    /// descriptor that `create_context_descriptor()` does.
+    #[inline]
+    pub fn enumerate_context_descriptors(
+        &self,
+        attributes: &ContextAttributes,
+    ) -> Result<Vec<ContextDescriptor>, Error> {
+        Ok(vec![self.create_context_descriptor(attributes)?])
+    }
+
+    /// Creates a context descriptor with the given attributes.
+    ///
+    /// There is only one pixel format to choose from, so the preferences are ignored.
+    #[inline]
+    pub fn choose_context_descriptor(
+        &self,
+        attributes: &ContextAttributes,
+        _: &PixelFormatPreferences,
+    ) -> Result<ContextDescriptor, Error> {
+        self.create_context_descriptor(attributes)
+    }
+
+    /// Creates a context descriptor for the first attempt in the negotiation that this device can
+    /// satisfy, and reports which preferences had to be given up.
+    ///
+    /// Each attempt is verified by creating a context, so this is much slower than
+    /// `create_context_descriptor()`.
+    #[inline]
+    pub fn negotiate_context_descriptor(
+        &mut self,
+        negotiation: &ContextNegotiation,
+    ) -> Result<(ContextDescriptor, ContextNegotiationReport), Error> {
+        crate::context::negotiate_context_descriptor(self, negotiation)
+    }
+
     /// Creates a new OpenGL context.
     ///
     /// The context initially has no surface attached. Until a surface is bound to it, rendering
@@ -313,6 +366,36 @@ impl Device {
         }
     }
 
+    // Describes the adapter from the OpenGL strings of a temporary legacy context.
+    pub(crate) fn query_adapter_info(&self) -> Result<AdapterInfo, Error> {
+        let descriptor = self.create_context_descriptor(&ContextAttributes::zeroed())?;
+        unsafe {
+            let hidden_window = HiddenWindow::new();
+            let hidden_window_dc = hidden_window.get_dc();
+            let dc = hidden_window_dc.dc;
+            set_dc_pixel_format(dc, descriptor.pixel_format);
+
+            let glrc = wglCreateContext(dc);
+            if glrc.is_null() {
+                return Err(Error::ContextCreationFailed(WindowingApiError::Failed));
+            }
+
+            let result = {
+                let _guard = CurrentContextGuard::new();
+                if wglMakeCurrent(dc, glrc) == FALSE {
+                    Err(Error::MakeCurrentFailed(WindowingApiError::Failed))
+                } else {
+                    Ok(AdapterInfo::from_current_context(&Gl::load_with(
+                        get_proc_address,
+                    )))
+                }
+            };
+
+            wglDeleteContext(glrc);
+            result
+        }
+    }
+
     /// Wraps an `HGLRC` in a `surfman` context and returns it.
     ///
     /// The `HGLRC` is not retained, as there is no way to do this in the Win32 API. Therefore, it
@@ -436,6 +519,7 @@ impl Device {
             let mut attributes = ContextAttributes {
                 version: context_descriptor.gl_version,
                 flags: ContextAttributeFlags::empty(),
+                ..ContextAttributes::default()
             };
             if alpha_bits > 0 {
                 attributes.flags.insert(ContextAttributeFlags::ALPHA);
@@ -463,6 +547,19 @@ impl Device {
         }
     }
 
+    /// Returns whether the given context has been lost because of a GPU reset.
+    ///
+    /// Resets are only reported for contexts created with the `LOSE_CONTEXT_ON_RESET` flag. Once
+    /// a reset has been reported, the context must be destroyed and recreated. If the driver lacks
+    /// the `glGetGraphicsResetStatus()` function, a `RequiredExtensionUnavailable` error is
+    /// returned.
+    ///
+    /// The context is briefly made current in order to query its status.
+    pub fn context_reset_status(&self, context: &Context) -> Result<ContextResetStatus, Error> {
+        let _guard = self.temporarily_make_context_current(context)?;
+        unsafe { current_context_reset_status(get_proc_address) }
+    }
+
     pub(crate) fn temporarily_make_context_current(
         &self,
         context: &Context,
@@ -503,6 +600,17 @@ impl Device {
         }
     }
 
+    /// Returns the properties of the pixel format that the context descriptor selects.
+    ///
+    /// Caveats and sample counts aren't reported on this backend.
+    #[inline]
+    pub fn context_descriptor_pixel_format(
+        &self,
+        context_descriptor: &ContextDescriptor,
+    ) -> PixelFormatInfo {
+        PixelFormatInfo::from_attributes(&self.context_descriptor_attributes(context_descriptor))
+    }
+
     /// Fetches the address of an OpenGL function associated with this context.
     ///
     /// OpenGL functions are local to a context. You should not use OpenGL functions on one context
@@ -4,7 +4,7 @@
 
 use super::connection::Connection;
 use super::context::WGL_EXTENSION_FUNCTIONS;
-use crate::{Error, GLApi};
+use crate::{AdapterInfo, DebugMessage, DebugMessageFilter, Error, GLApi, SurfaceFormat};
 
 use std::marker::PhantomData;
 use std::mem;
@@ -237,6 +237,44 @@ impl Device {
     pub fn gl_api(&self) -> GLApi {
         GLApi::GL
     }
+
+    /// Returns information about the hardware that this device renders with.
+    ///
+    /// The vendor and renderer are read from a temporary context. The driver and DRM render node
+    /// are never known on this backend.
+    pub fn adapter_info(&self) -> Result<AdapterInfo, Error> {
+        self.query_adapter_info()
+    }
+
+    /// Returns the formats that generic surfaces created on this device can have.
+    ///
+    /// Only `RGBA8` is supported on this backend.
+    #[inline]
+    pub fn supported_surface_formats(&self) -> Result<Vec<SurfaceFormat>, Error> {
+        Ok(vec![SurfaceFormat::RGBA8])
+    }
+
+    /// Registers a function that receives the debug messages of this device.
+    ///
+    /// WGL debug contexts aren't supported, so the callback is never called.
+    #[inline]
+    pub fn set_debug_message_callback<F>(&self, _: F)
+    where
+        F: Fn(&DebugMessage) + Send + Sync + 'static,
+    {
+    }
+
+    /// Removes the debug message callback.
+    ///
+    /// WGL debug contexts aren't supported, so this does nothing.
+    #[inline]
+    pub fn clear_debug_message_callback(&self) {}
+
+    /// Selects which debug messages of this device are delivered.
+    ///
+    /// WGL debug contexts aren't supported, so this does nothing.
+    #[inline]
+    pub fn set_debug_message_filter(&self, _: DebugMessageFilter) {}
 }
 
 impl Adapter {
@@ -6,7 +6,8 @@ use super::context::{self, Context, WGL_EXTENSION_FUNCTIONS};
 use super::device::Device;
 use crate::error::WindowingApiError;
 use crate::renderbuffers::Renderbuffers;
-use crate::{ContextID, Error, SurfaceAccess, SurfaceID, SurfaceInfo, SurfaceType};
+use crate::SurfaceType;
+use crate::{ContextID, Error, SurfaceAccess, SurfaceFormat, SurfaceID, SurfaceInfo};
 
 use crate::gl;
 use crate::gl::types::{GLenum, GLint, GLuint};
@@ -136,7 +137,11 @@ impl Device {
         surface_type: SurfaceType<NativeWidget>,
     ) -> Result<Surface, Error> {
         match surface_type {
-            SurfaceType::Generic { size } => self.create_generic_surface(context, &size),
+            SurfaceType::Generic {
+                size,
+                format: SurfaceFormat::RGBA8,
+            } => self.create_generic_surface(context, &size),
+            SurfaceType::Generic { .. } => Err(Error::UnsupportedSurfaceFormat),
             SurfaceType::Widget { native_widget } => {
                 self.create_widget_surface(context, native_widget)
             }
@@ -613,6 +618,20 @@ impl Device {
         Ok(())
     }
 
+    /// Resizes a surface, keeping its contents.
+    ///
+    /// `resize_surface()` never reallocates surfaces on this backend, so this is equivalent to
+    /// it.
+    #[inline]
+    pub fn resize_surface_preserving_contents(
+        &self,
+        context: &Context,
+        surface: &mut Surface,
+        size: Size2D<i32>,
+    ) -> Result<(), Error> {
+        self.resize_surface(context, surface, size)
+    }
+
     /// Returns various information about the surface, including the framebuffer object needed to
     /// render to this surface.
     ///
@@ -629,6 +648,7 @@ impl Device {
                 Win32Objects::Texture { gl_framebuffer, .. } => gl_framebuffer,
                 Win32Objects::Widget { .. } => 0,
             },
+            format: SurfaceFormat::RGBA8,
         }
     }
 
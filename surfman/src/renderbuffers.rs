@@ -4,7 +4,9 @@
 
 use crate::context::{ContextAttributeFlags, ContextAttributes};
 use crate::gl;
-use crate::gl::types::GLuint;
+use crate::gl::types::{GLenum, GLint, GLsizei, GLuint};
+use crate::gl_utils;
+use crate::surface::SurfaceFormat;
 use crate::Gl;
 
 use euclid::default::Size2D;
@@ -27,52 +29,55 @@ impl Drop for Renderbuffers {
     }
 }
 
+// A framebuffer with multisampled color, depth, and stencil renderbuffers, which is rendered to in
+// place of a single-sampled surface and resolved into it.
+pub(crate) struct MultisampleFramebuffer {
+    pub(crate) framebuffer_object: GLuint,
+    color_renderbuffer: GLuint,
+    renderbuffers: Renderbuffers,
+}
+
+impl Drop for MultisampleFramebuffer {
+    fn drop(&mut self) {
+        if self.framebuffer_object != 0 || self.color_renderbuffer != 0 {
+            panic!("Should have destroyed the multisample framebuffer with `destroy()`!");
+        }
+    }
+}
+
 impl Renderbuffers {
     pub(crate) fn new(
         gl: &Gl,
         size: &Size2D<i32>,
         attributes: &ContextAttributes,
+    ) -> Renderbuffers {
+        Renderbuffers::new_multisampled(gl, size, attributes, 0)
+    }
+
+    // Allocates depth and stencil renderbuffers with the given number of samples per pixel, or
+    // single-sampled ones if `samples` is 0.
+    pub(crate) fn new_multisampled(
+        gl: &Gl,
+        size: &Size2D<i32>,
+        attributes: &ContextAttributes,
+        samples: GLsizei,
     ) -> Renderbuffers {
         unsafe {
             if attributes
                 .flags
                 .contains(ContextAttributeFlags::DEPTH | ContextAttributeFlags::STENCIL)
             {
-                let mut renderbuffer = 0;
-                gl.GenRenderbuffers(1, &mut renderbuffer);
-                gl.BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
-                gl.RenderbufferStorage(
-                    gl::RENDERBUFFER,
-                    gl::DEPTH24_STENCIL8,
-                    size.width,
-                    size.height,
-                );
-                gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
+                let renderbuffer = create_renderbuffer(gl, gl::DEPTH24_STENCIL8, size, samples);
                 return Renderbuffers::CombinedDepthStencil(renderbuffer);
             }
 
             let (mut depth_renderbuffer, mut stencil_renderbuffer) = (0, 0);
             if attributes.flags.contains(ContextAttributeFlags::DEPTH) {
-                gl.GenRenderbuffers(1, &mut depth_renderbuffer);
-                gl.BindRenderbuffer(gl::RENDERBUFFER, depth_renderbuffer);
-                gl.RenderbufferStorage(
-                    gl::RENDERBUFFER,
-                    gl::DEPTH_COMPONENT24,
-                    size.width,
-                    size.height,
-                );
+                depth_renderbuffer = create_renderbuffer(gl, gl::DEPTH_COMPONENT24, size, samples);
             }
             if attributes.flags.contains(ContextAttributeFlags::STENCIL) {
-                gl.GenRenderbuffers(1, &mut stencil_renderbuffer);
-                gl.BindRenderbuffer(gl::RENDERBUFFER, stencil_renderbuffer);
-                gl.RenderbufferStorage(
-                    gl::RENDERBUFFER,
-                    gl::STENCIL_INDEX8,
-                    size.width,
-                    size.height,
-                );
+                stencil_renderbuffer = create_renderbuffer(gl, gl::STENCIL_INDEX8, size, samples);
             }
-            gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
 
             Renderbuffers::IndividualDepthStencil {
                 depth: depth_renderbuffer,
@@ -147,3 +152,125 @@ impl Renderbuffers {
         }
     }
 }
+
+impl MultisampleFramebuffer {
+    // Creates a multisampled framebuffer with a color buffer of the given format and leaves it
+    // bound to `GL_FRAMEBUFFER`.
+    //
+    // The sample count is clamped to what the implementation supports. Returns `None` if that
+    // leaves fewer than two samples.
+    pub(crate) fn new(
+        gl: &Gl,
+        size: &Size2D<i32>,
+        attributes: &ContextAttributes,
+        format: SurfaceFormat,
+    ) -> Option<MultisampleFramebuffer> {
+        unsafe {
+            let mut max_samples = 0;
+            gl.GetIntegerv(gl::MAX_SAMPLES, &mut max_samples);
+            let samples = (attributes.samples as GLint).min(max_samples);
+            if samples < 2 {
+                return None;
+            }
+
+            let color_renderbuffer =
+                create_renderbuffer(gl, format.gl_internal_format(), size, samples);
+            let renderbuffers = Renderbuffers::new_multisampled(gl, size, attributes, samples);
+
+            let mut framebuffer_object = 0;
+            gl.GenFramebuffers(1, &mut framebuffer_object);
+            gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer_object);
+            gl.FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                color_renderbuffer,
+            );
+            renderbuffers.bind_to_current_framebuffer(gl);
+
+            Some(MultisampleFramebuffer {
+                framebuffer_object,
+                color_renderbuffer,
+                renderbuffers,
+            })
+        }
+    }
+
+    // Resolves the color samples into the given single-sampled framebuffer, which must have the
+    // same size. The framebuffer bindings and scissor test are preserved.
+    pub(crate) fn resolve(&self, gl: &Gl, dest_framebuffer_object: GLuint, size: &Size2D<i32>) {
+        unsafe {
+            blit_color(gl, self.framebuffer_object, dest_framebuffer_object, size);
+        }
+    }
+
+    pub(crate) fn destroy(&mut self, gl: &Gl) {
+        unsafe {
+            gl_utils::destroy_framebuffer(gl, self.framebuffer_object);
+            self.framebuffer_object = 0;
+            gl.DeleteRenderbuffers(1, &self.color_renderbuffer);
+            self.color_renderbuffer = 0;
+            self.renderbuffers.destroy(gl);
+        }
+    }
+}
+
+unsafe fn create_renderbuffer(
+    gl: &Gl,
+    internal_format: GLenum,
+    size: &Size2D<i32>,
+    samples: GLsizei,
+) -> GLuint {
+    let mut renderbuffer = 0;
+    gl.GenRenderbuffers(1, &mut renderbuffer);
+    gl.BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+    if samples > 0 {
+        gl.RenderbufferStorageMultisample(
+            gl::RENDERBUFFER,
+            samples,
+            internal_format,
+            size.width,
+            size.height,
+        );
+    } else {
+        gl.RenderbufferStorage(gl::RENDERBUFFER, internal_format, size.width, size.height);
+    }
+    gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
+    renderbuffer
+}
+
+// Copies the color contents of one framebuffer into another, anchored at the origin. Blits are
+// subject to the scissor test, so it's temporarily disabled.
+pub(crate) unsafe fn blit_color(
+    gl: &Gl,
+    src_framebuffer_object: GLuint,
+    dest_framebuffer_object: GLuint,
+    size: &Size2D<i32>,
+) {
+    let (mut old_draw_framebuffer, mut old_read_framebuffer) = (0, 0);
+    gl.GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut old_draw_framebuffer);
+    gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut old_read_framebuffer);
+    let scissor_enabled = gl.IsEnabled(gl::SCISSOR_TEST) != gl::FALSE;
+
+    gl.Disable(gl::SCISSOR_TEST);
+    gl.BindFramebuffer(gl::READ_FRAMEBUFFER, src_framebuffer_object);
+    gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, dest_framebuffer_object);
+    gl.BlitFramebuffer(
+        0,
+        0,
+        size.width,
+        size.height,
+        0,
+        0,
+        size.width,
+        size.height,
+        gl::COLOR_BUFFER_BIT,
+        gl::NEAREST,
+    );
+
+    gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, old_draw_framebuffer as GLuint);
+    gl.BindFramebuffer(gl::READ_FRAMEBUFFER, old_read_framebuffer as GLuint);
+    if scissor_enabled {
+        gl.Enable(gl::SCISSOR_TEST);
+    }
+}
@@ -4,7 +4,8 @@
 
 use crate::context::ContextID;
 
-use crate::gl::types::GLuint;
+use crate::gl;
+use crate::gl::types::{GLenum, GLuint};
 use euclid::default::Size2D;
 use std::fmt::{self, Display, Formatter};
 
@@ -28,6 +29,10 @@ pub struct SurfaceInfo {
     ///
     /// This is only valid when the surface is actually attached to a context.
     pub framebuffer_object: GLuint,
+    /// The format of the color data of this surface.
+    ///
+    /// Widget surfaces always have the `RGBA8` format.
+    pub format: SurfaceFormat,
 }
 
 // The default framebuffer for a context.
@@ -92,17 +97,51 @@ pub enum SurfaceAccess {
     GPUCPUWriteCombined,
 }
 
+/// The format of the color data of a generic surface.
+///
+/// Not every device can render to every format. Use `Device::supported_surface_formats()` to find
+/// out which formats are available; creating a surface with an unsupported format returns an
+/// `UnsupportedSurfaceFormat` error.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum SurfaceFormat {
+    /// 8-bit red, green, blue, and alpha channels.
+    ///
+    /// This format is supported everywhere.
+    RGBA8,
+    /// 8-bit red, green, blue, and alpha channels, with the color channels in the sRGB color
+    /// space.
+    ///
+    /// Writes are encoded to sRGB if `GL_FRAMEBUFFER_SRGB` is enabled (always, on OpenGL ES), and
+    /// samples are decoded back to linear values.
+    SRGB8Alpha8,
+    /// 10-bit red, green, and blue channels, with a 2-bit alpha channel.
+    RGB10A2,
+    /// 16-bit floating-point red, green, blue, and alpha channels, for high dynamic range
+    /// rendering.
+    RGBA16F,
+    /// A single 8-bit red channel.
+    R8,
+    /// 8-bit red and green channels.
+    RG8,
+}
+
 /// Information specific to the type of surface: generic or widget.
 #[derive(Clone)]
 pub enum SurfaceType<NativeWidget> {
     /// An off-screen surface that has a pixel size. Generic surfaces can sometimes be shown on
     /// screen using platform-specific APIs, but `surfman` itself provides no way to draw their
     /// contents on screen. Only generic surfaces can be bound to textures.
+    ///
+    /// The `format` field is new in this release, so code that builds this variant with a struct
+    /// literal needs to set it. `SurfaceType::generic()` creates an `RGBA8` surface type, which
+    /// matches the old behavior.
     Generic {
         /// The size of the surface.
         ///
         /// For HiDPI screens, this is a physical size, not a logical size.
         size: Size2D<i32>,
+        /// The format of the color data of the surface.
+        format: SurfaceFormat,
     },
     /// A surface displayed inside a native widget (window or view). The size of a widget surface
     /// is automatically determined based on the size of the widget. (For example, if the widget is
@@ -116,6 +155,73 @@ pub enum SurfaceType<NativeWidget> {
     },
 }
 
+impl<NativeWidget> SurfaceType<NativeWidget> {
+    /// Returns the type of a generic surface with the given size and the `RGBA8` format.
+    #[inline]
+    pub fn generic(size: Size2D<i32>) -> SurfaceType<NativeWidget> {
+        SurfaceType::Generic {
+            size,
+            format: SurfaceFormat::RGBA8,
+        }
+    }
+}
+
+impl SurfaceFormat {
+    /// All surface formats, in declaration order.
+    pub const ALL: [SurfaceFormat; 6] = [
+        SurfaceFormat::RGBA8,
+        SurfaceFormat::SRGB8Alpha8,
+        SurfaceFormat::RGB10A2,
+        SurfaceFormat::RGBA16F,
+        SurfaceFormat::R8,
+        SurfaceFormat::RG8,
+    ];
+
+    // Returns the internal format, format, and type to pass to `glTexImage2D()` to allocate a
+    // texture of this format.
+    //
+    // `RGBA8` uses the unsized `GL_RGBA` internal format, which OpenGL ES 2.0 requires.
+    #[allow(dead_code)]
+    pub(crate) fn gl_texture_formats(self) -> (GLenum, GLenum, GLenum) {
+        match self {
+            SurfaceFormat::RGBA8 => (gl::RGBA, gl::RGBA, gl::UNSIGNED_BYTE),
+            SurfaceFormat::SRGB8Alpha8 => (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE),
+            SurfaceFormat::RGB10A2 => (gl::RGB10_A2, gl::RGBA, gl::UNSIGNED_INT_2_10_10_10_REV),
+            SurfaceFormat::RGBA16F => (gl::RGBA16F, gl::RGBA, gl::HALF_FLOAT),
+            SurfaceFormat::R8 => (gl::R8, gl::RED, gl::UNSIGNED_BYTE),
+            SurfaceFormat::RG8 => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE),
+        }
+    }
+
+    // Returns the size of a pixel of this format, as read back with the format and type that
+    // `gl_texture_formats()` returns.
+    #[allow(dead_code)]
+    pub(crate) fn bytes_per_pixel(self) -> usize {
+        match self {
+            SurfaceFormat::RGBA8 | SurfaceFormat::SRGB8Alpha8 | SurfaceFormat::RGB10A2 => 4,
+            SurfaceFormat::RGBA16F => 8,
+            SurfaceFormat::R8 => 1,
+            SurfaceFormat::RG8 => 2,
+        }
+    }
+
+    // Returns the sized internal format of this format, for use with renderbuffers.
+    #[allow(dead_code)]
+    pub(crate) fn gl_internal_format(self) -> GLenum {
+        match self {
+            SurfaceFormat::RGBA8 => gl::RGBA8,
+            _ => self.gl_texture_formats().0,
+        }
+    }
+}
+
+impl Default for SurfaceFormat {
+    #[inline]
+    fn default() -> SurfaceFormat {
+        SurfaceFormat::RGBA8
+    }
+}
+
 impl SurfaceAccess {
     #[allow(dead_code)]
     #[inline]
@@ -13,13 +13,21 @@ use super::context::{Context, ContextDescriptor, NativeContext};
 use super::device::{Adapter, Device};
 use super::surface::Surface;
 use crate::gl;
-use crate::gl::types::{GLenum, GLuint};
+use crate::gl::types::{GLenum, GLint, GLuint};
+#[cfg(target_os = "linux")]
+use crate::platform::generic::egl::ffi::{DRM_FORMAT_ABGR8888, DRM_FORMAT_MOD_LINEAR};
+use crate::SurfaceFormat;
+use crate::SurfaceType;
+use crate::WindowingApiError;
 use crate::{ContextAttributeFlags, ContextAttributes, Error, GLApi, GLVersion, Gl, SurfaceAccess};
-use crate::{SurfaceType, WindowingApiError};
+use crate::{ContextNegotiation, ContextPriority, ContextReleaseBehavior, ContextResetStatus};
+use crate::{DebugMessageFilter, DebugMessageSeverity, DebugMessageSource, DebugMessageType};
+use crate::{PixelFormatCaveat, PixelFormatInfo, PixelFormatPreferences};
 
 use euclid::default::Size2D;
 use std::os::raw::c_void;
 use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
 use std::thread;
 
 static GL_VERSIONS: [GLVersion; 6] = [
@@ -43,7 +51,13 @@ pub fn test_adapter_creation() {
     let connection = Connection::new().unwrap();
     connection.create_hardware_adapter().unwrap();
     connection.create_low_power_adapter().unwrap();
-    connection.create_software_adapter().unwrap();
+    match connection.create_software_adapter() {
+        Ok(_) => {}
+        Err(Error::RequiredExtensionUnavailable) => {
+            // The software rasterizer can't be selected on this hardware.
+        }
+        Err(err) => panic!("Failed to create software adapter: {:?}", err),
+    }
 }
 
 #[cfg_attr(not(feature = "sm-test"), test)]
@@ -77,6 +91,83 @@ pub fn test_device_accessors() {
     drop(device.connection());
     drop(device.adapter());
     drop(device.gl_api());
+
+    match device.adapter_info() {
+        Ok(adapter_info) => {
+            assert!(!adapter_info.renderer.is_empty());
+            assert_eq!(
+                adapter_info.egl_version.is_some(),
+                adapter_info.egl_platform_path.is_some()
+            );
+            assert_eq!(connection.adapter_info(&adapter).unwrap(), adapter_info);
+        }
+        Err(Error::Unimplemented) => {}
+        Err(err) => panic!("Failed to query adapter info: {:?}", err),
+    }
+}
+
+// Tests that closing one connection to a display leaves other connections to it working, and that
+// the display can be opened again once every connection to it has been closed.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_connection_lifetime() {
+    for _ in 0..2 {
+        let connection = Connection::new().unwrap();
+        let other_connection = Connection::new().unwrap();
+        let adapter = connection.create_low_power_adapter().unwrap();
+        let mut device = match connection.create_device(&adapter) {
+            Ok(device) => device,
+            Err(Error::RequiredExtensionUnavailable) => {
+                // Can't run these tests on this hardware.
+                return;
+            }
+            Err(err) => panic!("Failed to create device: {:?}", err),
+        };
+        drop(other_connection);
+
+        let context_descriptor = device
+            .create_context_descriptor(&ContextAttributes {
+                version: GLVersion::new(3, 0),
+                flags: ContextAttributeFlags::empty(),
+                ..ContextAttributes::default()
+            })
+            .unwrap();
+        let mut context = device.create_context(&context_descriptor, None).unwrap();
+        device.make_context_current(&context).unwrap();
+        device.destroy_context(&mut context).unwrap();
+    }
+}
+
+// Tests that a connection wrapping the native connection of another one keeps working after the
+// original connection has been closed.
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_connection_from_native_connection() {
+    let connection = Connection::new().unwrap();
+    let native_connection = connection.native_connection();
+    let wrapped_connection = unsafe { Connection::from_native_connection(native_connection) };
+    let wrapped_connection = wrapped_connection.unwrap();
+    drop(connection);
+
+    let adapter = wrapped_connection.create_adapter().unwrap();
+    let mut device = match wrapped_connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => {
+            // Can't run these tests on this hardware.
+            return;
+        }
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let context_descriptor = device
+        .create_context_descriptor(&ContextAttributes {
+            version: GLVersion::new(3, 0),
+            flags: ContextAttributeFlags::empty(),
+            ..ContextAttributes::default()
+        })
+        .unwrap();
+    let mut context = device.create_context(&context_descriptor, None).unwrap();
+    device.make_context_current(&context).unwrap();
+    device.destroy_context(&mut context).unwrap();
 }
 
 // Tests that all combinations of flags result in the creation of valid context descriptors and
@@ -102,13 +193,42 @@ pub fn test_context_creation() {
         GLApi::GLES => &GL_ES_VERSIONS[..],
     };
 
+    // Every combination of the surface flags, each with at most one of the other flags. Trying
+    // every combination of all flags would create over a thousand contexts per version.
+    let surface_flags = ContextAttributeFlags::ALPHA
+        | ContextAttributeFlags::DEPTH
+        | ContextAttributeFlags::STENCIL
+        | ContextAttributeFlags::COMPATIBILITY_PROFILE;
+    let other_flags = [
+        ContextAttributeFlags::empty(),
+        ContextAttributeFlags::ROBUST_ACCESS,
+        ContextAttributeFlags::LOSE_CONTEXT_ON_RESET,
+        ContextAttributeFlags::DEBUG,
+        ContextAttributeFlags::FORWARD_COMPATIBLE,
+        ContextAttributeFlags::NO_ERROR,
+        ContextAttributeFlags::NO_CONFIG,
+    ];
+    let mut flag_combinations = vec![];
+    for surface_flag_bits in 0..(surface_flags.bits() + 1) {
+        let flags = ContextAttributeFlags::from_bits_truncate(surface_flag_bits) & surface_flags;
+        for &other_flag in &other_flags {
+            flag_combinations.push(flags | other_flag);
+        }
+    }
+
     for &version in versions {
-        for flag_bits in 0..(ContextAttributeFlags::all().bits() + 1) {
-            let flags = ContextAttributeFlags::from_bits_truncate(flag_bits);
-            let attributes = ContextAttributes { version, flags };
+        for &flags in &flag_combinations {
+            let attributes = ContextAttributes {
+                version,
+                flags,
+                ..ContextAttributes::default()
+            };
             let descriptor = match device.create_context_descriptor(&attributes) {
                 Ok(descriptor) => descriptor,
-                Err(Error::UnsupportedGLProfile) | Err(Error::UnsupportedGLVersion) => {
+                Err(Error::UnsupportedGLProfile)
+                | Err(Error::UnsupportedGLVersion)
+                | Err(Error::RequiredExtensionUnavailable)
+                | Err(Error::IncompatibleContextAttributeFlags) => {
                     // Nothing we can do about this. Go on to the next one.
                     continue;
                 }
@@ -179,6 +299,7 @@ pub fn test_newly_created_contexts_are_not_current() {
         .create_context_descriptor(&ContextAttributes {
             version: GLVersion::new(3, 0),
             flags: ContextAttributeFlags::empty(),
+            ..ContextAttributes::default()
         })
         .unwrap();
 
@@ -247,6 +368,7 @@ pub fn test_context_sharing() {
         .create_context_descriptor(&ContextAttributes {
             version: GLVersion::new(3, 0),
             flags: ContextAttributeFlags::empty(),
+            ..ContextAttributes::default()
         })
         .unwrap();
 
@@ -287,6 +409,7 @@ pub fn test_generic_surface_creation() {
         .create_context_descriptor(&ContextAttributes {
             version: GLVersion::new(3, 0),
             flags: ContextAttributeFlags::empty(),
+            ..ContextAttributes::default()
         })
         .unwrap();
 
@@ -306,6 +429,7 @@ pub fn test_generic_surface_creation() {
                 access,
                 SurfaceType::Generic {
                     size: Size2D::new(640, 480),
+                    format: SurfaceFormat::RGBA8,
                 },
             )
             .unwrap();
@@ -748,6 +872,7 @@ pub fn test_depth_and_stencil() {
         .create_context_descriptor(&ContextAttributes {
             version: GLVersion::new(3, 0),
             flags: ContextAttributeFlags::DEPTH,
+            ..ContextAttributes::default()
         })
         .unwrap();
 
@@ -795,6 +920,7 @@ pub fn test_depth_and_stencil() {
         .create_context_descriptor(&ContextAttributes {
             version: GLVersion::new(3, 0),
             flags: ContextAttributeFlags::STENCIL,
+            ..ContextAttributes::default()
         })
         .unwrap();
 
@@ -872,6 +998,937 @@ fn bind_context_fbo(gl: &Gl, device: &Device, context: &Context) {
     }
 }
 
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_surface_data_access() {
+    let mut env = match BasicEnvironment::new() {
+        None => return,
+        Some(env) => env,
+    };
+
+    unsafe {
+        let mut main_surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+        let cpu_surface = env
+            .device
+            .create_surface(
+                &env.context,
+                SurfaceAccess::GPUCPU,
+                SurfaceType::Generic {
+                    size: Size2D::new(640, 480),
+                    format: SurfaceFormat::RGBA8,
+                },
+            )
+            .unwrap();
+        env.device
+            .bind_surface_to_context(&mut env.context, cpu_surface)
+            .unwrap();
+        env.gl
+            .BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&env.device, &env.context));
+        clear(&env.gl, &[255, 0, 0, 255]);
+        let mut cpu_surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+
+        // Read the cleared color back and overwrite the bottom left pixel.
+        {
+            let mut guard = env.device.lock_surface_data(&mut cpu_surface).unwrap();
+            assert!(guard.stride() >= 640 * 4);
+            assert_eq!(&guard.data()[0..4], &[255, 0, 0, 255]);
+            guard.data()[0..4].copy_from_slice(&[0, 255, 0, 255]);
+        }
+
+        env.device
+            .bind_surface_to_context(&mut env.context, cpu_surface)
+            .unwrap();
+        env.gl
+            .BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&env.device, &env.context));
+        assert_eq!(get_pixel_from_bottom_row(&env.gl), [0, 255, 0, 255]);
+
+        // Clean up.
+        env.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        check_gl(&env.gl);
+        let mut cpu_surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+        env.device
+            .destroy_surface(&mut env.context, &mut cpu_surface)
+            .unwrap();
+        env.device
+            .destroy_surface(&mut env.context, &mut main_surface)
+            .unwrap();
+        env.device.destroy_context(&mut env.context).unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_surface_dmabuf_export() {
+    let mut env = match BasicEnvironment::new() {
+        None => return,
+        Some(env) => env,
+    };
+
+    let mut surface = env
+        .device
+        .create_surface(
+            &env.context,
+            SurfaceAccess::GPUOnly,
+            SurfaceType::Generic {
+                size: Size2D::new(640, 480),
+                format: SurfaceFormat::RGBA8,
+            },
+        )
+        .unwrap();
+
+    match env.device.export_surface_dmabuf(&surface) {
+        Ok(descriptor) => {
+            assert_eq!(descriptor.size, Size2D::new(640, 480));
+            assert_eq!(descriptor.fourcc, DRM_FORMAT_ABGR8888);
+            // Tiled layouts may carry auxiliary planes, but a linear RGBA buffer has only one.
+            assert!(!descriptor.planes.is_empty());
+            if descriptor.modifier == DRM_FORMAT_MOD_LINEAR {
+                assert_eq!(descriptor.planes.len(), 1);
+            }
+            assert!(descriptor.planes[0].stride >= 640 * 4);
+        }
+        Err(Error::RequiredExtensionUnavailable) => {
+            // `EGL_MESA_image_dma_buf_export` isn't supported here.
+        }
+        Err(err) => panic!("Failed to export surface: {:?}", err),
+    }
+
+    env.device
+        .destroy_surface(&mut env.context, &mut surface)
+        .unwrap();
+    env.device.destroy_context(&mut env.context).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_surface_dmabuf_import() {
+    let mut env = match BasicEnvironment::new() {
+        None => return,
+        Some(env) => env,
+    };
+
+    unsafe {
+        clear(&env.gl, &[0, 255, 0, 255]);
+        let mut green_surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+
+        // Export the surface and import it again as a surface texture.
+        let imported_surface_texture = match env
+            .device
+            .export_surface_dmabuf(&green_surface)
+            .and_then(|descriptor| {
+                env.device
+                    .create_surface_texture_from_dmabuf(&mut env.context, descriptor)
+            }) {
+            Ok(surface_texture) => surface_texture,
+            Err(Error::RequiredExtensionUnavailable) => {
+                // DMA-BUF export or import isn't supported here.
+                env.device
+                    .destroy_surface(&mut env.context, &mut green_surface)
+                    .unwrap();
+                env.device.destroy_context(&mut env.context).unwrap();
+                return;
+            }
+            Err(err) => panic!("Failed to import surface: {:?}", err),
+        };
+
+        let main_surface = make_surface(&mut env.device, &env.context);
+        env.device
+            .bind_surface_to_context(&mut env.context, main_surface)
+            .unwrap();
+        let main_framebuffer_object = context_fbo(&env.device, &env.context);
+        env.gl
+            .BindFramebuffer(gl::FRAMEBUFFER, main_framebuffer_object);
+        clear(&env.gl, &[255, 0, 0, 255]);
+
+        // Read the imported contents back.
+        let imported_framebuffer_object = make_fbo(
+            &env.gl,
+            env.device.surface_gl_texture_target(),
+            env.device.surface_texture_object(&imported_surface_texture),
+        );
+        blit_fbo(
+            &env.gl,
+            main_framebuffer_object,
+            imported_framebuffer_object,
+        );
+        env.gl
+            .BindFramebuffer(gl::FRAMEBUFFER, main_framebuffer_object);
+        check_gl(&env.gl);
+        assert_eq!(get_pixel_from_bottom_row(&env.gl), [0, 255, 0, 255]);
+
+        // The surface left over from the imported surface texture can't be rendered to.
+        env.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        env.gl.DeleteFramebuffers(1, &imported_framebuffer_object);
+        let mut main_surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+        let imported_surface = env
+            .device
+            .destroy_surface_texture(&mut env.context, imported_surface_texture)
+            .unwrap();
+        let mut imported_surface = match env
+            .device
+            .bind_surface_to_context(&mut env.context, imported_surface)
+        {
+            Err((Error::SurfaceNotRenderable, surface)) => surface,
+            Err((err, _)) => panic!("Unexpected error binding imported surface: {:?}", err),
+            Ok(()) => panic!("Bound an imported surface without a framebuffer!"),
+        };
+
+        // Clean up.
+        env.device
+            .destroy_surface(&mut env.context, &mut imported_surface)
+            .unwrap();
+        env.device
+            .destroy_surface(&mut env.context, &mut main_surface)
+            .unwrap();
+        env.device
+            .destroy_surface(&mut env.context, &mut green_surface)
+            .unwrap();
+        env.device.destroy_context(&mut env.context).unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_generic_surface_resize() {
+    let mut env = match BasicEnvironment::new() {
+        None => return,
+        Some(env) => env,
+    };
+
+    unsafe {
+        clear(&env.gl, &[0, 255, 0, 255]);
+        let mut surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+
+        // Shrinking while preserving contents keeps the overlapping pixels.
+        env.device
+            .resize_surface_preserving_contents(&env.context, &mut surface, Size2D::new(320, 240))
+            .unwrap();
+        assert_eq!(
+            env.device.surface_info(&surface).size,
+            Size2D::new(320, 240)
+        );
+        env.device
+            .bind_surface_to_context(&mut env.context, surface)
+            .unwrap();
+        env.gl
+            .BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&env.device, &env.context));
+        assert_eq!(get_pixel_from_bottom_row(&env.gl), [0, 255, 0, 255]);
+        let mut surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+
+        // Plain resizing reallocates the surface at the new size.
+        env.device
+            .resize_surface(&env.context, &mut surface, Size2D::new(800, 600))
+            .unwrap();
+        assert_eq!(
+            env.device.surface_info(&surface).size,
+            Size2D::new(800, 600)
+        );
+
+        // Clean up.
+        env.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        check_gl(&env.gl);
+        env.device
+            .destroy_surface(&mut env.context, &mut surface)
+            .unwrap();
+        env.device.destroy_context(&mut env.context).unwrap();
+    }
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_gles_context() {
+    use crate::gles::{self, Gles2};
+    use std::ffi::CStr;
+
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let attributes = ContextAttributes {
+        version: GLVersion::new(3, 0),
+        flags: ContextAttributeFlags::empty(),
+        ..ContextAttributes::default()
+    };
+    let context_descriptor =
+        match device.create_context_descriptor_with_gl_api(&attributes, GLApi::GLES) {
+            Ok(context_descriptor) => context_descriptor,
+            Err(Error::UnsupportedGLType) | Err(Error::NoPixelFormatFound) => return,
+            Err(err) => panic!("Failed to create context descriptor: {:?}", err),
+        };
+    assert_eq!(
+        device.context_descriptor_gl_api(&context_descriptor),
+        GLApi::GLES
+    );
+
+    // OpenGL ES has no compatibility profile.
+    match device.create_context_descriptor_with_gl_api(
+        &ContextAttributes {
+            version: GLVersion::new(2, 0),
+            flags: ContextAttributeFlags::COMPATIBILITY_PROFILE,
+            ..ContextAttributes::default()
+        },
+        GLApi::GLES,
+    ) {
+        Err(Error::UnsupportedGLProfile) => {}
+        _ => panic!("Expected an `UnsupportedGLProfile` error!"),
+    }
+
+    let mut context = device.create_context(&context_descriptor, None).unwrap();
+    let surface = make_surface(&mut device, &context);
+    device
+        .bind_surface_to_context(&mut context, surface)
+        .unwrap();
+    device.make_context_current(&context).unwrap();
+
+    let descriptor = device.context_descriptor(&context);
+    assert_eq!(device.context_descriptor_gl_api(&descriptor), GLApi::GLES);
+
+    let gl = Gles2::load_with(|symbol| device.get_proc_address(&context, symbol));
+    unsafe {
+        let version = CStr::from_ptr(gl.GetString(gles::VERSION) as *const _);
+        assert!(version.to_string_lossy().starts_with("OpenGL ES"));
+
+        let framebuffer_object = context_fbo(&device, &context);
+        gl.BindFramebuffer(gles::FRAMEBUFFER, framebuffer_object);
+        gl.Viewport(0, 0, 640, 480);
+        gl.ClearColor(0.0, 0.0, 1.0, 1.0);
+        gl.Clear(gles::COLOR_BUFFER_BIT);
+
+        let mut pixel = [0u8; 4];
+        gl.ReadPixels(
+            0,
+            0,
+            1,
+            1,
+            gles::RGBA,
+            gles::UNSIGNED_BYTE,
+            pixel.as_mut_ptr() as *mut c_void,
+        );
+        assert_eq!(pixel, [0, 0, 255, 255]);
+        assert_eq!(gl.GetError(), gles::NO_ERROR);
+    }
+
+    device.destroy_context(&mut context).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_robust_context() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let flags = ContextAttributeFlags::ROBUST_ACCESS | ContextAttributeFlags::LOSE_CONTEXT_ON_RESET;
+    let context_descriptor = match device.create_context_descriptor(&ContextAttributes {
+        version: GLVersion::new(3, 0),
+        flags,
+        ..ContextAttributes::default()
+    }) {
+        Ok(context_descriptor) => context_descriptor,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create context descriptor: {:?}", err),
+    };
+    let mut context = device.create_context(&context_descriptor, None).unwrap();
+
+    // The flags should survive a round trip through the driver.
+    let actual_descriptor = device.context_descriptor(&context);
+    let actual_attributes = device.context_descriptor_attributes(&actual_descriptor);
+    assert!(actual_attributes.flags.contains(flags));
+
+    match device.context_reset_status(&context) {
+        Ok(status) => assert_eq!(status, ContextResetStatus::NoError),
+        Err(Error::RequiredExtensionUnavailable) => {}
+        Err(err) => panic!("Failed to query the reset status: {:?}", err),
+    }
+
+    device.destroy_context(&mut context).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_debug_context() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let context_descriptor = match device.create_context_descriptor(&ContextAttributes {
+        version: GLVersion::new(3, 0),
+        flags: ContextAttributeFlags::DEBUG,
+        ..ContextAttributes::default()
+    }) {
+        Ok(context_descriptor) => context_descriptor,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create context descriptor: {:?}", err),
+    };
+
+    let messages = Arc::new(Mutex::new(vec![]));
+    let messages_for_callback = messages.clone();
+    device.set_debug_message_callback(move |message| {
+        messages_for_callback.lock().unwrap().push(message.clone())
+    });
+    device.set_debug_message_filter(DebugMessageFilter {
+        min_severity: DebugMessageSeverity::Notification,
+        sources: Some(vec![DebugMessageSource::Api]),
+    });
+
+    let mut context = device.create_context(&context_descriptor, None).unwrap();
+    let actual_descriptor = device.context_descriptor(&context);
+    let actual_attributes = device.context_descriptor_attributes(&actual_descriptor);
+    assert!(actual_attributes
+        .flags
+        .contains(ContextAttributeFlags::DEBUG));
+
+    // Provoke an error, which the driver should report synchronously.
+    device.make_context_current(&context).unwrap();
+    let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+    unsafe {
+        gl.BindTexture(0xffff, 0);
+        assert_eq!(gl.GetError(), gl::INVALID_ENUM);
+    }
+
+    {
+        let messages = messages.lock().unwrap();
+        assert!(messages.iter().any(|message| {
+            message.source == DebugMessageSource::Api
+                && message.message_type == DebugMessageType::Error
+        }));
+        assert!(messages
+            .iter()
+            .all(|message| message.source == DebugMessageSource::Api));
+    }
+
+    device.clear_debug_message_callback();
+    device.destroy_context(&mut context).unwrap();
+}
+
+// Tests that context priorities and release behaviors are negotiated rather than failing.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_context_priority_and_release_behavior() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let version = match device.gl_api() {
+        GLApi::GL => GLVersion::new(3, 0),
+        GLApi::GLES => GLVersion::new(2, 0),
+    };
+    for &priority in &[ContextPriority::Low, ContextPriority::High] {
+        let attributes = ContextAttributes {
+            version,
+            flags: ContextAttributeFlags::empty(),
+            priority,
+            release_behavior: ContextReleaseBehavior::None,
+            samples: 0,
+        };
+        let context_descriptor = device.create_context_descriptor(&attributes).unwrap();
+        let requested_attributes = device.context_descriptor_attributes(&context_descriptor);
+        assert!(
+            requested_attributes.priority == priority
+                || requested_attributes.priority == ContextPriority::Medium
+        );
+
+        let mut context = device.create_context(&context_descriptor, None).unwrap();
+        let actual_descriptor = device.context_descriptor(&context);
+        let actual_attributes = device.context_descriptor_attributes(&actual_descriptor);
+        if requested_attributes.priority == ContextPriority::Medium {
+            assert_eq!(actual_attributes.priority, ContextPriority::Medium);
+        }
+        if requested_attributes.release_behavior == ContextReleaseBehavior::Flush {
+            assert_eq!(
+                actual_attributes.release_behavior,
+                ContextReleaseBehavior::Flush
+            );
+        }
+
+        // Contexts that don't flush on release must still be usable.
+        device.make_context_current(&context).unwrap();
+        device.make_no_context_current().unwrap();
+        device.destroy_context(&mut context).unwrap();
+    }
+}
+
+// Tests that multisampled surfaces are resolved before they're read from.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_multisampled_surface() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let context_descriptor = device
+        .create_context_descriptor(&ContextAttributes {
+            version: GLVersion::new(3, 0),
+            flags: ContextAttributeFlags::ALPHA,
+            samples: 4,
+            ..ContextAttributes::default()
+        })
+        .unwrap();
+    let requested_samples = device
+        .context_descriptor_attributes(&context_descriptor)
+        .samples;
+    assert!(requested_samples == 4 || requested_samples == 0);
+
+    let mut context = device.create_context(&context_descriptor, None).unwrap();
+    let surface = make_surface(&mut device, &context);
+    device
+        .bind_surface_to_context(&mut context, surface)
+        .unwrap();
+    device.make_context_current(&context).unwrap();
+    let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+
+    unsafe {
+        let framebuffer_object = context_fbo(&device, &context);
+        gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer_object);
+        gl.Viewport(0, 0, 640, 480);
+        let mut samples = 0;
+        gl.GetIntegerv(gl::SAMPLES, &mut samples);
+        check_gl(&gl);
+        // The sample count may have been clamped to what the implementation supports.
+        let actual_samples = device
+            .context_descriptor_attributes(&device.context_descriptor(&context))
+            .samples;
+        if actual_samples == 0 {
+            assert_eq!(samples, 0);
+        } else {
+            assert!(samples > 1 && samples <= actual_samples as GLint);
+        }
+
+        clear(&gl, &[0, 0, 255, 255]);
+        check_gl(&gl);
+
+        // Unbinding resolves the samples, so the surface texture sees the rendering.
+        let surface = device
+            .unbind_surface_from_context(&mut context)
+            .unwrap()
+            .unwrap();
+        let surface_texture = device
+            .create_surface_texture(&mut context, surface)
+            .unwrap();
+        let texture_framebuffer_object = make_fbo(
+            &gl,
+            device.surface_gl_texture_target(),
+            device.surface_texture_object(&surface_texture),
+        );
+        gl.BindFramebuffer(gl::FRAMEBUFFER, texture_framebuffer_object);
+        check_gl(&gl);
+        assert_eq!(get_pixel_from_bottom_row(&gl), [0, 0, 255, 255]);
+
+        gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+        gl.DeleteFramebuffers(1, &texture_framebuffer_object);
+        let mut surface = device
+            .destroy_surface_texture(&mut context, surface_texture)
+            .unwrap();
+        device.destroy_surface(&mut context, &mut surface).unwrap();
+    }
+
+    device.destroy_context(&mut context).unwrap();
+}
+
+// Tests that generic surfaces can be created in every supported format, and only those.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_surface_formats() {
+    let mut env = match BasicEnvironment::new() {
+        None => return,
+        Some(env) => env,
+    };
+
+    let supported_formats = env.device.supported_surface_formats().unwrap();
+    assert!(supported_formats.contains(&SurfaceFormat::RGBA8));
+
+    unsafe {
+        let mut main_surface = env
+            .device
+            .unbind_surface_from_context(&mut env.context)
+            .unwrap()
+            .unwrap();
+
+        for &format in &SurfaceFormat::ALL {
+            let surface = env.device.create_surface(
+                &env.context,
+                SurfaceAccess::GPUOnly,
+                SurfaceType::Generic {
+                    size: Size2D::new(640, 480),
+                    format,
+                },
+            );
+            let surface = match surface {
+                Ok(surface) => surface,
+                Err(Error::UnsupportedSurfaceFormat) => {
+                    assert!(!supported_formats.contains(&format));
+                    continue;
+                }
+                Err(err) => panic!("Failed to create {:?} surface: {:?}", format, err),
+            };
+            assert!(supported_formats.contains(&format));
+            assert_eq!(env.device.surface_info(&surface).format, format);
+
+            env.device
+                .bind_surface_to_context(&mut env.context, surface)
+                .unwrap();
+            env.gl
+                .BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&env.device, &env.context));
+            clear(&env.gl, &[255, 0, 0, 255]);
+            check_gl(&env.gl);
+
+            // Missing channels read back as 0, except for alpha, which reads back as 1.
+            let surface = env
+                .device
+                .unbind_surface_from_context(&mut env.context)
+                .unwrap()
+                .unwrap();
+            let surface_texture = env
+                .device
+                .create_surface_texture(&mut env.context, surface)
+                .unwrap();
+            let texture_framebuffer_object = make_fbo(
+                &env.gl,
+                env.device.surface_gl_texture_target(),
+                env.device.surface_texture_object(&surface_texture),
+            );
+            // OpenGL ES can only read floating-point framebuffers as floats.
+            if format != SurfaceFormat::RGBA16F || env.device.gl_api() == GLApi::GL {
+                assert_eq!(get_pixel_from_bottom_row(&env.gl), [255, 0, 0, 255]);
+            }
+
+            env.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+            env.gl.DeleteFramebuffers(1, &texture_framebuffer_object);
+            let mut surface = env
+                .device
+                .destroy_surface_texture(&mut env.context, surface_texture)
+                .unwrap();
+            env.device
+                .destroy_surface(&mut env.context, &mut surface)
+                .unwrap();
+        }
+
+        env.device
+            .destroy_surface(&mut env.context, &mut main_surface)
+            .unwrap();
+        env.device.destroy_context(&mut env.context).unwrap();
+    }
+}
+
+// Tests that every enumerated context descriptor satisfies the requested attributes, and that
+// choosing a descriptor follows the preferences.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_context_descriptor_enumeration() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let attributes = ContextAttributes {
+        version: GLVersion::new(3, 0),
+        flags: ContextAttributeFlags::ALPHA | ContextAttributeFlags::DEPTH,
+        ..ContextAttributes::default()
+    };
+    let descriptors = device.enumerate_context_descriptors(&attributes).unwrap();
+    assert!(!descriptors.is_empty());
+    let pixel_formats: Vec<PixelFormatInfo> = descriptors
+        .iter()
+        .map(|descriptor| device.context_descriptor_pixel_format(descriptor))
+        .collect();
+    for pixel_format in &pixel_formats {
+        assert_eq!(
+            (
+                pixel_format.red_size,
+                pixel_format.green_size,
+                pixel_format.blue_size
+            ),
+            (8, 8, 8)
+        );
+        assert!(pixel_format.alpha_size > 0);
+        assert!(pixel_format.depth_size > 0);
+    }
+
+    // The default descriptor avoids caveats whenever it can.
+    let descriptor = device.create_context_descriptor(&attributes).unwrap();
+    let pixel_format = device.context_descriptor_pixel_format(&descriptor);
+    if pixel_formats
+        .iter()
+        .any(|pixel_format| pixel_format.caveat == PixelFormatCaveat::None)
+    {
+        assert_eq!(pixel_format.caveat, PixelFormatCaveat::None);
+    }
+
+    let preferences = PixelFormatPreferences {
+        samples: Some(4),
+        native_renderable: Some(true),
+        ..PixelFormatPreferences::default()
+    };
+    let descriptor = device
+        .choose_context_descriptor(&attributes, &preferences)
+        .unwrap();
+    let best_rank = pixel_formats
+        .iter()
+        .map(|pixel_format| preferences.rank(pixel_format))
+        .min()
+        .unwrap();
+    assert_eq!(
+        preferences.rank(&device.context_descriptor_pixel_format(&descriptor)),
+        best_rank
+    );
+
+    // Any enumerated descriptor can be used to create a context.
+    let descriptor = descriptors.last().unwrap();
+    let mut context = device.create_context(descriptor, None).unwrap();
+    let actual_attributes =
+        device.context_descriptor_attributes(&device.context_descriptor(&context));
+    assert!(actual_attributes
+        .flags
+        .contains(ContextAttributeFlags::ALPHA | ContextAttributeFlags::DEPTH));
+    device.make_context_current(&context).unwrap();
+    device.destroy_context(&mut context).unwrap();
+}
+
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_context_negotiation() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    // No implementation supports this version, so negotiation must fall back.
+    let unsupported_attributes = ContextAttributes {
+        version: GLVersion::new(9, 9),
+        flags: ContextAttributeFlags::ALPHA,
+        ..ContextAttributes::default()
+    };
+    // Not every implementation supports no-error contexts, and none combine them with debug
+    // contexts, so at least one of those flags may need to be dropped.
+    let supported_attributes = ContextAttributes {
+        version: GLVersion::new(3, 0),
+        flags: ContextAttributeFlags::ALPHA
+            | ContextAttributeFlags::DEPTH
+            | ContextAttributeFlags::DEBUG
+            | ContextAttributeFlags::NO_ERROR,
+        ..ContextAttributes::default()
+    };
+    let negotiation = ContextNegotiation {
+        candidates: vec![unsupported_attributes, supported_attributes],
+        optional_flags: vec![
+            ContextAttributeFlags::NO_ERROR,
+            ContextAttributeFlags::DEBUG,
+        ],
+    };
+
+    let (descriptor, report) = device.negotiate_context_descriptor(&negotiation).unwrap();
+    assert_eq!(report.candidate_index, 1);
+    assert_eq!(report.failed_attempts[0].0, unsupported_attributes);
+    assert_eq!(
+        report.failed_attempts.len(),
+        1 + report.dropped_flags.bits().count_ones() as usize
+    );
+    assert_eq!(
+        report.attributes.flags,
+        supported_attributes.flags - report.dropped_flags
+    );
+    assert!(!report
+        .attributes
+        .flags
+        .contains(ContextAttributeFlags::DEBUG | ContextAttributeFlags::NO_ERROR));
+
+    let mut context = device.create_context(&descriptor, None).unwrap();
+    let actual_attributes =
+        device.context_descriptor_attributes(&device.context_descriptor(&context));
+    assert!(actual_attributes
+        .flags
+        .contains(ContextAttributeFlags::ALPHA | ContextAttributeFlags::DEPTH));
+    device.destroy_context(&mut context).unwrap();
+
+    // If nothing can be satisfied, the error of the most preferred candidate is returned.
+    let negotiation = ContextNegotiation {
+        candidates: vec![unsupported_attributes],
+        optional_flags: vec![],
+    };
+    assert!(device.negotiate_context_descriptor(&negotiation).is_err());
+}
+
+// Tests that compatibility profiles after OpenGL 3.0 are available wherever the driver provides
+// them.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_modern_compatibility_profile() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+    if device.gl_api() != GLApi::GL {
+        return;
+    }
+
+    for &version in &[GLVersion::new(3, 3), GLVersion::new(4, 1)] {
+        let attributes = ContextAttributes {
+            version,
+            flags: ContextAttributeFlags::COMPATIBILITY_PROFILE,
+            ..ContextAttributes::default()
+        };
+        let descriptor = match device.create_context_descriptor(&attributes) {
+            Ok(descriptor) => descriptor,
+            Err(Error::UnsupportedGLProfile) => continue,
+            Err(err) => panic!("Context descriptor creation failed: {:?}", err),
+        };
+
+        // The descriptor was probed, so creating the context must succeed.
+        let mut context = device.create_context(&descriptor, None).unwrap();
+        let actual_attributes =
+            device.context_descriptor_attributes(&device.context_descriptor(&context));
+        assert!(actual_attributes
+            .flags
+            .contains(ContextAttributeFlags::COMPATIBILITY_PROFILE));
+        assert!(
+            actual_attributes.version.major > version.major
+                || actual_attributes.version.major == version.major
+                    && actual_attributes.version.minor >= version.minor
+        );
+        device.destroy_context(&mut context).unwrap();
+    }
+}
+
+// Tests that a context created without a config can render to surfaces of different formats.
+#[cfg_attr(not(feature = "sm-test"), test)]
+pub fn test_no_config_context() {
+    let connection = Connection::new().unwrap();
+    let adapter = connection
+        .create_low_power_adapter()
+        .expect("Failed to create adapter!");
+    let mut device = match connection.create_device(&adapter) {
+        Ok(device) => device,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Failed to create device: {:?}", err),
+    };
+
+    let context_descriptor = match device.create_context_descriptor(&ContextAttributes {
+        version: GLVersion::new(3, 0),
+        flags: ContextAttributeFlags::NO_CONFIG,
+        ..ContextAttributes::default()
+    }) {
+        Ok(context_descriptor) => context_descriptor,
+        Err(Error::RequiredExtensionUnavailable) => return,
+        Err(err) => panic!("Context descriptor creation failed: {:?}", err),
+    };
+    let mut context = device.create_context(&context_descriptor, None).unwrap();
+    let actual_attributes =
+        device.context_descriptor_attributes(&device.context_descriptor(&context));
+    assert!(actual_attributes
+        .flags
+        .contains(ContextAttributeFlags::NO_CONFIG));
+
+    device.make_context_current(&context).unwrap();
+    let gl = Gl::load_with(|symbol| device.get_proc_address(&context, symbol));
+    let supported_formats = device.supported_surface_formats().unwrap();
+    for &format in &[SurfaceFormat::RGBA8, SurfaceFormat::RGBA16F] {
+        if !supported_formats.contains(&format) {
+            continue;
+        }
+
+        let surface = device
+            .create_surface(
+                &context,
+                SurfaceAccess::GPUOnly,
+                SurfaceType::Generic {
+                    size: Size2D::new(640, 480),
+                    format,
+                },
+            )
+            .unwrap();
+        device
+            .bind_surface_to_context(&mut context, surface)
+            .unwrap();
+        device.make_context_current(&context).unwrap();
+
+        unsafe {
+            gl.BindFramebuffer(gl::FRAMEBUFFER, context_fbo(&device, &context));
+            gl.Viewport(0, 0, 640, 480);
+            clear(&gl, &[0, 255, 0, 255]);
+            check_gl(&gl);
+            // OpenGL ES can only read floating-point framebuffers as floats.
+            if format != SurfaceFormat::RGBA16F || device.gl_api() == GLApi::GL {
+                assert_eq!(get_pixel_from_bottom_row(&gl), [0, 255, 0, 255]);
+            }
+        }
+
+        let mut surface = device
+            .unbind_surface_from_context(&mut context)
+            .unwrap()
+            .unwrap();
+        device.destroy_surface(&mut context, &mut surface).unwrap();
+    }
+
+    device.destroy_context(&mut context).unwrap();
+}
+
 fn context_fbo(device: &Device, context: &Context) -> GLuint {
     device
         .context_surface_info(context)
@@ -887,6 +1944,7 @@ fn make_surface(device: &mut Device, context: &Context) -> Surface {
             SurfaceAccess::GPUOnly,
             SurfaceType::Generic {
                 size: Size2D::new(640, 480),
+                format: SurfaceFormat::RGBA8,
             },
         )
         .unwrap()
@@ -965,6 +2023,7 @@ impl BasicEnvironment {
             .create_context_descriptor(&ContextAttributes {
                 version: GLVersion::new(3, 0),
                 flags: ContextAttributeFlags::empty(),
+                ..ContextAttributes::default()
             })
             .unwrap();
 